@@ -9,6 +9,15 @@ BINARY="./target/release/mobile-device-mcp-server"
 DEVICE_ID="${DEVICE_ID:-emulator-5554}"
 PLATFORM="${PLATFORM:-android}"
 
+# MCP handshake sent ahead of every request (tool calls are rejected before initialize)
+INIT_REQUEST='{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test-script","version":"1.0"}}}'
+INITIALIZED_NOTIFICATION='{"jsonrpc":"2.0","method":"notifications/initialized"}'
+
+# Send the request read from stdin after the handshake and print only its response
+mcp_request() {
+    { echo "$INIT_REQUEST"; echo "$INITIALIZED_NOTIFICATION"; cat; } | $BINARY | tail -n 1
+}
+
 echo "=================================================="
 echo "Mobile Device MCP - Comprehensive Tool Testing"
 echo "=================================================="
@@ -39,7 +48,7 @@ run_test() {
     echo "Test $TOTAL_TESTS: $test_name"
     echo "-------------------"
 
-    result=$(echo "$test_cmd" | $BINARY 2>&1)
+    result=$(echo "$test_cmd" | mcp_request 2>&1)
 
     if echo "$result" | grep -q "\"error\""; then
         echo "❌ FAILED - Error in response"
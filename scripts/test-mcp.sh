@@ -8,6 +8,15 @@ set -e
 BINARY="./target/release/mobile-device-mcp-server"
 SETTINGS='{"debug": true, "platform": "auto"}'
 
+# MCP handshake sent ahead of every request (tool calls are rejected before initialize)
+INIT_REQUEST='{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test-script","version":"1.0"}}}'
+INITIALIZED_NOTIFICATION='{"jsonrpc":"2.0","method":"notifications/initialized"}'
+
+# Send the request read from stdin after the handshake and print only its response
+mcp_request() {
+    { echo "$INIT_REQUEST"; echo "$INITIALIZED_NOTIFICATION"; cat; } | $BINARY | tail -n 1
+}
+
 echo "🧪 Mobile Device MCP Server Test Suite"
 echo "================================"
 
@@ -31,7 +40,7 @@ echo
 # Test 2: List tools
 echo "📋 Test 2: List Tools"
 TOOLS=$(echo '{"jsonrpc": "2.0", "id": 2, "method": "tools/list", "params": {}}' | \
-    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY | \
+    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request | \
     jq -r '.result.tools | length')
 echo "✅ Found $TOOLS tools"
 echo
@@ -39,7 +48,7 @@ echo
 # Test 3: List devices
 echo "📋 Test 3: List Available Devices"
 DEVICES=$(echo '{"jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": {"name": "mobile_list_available_devices", "arguments": {}}}' | \
-    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY | \
+    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request | \
     jq -r '.result.content[0].text')
 echo "Devices found:"
 echo "$DEVICES"
@@ -65,7 +74,7 @@ if [ -n "$DEVICE_ID" ]; then
     # Test 4: Take screenshot
     echo "📋 Test 4: Take Screenshot"
     SCREENSHOT_RESULT=$(echo "{\"jsonrpc\": \"2.0\", \"id\": 4, \"method\": \"tools/call\", \"params\": {\"name\": \"mobile_take_screenshot\", \"arguments\": {\"device_id\": \"$DEVICE_ID\", \"platform\": \"android\"}}}" | \
-        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY)
+        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request)
 
     if echo "$SCREENSHOT_RESULT" | jq -e '.result.content[0].data' > /dev/null 2>&1; then
         SCREENSHOT_SIZE=$(echo "$SCREENSHOT_RESULT" | jq -r '.result.content[0].data' | wc -c)
@@ -79,7 +88,7 @@ if [ -n "$DEVICE_ID" ]; then
     # Test 5: Tap screen
     echo "📋 Test 5: Tap Screen (center: 500, 500)"
     TAP_RESULT=$(echo "{\"jsonrpc\": \"2.0\", \"id\": 5, \"method\": \"tools/call\", \"params\": {\"name\": \"mobile_tap_screen\", \"arguments\": {\"device_id\": \"$DEVICE_ID\", \"platform\": \"android\", \"x\": 500, \"y\": 500}}}" | \
-        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY)
+        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request)
 
     if echo "$TAP_RESULT" | jq -e '.result' > /dev/null 2>&1; then
         TAP_MSG=$(echo "$TAP_RESULT" | jq -r '.result.content[0].text')
@@ -93,7 +102,7 @@ if [ -n "$DEVICE_ID" ]; then
     # Test 6: Type text
     echo "📋 Test 6: Type Text"
     TYPE_RESULT=$(echo "{\"jsonrpc\": \"2.0\", \"id\": 6, \"method\": \"tools/call\", \"params\": {\"name\": \"mobile_type_text\", \"arguments\": {\"device_id\": \"$DEVICE_ID\", \"platform\": \"android\", \"text\": \"Hello MCP!\"}}}" | \
-        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY)
+        MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request)
 
     if echo "$TYPE_RESULT" | jq -e '.result' > /dev/null 2>&1; then
         TYPE_MSG=$(echo "$TYPE_RESULT" | jq -r '.result.content[0].text')
@@ -111,7 +120,7 @@ echo "================================"
 # Test invalid method
 echo "📋 Error Handling Test: Invalid Method"
 ERROR_TEST=$(echo '{"jsonrpc": "2.0", "id": 99, "method": "invalid/method", "params": {}}' | \
-    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" $BINARY)
+    MOBILE_DEVICE_MCP_SETTINGS="$SETTINGS" mcp_request)
 
 if echo "$ERROR_TEST" | jq -e '.error' > /dev/null 2>&1; then
     ERROR_MSG=$(echo "$ERROR_TEST" | jq -r '.error.message')
@@ -5,6 +5,15 @@ set -e
 
 BINARY="./target/release/mobile-device-mcp-server"
 
+# MCP handshake sent ahead of every request (tool calls are rejected before initialize)
+INIT_REQUEST='{"jsonrpc":"2.0","id":0,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test-script","version":"1.0"}}}'
+INITIALIZED_NOTIFICATION='{"jsonrpc":"2.0","method":"notifications/initialized"}'
+
+# Send the request read from stdin after the handshake and print only its response
+mcp_request() {
+    { echo "$INIT_REQUEST"; echo "$INITIALIZED_NOTIFICATION"; cat; } | $BINARY | tail -n 1
+}
+
 echo "Testing Mobile Device MCP Tools..."
 echo "=================================="
 
@@ -19,7 +28,7 @@ fi
 echo ""
 echo "Test 1: List Available Devices"
 echo "-------------------------------"
-echo '{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"mobile_device_mcp_list_available_devices","arguments":{}}}' | $BINARY
+echo '{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"mobile_device_mcp_list_available_devices","arguments":{}}}' | mcp_request
 
 # Get device ID from environment or use default
 DEVICE_ID="${DEVICE_ID:-emulator-5554}"
@@ -32,19 +41,19 @@ echo ""
 # Test 2: Get screen size
 echo "Test 2: Get Screen Size"
 echo "-----------------------"
-echo "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_get_screen_size\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | $BINARY
+echo "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_get_screen_size\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | mcp_request
 
 # Test 3: Take screenshot
 echo ""
 echo "Test 3: Take Screenshot"
 echo "-----------------------"
-echo "{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_take_screenshot\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | $BINARY | head -100
+echo "{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_take_screenshot\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | mcp_request | head -100
 
 # Test 4: Save screenshot
 echo ""
 echo "Test 4: Save Screenshot to File"
 echo "--------------------------------"
-echo "{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_save_screenshot\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\",\"output_path\":\"/tmp/test-screenshot.png\"}}}" | $BINARY
+echo "{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_save_screenshot\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\",\"output_path\":\"/tmp/test-screenshot.png\"}}}" | mcp_request
 
 # Check if screenshot was saved
 if [ -f "/tmp/test-screenshot.png" ]; then
@@ -58,7 +67,7 @@ fi
 echo ""
 echo "Test 5: List Elements on Screen"
 echo "--------------------------------"
-echo "{\"jsonrpc\":\"2.0\",\"id\":5,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_list_elements_on_screen\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | $BINARY
+echo "{\"jsonrpc\":\"2.0\",\"id\":5,\"method\":\"tools/call\",\"params\":{\"name\":\"mobile_list_elements_on_screen\",\"arguments\":{\"device_id\":\"$DEVICE_ID\",\"platform\":\"$PLATFORM\"}}}" | mcp_request
 
 echo ""
 echo "=================================="
@@ -0,0 +1,306 @@
+// mobile-mcp-zed-extension/src/completion/handlers.rs
+// Argument Completion
+//
+// Implements "completion/complete" so clients can suggest argument values
+// while the user types them, instead of the user recalling device serials
+// and bundle identifiers from memory:
+//
+// - device_id:           connected devices (optionally of the context's platform)
+//                        and the device aliases from the settings
+// - app_id / package:    apps installed on the device from the context
+// - button:              buttons of the device's platform
+// - orientation:         portrait, landscape
+// - other enum arguments of a tool (e.g., platform): the schema's values
+
+use serde_json::{json, Value};
+
+use crate::devices::{DeviceSelection, Orientation};
+use crate::mcp::{CompleteParams, CompletionReference};
+use crate::prompts::get_all_prompts;
+use crate::resources::get_resource_templates;
+use crate::tools::find_tool;
+use crate::MobileDeviceManager;
+
+/// Most values a completion result may carry (MCP limit)
+pub const MAX_VALUES: usize = 100;
+
+/// Result type for completion handlers
+///
+/// Success returns the "completion/complete" result. Errors are messages for
+/// an invalid params response (unknown prompt, tool or resource template).
+pub type CompletionResult = Result<Value, String>;
+
+/// Suggest values for a prompt, resource template or tool argument
+///
+/// Arguments the reference doesn't declare, or that have no known values,
+/// complete to an empty list.
+///
+/// # Arguments
+/// * `manager` - Mobile device manager (used to list devices and apps)
+/// * `params` - The "completion/complete" params
+/// * `selection` - Device aliases of the session
+/// * `default_platform` - Platform to assume when the device's is unknown
+///
+/// # Example Response
+/// ```json
+/// {"completion": {"values": ["com.example.app"], "total": 1, "hasMore": false}}
+/// ```
+pub fn handle_complete(
+    manager: &mut MobileDeviceManager,
+    params: &CompleteParams,
+    selection: &DeviceSelection,
+    default_platform: &str,
+) -> CompletionResult {
+    let Some(declared) = declared_values(&params.reference, &params.argument.name)? else {
+        return Ok(completion_result(&[], &params.argument.value));
+    };
+
+    let context = &params.context.arguments;
+    let device_id = context.get("device_id").filter(|id| !id.is_empty());
+    let platform = context
+        .get("platform")
+        .filter(|p| manager.platforms().contains(&p.as_str()));
+
+    let candidates: Vec<String> = match params.argument.name.as_str() {
+        "device_id" => manager
+            .list_all_devices(platform.map(String::as_str).unwrap_or("auto"))
+            .into_iter()
+            .map(|device| device.id)
+            .chain(selection.aliases())
+            .collect(),
+        "app_id" | "package" => {
+            // Without a device in the context, only a single connected device is unambiguous
+            let devices = manager.list_all_devices("auto");
+            let device = match device_id {
+                Some(id) => devices.into_iter().find(|device| &device.id == id),
+                None if devices.len() == 1 => devices.into_iter().next(),
+                None => None,
+            };
+            match device {
+                Some(device) => {
+                    let platform = platform.unwrap_or(&device.platform);
+                    let mut apps: Vec<String> = manager
+                        .list_apps(&device.id, platform)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|app| app.package_name)
+                        .collect();
+                    apps.sort();
+                    apps
+                }
+                None => Vec::new(),
+            }
+        }
+        "button" => {
+            let platform = match (platform, device_id) {
+                (Some(platform), _) => platform.clone(),
+                (None, Some(id)) => manager
+                    .list_all_devices("auto")
+                    .into_iter()
+                    .find(|device| &device.id == id)
+                    .map(|device| device.platform)
+                    .unwrap_or_else(|| default_platform.to_string()),
+                (None, None) => default_platform.to_string(),
+            };
+            manager
+                .buttons(&platform)
+                .iter()
+                .map(|button| button.name().to_string())
+                .collect()
+        }
+        "orientation" => Orientation::ALL
+            .iter()
+            .map(|orientation| orientation.name().to_string())
+            .collect(),
+        _ => declared,
+    };
+
+    Ok(completion_result(&candidates, &params.argument.value))
+}
+
+/// Look up the argument on the reference
+///
+/// # Returns
+/// None if the reference doesn't declare the argument, otherwise the values
+/// its schema enumerates (empty when it doesn't enumerate any)
+fn declared_values(
+    reference: &CompletionReference,
+    argument: &str,
+) -> Result<Option<Vec<String>>, String> {
+    match reference {
+        CompletionReference::Prompt { name } => {
+            let prompt = get_all_prompts()
+                .into_iter()
+                .find(|p| &p.name == name)
+                .ok_or_else(|| format!("Unknown prompt: {}", name))?;
+            Ok(prompt
+                .arguments
+                .iter()
+                .any(|a| a.name == argument)
+                .then(Vec::new))
+        }
+        CompletionReference::Resource { uri } => {
+            let template = get_resource_templates()
+                .into_iter()
+                .find(|t| &t.uri_template == uri)
+                .ok_or_else(|| format!("Unknown resource template: {}", uri))?;
+            Ok(template
+                .uri_template
+                .contains(&format!("{{{}}}", argument))
+                .then(Vec::new))
+        }
+        CompletionReference::Tool { name } => {
+            let tool = find_tool(name).ok_or_else(|| format!("Unknown tool: {}", name))?;
+            Ok(tool.input_schema["properties"]
+                .get(argument)
+                .map(|property| {
+                    property["enum"]
+                        .as_array()
+                        .map(|values| {
+                            values
+                                .iter()
+                                .filter_map(Value::as_str)
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                }))
+        }
+    }
+}
+
+/// Build the completion result for the candidates matching the typed value
+///
+/// Matching is case-insensitive. Candidates starting with the value come
+/// first, followed by those containing it elsewhere (so "chrome" finds
+/// "com.android.chrome").
+pub fn completion_result(candidates: &[String], value: &str) -> Value {
+    let value = value.to_lowercase();
+    let (mut matches, rest): (Vec<&String>, Vec<&String>) = candidates
+        .iter()
+        .filter(|c| c.to_lowercase().contains(&value))
+        .partition(|c| c.to_lowercase().starts_with(&value));
+    matches.extend(rest);
+
+    let values: Vec<&String> = matches.iter().take(MAX_VALUES).copied().collect();
+    json!({
+        "completion": {
+            "values": values,
+            "total": matches.len(),
+            "hasMore": matches.len() > MAX_VALUES
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::BackendRegistry;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_prefix_matches_come_first() {
+        let candidates = strings(&["com.android.chrome", "chrome.beta", "org.mozilla.firefox"]);
+        let result = completion_result(&candidates, "Chrome");
+        assert_eq!(
+            result["completion"]["values"],
+            json!(["chrome.beta", "com.android.chrome"])
+        );
+        assert_eq!(result["completion"]["total"], 2);
+        assert_eq!(result["completion"]["hasMore"], false);
+    }
+
+    #[test]
+    fn test_result_is_capped() {
+        let candidates: Vec<String> = (0..150).map(|i| format!("com.app{}", i)).collect();
+        let result = completion_result(&candidates, "");
+        assert_eq!(
+            result["completion"]["values"].as_array().unwrap().len(),
+            MAX_VALUES
+        );
+        assert_eq!(result["completion"]["total"], 150);
+        assert_eq!(result["completion"]["hasMore"], true);
+    }
+
+    fn complete(backends: BackendRegistry, tool: &str, argument: &str, context: Value) -> Value {
+        let params: CompleteParams = serde_json::from_value(json!({
+            "ref": {"type": "ref/tool", "name": format!("mobile_device_mcp_{}", tool)},
+            "argument": {"name": argument, "value": ""},
+            "context": {"arguments": context}
+        }))
+        .unwrap();
+        let selection =
+            DeviceSelection::new([("pixel".to_string(), "emulator-5554".to_string())].into());
+        let mut manager = MobileDeviceManager::with_backends(backends);
+        let result = handle_complete(&mut manager, &params, &selection, "android").unwrap();
+        result["completion"]["values"].clone()
+    }
+
+    #[test]
+    fn test_values_come_from_the_enums_and_aliases() {
+        let mut ios = BackendRegistry::empty();
+        ios.register(Box::new(crate::devices::IOSDeviceManager::new()));
+        assert_eq!(
+            complete(ios, "press_button", "button", json!({"platform": "ios"})),
+            json!(["home", "power", "volume_up", "volume_down"])
+        );
+        assert_eq!(
+            complete(
+                BackendRegistry::empty(),
+                "set_orientation",
+                "orientation",
+                json!({})
+            ),
+            json!(["portrait", "landscape"])
+        );
+        // No devices are connected, but the alias can still be used
+        assert_eq!(
+            complete(
+                BackendRegistry::empty(),
+                "press_button",
+                "device_id",
+                json!({})
+            ),
+            json!(["pixel"])
+        );
+    }
+
+    #[test]
+    fn test_declared_values() {
+        let tool = |name: &str| CompletionReference::Tool {
+            name: format!("mobile_device_mcp_{}", name),
+        };
+        assert_eq!(
+            declared_values(&tool("set_orientation"), "platform").unwrap(),
+            Some(strings(&["android", "ios"]))
+        );
+        assert_eq!(
+            declared_values(&tool("launch_app"), "app_id").unwrap(),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            declared_values(&tool("launch_app"), "button").unwrap(),
+            None
+        );
+        assert!(declared_values(&tool("no_such_tool"), "app_id").is_err());
+
+        let prompt = CompletionReference::Prompt {
+            name: "explore_app".to_string(),
+        };
+        assert_eq!(
+            declared_values(&prompt, "package").unwrap(),
+            Some(Vec::new())
+        );
+
+        let resource = CompletionReference::Resource {
+            uri: "device://{device_id}/info".to_string(),
+        };
+        assert_eq!(
+            declared_values(&resource, "device_id").unwrap(),
+            Some(Vec::new())
+        );
+    }
+}
@@ -0,0 +1,6 @@
+// mobile-mcp-zed-extension/src/completion/mod.rs
+// Completion module suggesting argument values
+
+pub mod handlers;
+
+pub use handlers::*;
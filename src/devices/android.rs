@@ -1,12 +1,23 @@
 // mobile-mcp-zed-extension/src/devices/android.rs
 // Comprehensive Android Device Management Module with mobile-mcp features
 
+use crate::mcp::logging::{self, LogLevel};
+use crate::recording;
 use crate::types::DeviceInfo;
-use adb_client::{ADBDeviceExt, ADBServer, DeviceState};
+use adb_client::{ADBDeviceExt, ADBServer, ADBServerDevice, DeviceState};
 use quick_xml::events::Event;
 use quick_xml::Reader as XmlReader;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::backend::{
+    Button, DeviceBackend, InstalledApp, Orientation, ScreenElement, ScreenElementRect, ScreenSize,
+};
+use super::{cancel, Progress};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -24,109 +35,344 @@ pub enum AndroidDeviceType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
-pub struct InstalledApp {
-    pub package_name: String,
-    pub app_name: String,
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub struct ScreenElement {
-    pub element_type: String,
-    pub text: Option<String>,
-    pub label: String,
-    pub rect: ScreenElementRect,
-    pub focused: Option<bool>,
-    pub identifier: Option<String>,
+/// Reader reporting how much of a file push has been sent (0-40%)
+struct ProgressReader<'a, R> {
+    inner: R,
+    sent: u64,
+    total: u64,
+    progress: Progress<'a>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub struct ScreenElementRect {
-    pub x: i32,
-    pub y: i32,
-    pub width: i32,
-    pub height: i32,
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // Failing the read aborts the push
+        cancel::check().map_err(|e| std::io::Error::new(std::io::ErrorKind::Interrupted, e))?;
+        let read = self.inner.read(buf)?;
+        self.sent += read as u64;
+        if self.total > 0 && read > 0 {
+            let percent = 40.0 * self.sent as f64 / self.total as f64;
+            (self.progress)(percent, "Pushing APK to device");
+        }
+        Ok(read)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub struct ScreenSize {
-    pub width: u32,
-    pub height: u32,
-    pub scale: f32,
+/// Output of a shell command started with `echo $$; exec ...`
+///
+/// The first line is the PID of the remote process. Once it arrives, a
+/// cancellation hook is registered that kills the process, which ends the
+/// command and unblocks the adb read.
+struct ShellOutput {
+    device_id: String,
+    /// First line while it is still incomplete
+    header: Option<Vec<u8>>,
+    output: Vec<u8>,
+    _cancel_hook: Option<cancel::CancelHook>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub enum SwipeDirection {
-    Up,
-    Down,
-    Left,
-    Right,
+impl ShellOutput {
+    fn new(device_id: &str) -> Self {
+        Self {
+            device_id: device_id.to_string(),
+            header: Some(Vec::new()),
+            output: Vec::new(),
+            _cancel_hook: None,
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        // The command ended before the PID line was complete
+        if let Some(header) = self.header.take() {
+            return header;
+        }
+        self.output
+    }
+
+    fn on_header(&mut self, header: Vec<u8>) {
+        let line = String::from_utf8_lossy(&header);
+        let pid = line.trim();
+        if pid.is_empty() || !pid.bytes().all(|b| b.is_ascii_digit()) {
+            // Not our PID line, keep it as output
+            self.output.extend_from_slice(&header);
+            self.output.push(b'\n');
+            return;
+        }
+
+        let device_id = self.device_id.clone();
+        let pid = pid.to_string();
+        self._cancel_hook = cancel::on_cancel(move || {
+            // Runs on the cancelling thread: don't block it on adb
+            std::thread::spawn(move || {
+                let mut server = ADBServer::default();
+                if let Ok(mut device) = server.get_device_by_name(&device_id) {
+                    let _ = device.shell_command(&["kill", &pid], &mut std::io::sink());
+                }
+            });
+        });
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub enum Button {
-    Back,
-    Home,
-    Menu,
-    Power,
-    Camera,
-    VolumeUp,
-    VolumeDown,
-    Enter,
-    DpadCenter,
-    DpadUp,
-    DpadDown,
-    DpadLeft,
-    DpadRight,
+impl Write for ShellOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.header.as_mut() {
+            Some(header) => match buf.iter().position(|&b| b == b'\n') {
+                Some(end) => {
+                    header.extend_from_slice(&buf[..end]);
+                    let header = self.header.take().unwrap_or_default();
+                    self.on_header(header);
+                    self.output.extend_from_slice(&buf[end + 1..]);
+                }
+                None => header.extend_from_slice(buf),
+            },
+            None => self.output.extend_from_slice(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)]
-pub enum Orientation {
-    Portrait,
-    Landscape,
+// ============================================================================
+// Device Sessions
+// ============================================================================
+
+/// What a session knows about its device, queried on first use
+#[derive(Debug, Clone, Default)]
+struct Capabilities {
+    /// Screen size in pixels, with density / 160 as scale
+    screen_size: Option<ScreenSize>,
+    density: Option<u32>,
+    /// Displays known to SurfaceFlinger
+    display_ids: Option<Vec<String>>,
+    /// Display screenshots are taken of on multi-display devices
+    active_display: Option<String>,
+    sdk_level: Option<u32>,
+    /// All installed packages
+    packages: Option<Vec<String>>,
+    /// Packages the user installed (`pm list packages -3`)
+    third_party_apps: Option<Vec<InstalledApp>>,
+    /// Apps with a launcher activity
+    launcher_apps: Option<Vec<InstalledApp>>,
+    features: Option<Vec<String>>,
+}
+
+/// Persistent state of one device, shared by every request for it
+///
+/// Keeps an idle adb connection for the next request and caches the device's
+/// capabilities. Installing or uninstalling forgets the packages, rotating
+/// forgets the screen size. The session ends when the device disappears from
+/// the device list or reconnects (an emulator restarting keeps its serial
+/// but gets a new adb transport id). A command that loses the device forgets
+/// everything cached.
+#[derive(Default)]
+pub struct AndroidSession {
+    /// adb transport the session belongs to, if known
+    transport_id: Mutex<Option<u32>>,
+    connection: Mutex<Option<ADBServerDevice>>,
+    capabilities: Mutex<Capabilities>,
+}
+
+impl AndroidSession {
+    /// Take the idle connection (a concurrent request opens its own)
+    fn acquire(&self) -> Option<ADBServerDevice> {
+        lock(&self.connection).take()
+    }
+
+    /// Keep a connection for the next request
+    fn release(&self, device: ADBServerDevice) {
+        let mut connection = lock(&self.connection);
+        if connection.is_none() {
+            *connection = Some(device);
+        }
+    }
+
+    fn capabilities(&self) -> MutexGuard<'_, Capabilities> {
+        lock(&self.capabilities)
+    }
+
+    /// Forget the package lists after installing or uninstalling
+    fn forget_packages(&self) {
+        let mut capabilities = self.capabilities();
+        capabilities.packages = None;
+        capabilities.third_party_apps = None;
+        capabilities.launcher_apps = None;
+    }
+
+    /// Forget the screen size after rotating
+    fn forget_screen_size(&self) {
+        self.capabilities().screen_size = None;
+    }
+
+    /// Forget everything after losing the device: it may come back rebooted
+    fn forget_all(&self) {
+        *lock(&self.connection) = None;
+        *self.capabilities() = Capabilities::default();
+    }
+}
+
+/// Sessions of the connected devices, shared by all clones of the manager
+#[derive(Clone, Default)]
+struct AndroidSessions {
+    sessions: Arc<Mutex<HashMap<String, Arc<AndroidSession>>>>,
+}
+
+impl AndroidSessions {
+    /// Session of a device, started on first use
+    fn get(&self, device_id: &str) -> Arc<AndroidSession> {
+        lock(&self.sessions)
+            .entry(device_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// End the sessions of devices that are gone or reconnected since
+    ///
+    /// # Arguments
+    /// * `connected` - Ids of the connected devices
+    /// * `transport_ids` - adb transport id of each, where known
+    fn retain(&self, connected: &[String], transport_ids: &HashMap<String, u32>) {
+        let mut sessions = lock(&self.sessions);
+        sessions.retain(|device_id, session| {
+            if !connected.contains(device_id) {
+                return false;
+            }
+            let Some(id) = transport_ids.get(device_id) else {
+                return true;
+            };
+            // A session started before the first listing takes the listed
+            // transport; only a different known one means a reconnect
+            *lock(&session.transport_id).get_or_insert(*id) == *id
+        });
+        for (device_id, id) in transport_ids {
+            sessions.entry(device_id.clone()).or_insert_with(|| {
+                Arc::new(AndroidSession {
+                    transport_id: Mutex::new(Some(*id)),
+                    ..AndroidSession::default()
+                })
+            });
+        }
+    }
 }
 
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Ids of the displays in `dumpsys SurfaceFlinger --display-id` output
+fn parse_display_ids(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Display "))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|id| id.to_string())
+        .collect()
+}
+
+/// First characters of a serial for device names (serials can be short)
+fn short_id(device_id: &str) -> String {
+    device_id.chars().take(8).collect()
+}
+
+/// Value of `wm size` or `wm density` output; an override comes last and wins
+fn parse_wm_value(output: &str) -> Option<&str> {
+    output.split_whitespace().last()
+}
+
+// ============================================================================
+// Robot
+// ============================================================================
+
+/// Drives one device for one request, through the device's session
 #[allow(dead_code)]
 pub struct AndroidRobot {
     device_id: String,
-    server: ADBServer,
-    debug: bool,
+    session: Arc<AndroidSession>,
+    device: Option<ADBServerDevice>,
+}
+
+impl Drop for AndroidRobot {
+    fn drop(&mut self) {
+        if let Some(device) = self.device.take() {
+            self.session.release(device);
+        }
+    }
 }
 
 #[allow(dead_code)]
 impl AndroidRobot {
-    pub fn new(device_id: String, debug: bool) -> Self {
+    pub fn new(device_id: String, session: Arc<AndroidSession>) -> Self {
+        let device = session.acquire();
         Self {
             device_id,
-            server: ADBServer::default(),
-            debug,
+            session,
+            device,
         }
     }
 
     fn log_debug(&self, message: &str) {
-        if self.debug {
-            eprintln!("[DEBUG] Android Robot ({}): {}", self.device_id, message);
-        }
+        logging::log(
+            LogLevel::Debug,
+            logging::ADB,
+            &format!("{}: {}", self.device_id, message),
+        );
+    }
+
+    /// Connection to the device, reused for every command of the session
+    fn get_device(&mut self) -> &mut ADBServerDevice {
+        let device_id = &self.device_id;
+        self.device
+            .get_or_insert_with(|| ADBServerDevice::new(device_id.clone(), None))
     }
 
-    fn get_device(&mut self) -> Result<adb_client::ADBServerDevice, String> {
-        self.server
-            .get_device_by_name(&self.device_id)
-            .map_err(|e| format!("Failed to get device {}: {:?}", self.device_id, e))
+    /// Cached capability, queried with `load` on first use
+    fn capability<T: Clone>(
+        &mut self,
+        field: fn(&mut Capabilities) -> &mut Option<T>,
+        load: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        if let Some(value) = field(&mut self.session.capabilities()).clone() {
+            return Ok(value);
+        }
+        let value = load(self)?;
+        *field(&mut self.session.capabilities()) = Some(value.clone());
+        Ok(value)
     }
 
     fn execute_shell_command(&mut self, args: &[&str]) -> Result<Vec<u8>, String> {
-        let mut device = self.get_device()?;
-        let mut output = Vec::new();
-        device
-            .shell_command(args, &mut output)
-            .map_err(|e| format!("Shell command failed: {:?}", e))?;
-        Ok(output)
+        cancel::check()?;
+        let device_id = self.device_id.clone();
+        let result = recording::adb_shell(&device_id, args, || {
+            let device = self.get_device();
+
+            // Print the shell's PID first and exec the command in its place, so a
+            // cancelled request can kill exactly this process on the device
+            let mut command = vec!["echo", "$$;", "exec"];
+            command.extend_from_slice(args);
+            let mut output = ShellOutput::new(&device_id);
+            let result = device.shell_command(&command, &mut output);
+
+            cancel::check()?;
+            if let Err(e) = result {
+                // adb lost the device, which may come back rebooted
+                self.session.forget_all();
+                return Err(format!("Shell command failed: {:?}", e));
+            }
+            Ok(output.into_bytes())
+        });
+        if result.is_err() {
+            // Don't hand a connection that failed to the next request
+            self.device = None;
+        }
+        result
     }
 
     fn execute_shell_command_string(&mut self, args: &[&str]) -> Result<String, String> {
@@ -135,47 +381,94 @@ impl AndroidRobot {
     }
 
     pub fn get_system_features(&mut self) -> Result<Vec<String>, String> {
-        self.log_debug("Getting system features");
-        let output = self.execute_shell_command_string(&["pm", "list", "features"])?;
-
-        Ok(output
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| line.starts_with("feature:"))
-            .map(|line| line["feature:".len()..].to_string())
-            .collect())
+        self.capability(
+            |c| &mut c.features,
+            |robot| {
+                robot.log_debug("Getting system features");
+                let output = robot.execute_shell_command_string(&["pm", "list", "features"])?;
+
+                Ok(output
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| line.starts_with("feature:"))
+                    .map(|line| line["feature:".len()..].to_string())
+                    .collect())
+            },
+        )
     }
 
-    pub fn list_installed_apps(&mut self) -> Result<Vec<InstalledApp>, String> {
-        self.log_debug("Listing installed apps");
+    /// API level of the Android version (e.g., 30 for Android 11)
+    pub fn get_sdk_level(&mut self) -> Result<u32, String> {
+        self.capability(
+            |c| &mut c.sdk_level,
+            |robot| {
+                robot.log_debug("Getting SDK level");
+                let output =
+                    robot.execute_shell_command_string(&["getprop", "ro.build.version.sdk"])?;
+                output
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("Failed to parse SDK level: {}", output.trim()))
+            },
+        )
+    }
 
-        let output = self.execute_shell_command(&["pm", "list", "packages", "-3"])?;
-        let output_str = String::from_utf8_lossy(&output);
+    /// Names of all installed packages, including system ones
+    fn get_installed_packages(&mut self) -> Result<Vec<String>, String> {
+        self.capability(
+            |c| &mut c.packages,
+            |robot| {
+                robot.log_debug("Listing installed packages");
+                let output = robot.execute_shell_command_string(&["pm", "list", "packages"])?;
+
+                Ok(output
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix("package:"))
+                    .map(|package| package.to_string())
+                    .collect())
+            },
+        )
+    }
 
-        let mut apps = Vec::new();
-        for line in output_str.lines() {
-            if let Some(package) = line.strip_prefix("package:") {
-                apps.push(InstalledApp {
-                    package_name: package.to_string(),
-                    app_name: package.to_string(), // Would need additional query for actual name
-                });
-            }
-        }
+    pub fn list_installed_apps(&mut self) -> Result<Vec<InstalledApp>, String> {
+        self.capability(
+            |c| &mut c.third_party_apps,
+            |robot| {
+                robot.log_debug("Listing installed apps");
+
+                let output = robot.execute_shell_command(&["pm", "list", "packages", "-3"])?;
+                let output_str = String::from_utf8_lossy(&output);
+
+                let mut apps = Vec::new();
+                for line in output_str.lines() {
+                    if let Some(package) = line.strip_prefix("package:") {
+                        apps.push(InstalledApp {
+                            package_name: package.to_string(),
+                            app_name: package.to_string(), // Would need additional query for actual name
+                        });
+                    }
+                }
 
-        Ok(apps)
+                Ok(apps)
+            },
+        )
     }
 
     pub fn list_screen_elements(
         &mut self,
         filter: Option<&str>,
+        progress: Progress,
     ) -> Result<Vec<ScreenElement>, String> {
         self.log_debug("Listing screen elements");
 
         // Use uiautomator dump to get UI hierarchy
         // First dump to default location, then read the file
+        progress(0.0, "Dumping UI hierarchy");
         let _ = self.execute_shell_command(&["uiautomator", "dump"])?;
+        progress(70.0, "Reading UI hierarchy");
         let output = self.execute_shell_command(&["cat", "/sdcard/window_dump.xml"])?;
         let output_str = String::from_utf8_lossy(&output);
+        progress(90.0, "Parsing elements");
 
         self.log_debug(&format!(
             "XML content length: {} bytes, first 200 chars: {}",
@@ -206,97 +499,95 @@ impl AndroidRobot {
 
         loop {
             match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
-                    if e.name().as_ref() == b"node" {
-                        total_nodes += 1;
-                        // Parse attributes into a HashMap
-                        let mut attrs = HashMap::new();
-                        for attr in e.attributes().flatten() {
-                            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                            let value = String::from_utf8_lossy(&attr.value).to_string();
-                            attrs.insert(key, value);
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.name().as_ref() == b"node" => {
+                    total_nodes += 1;
+                    // Parse attributes into a HashMap
+                    let mut attrs = HashMap::new();
+                    for attr in e.attributes().flatten() {
+                        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                        let value = String::from_utf8_lossy(&attr.value).to_string();
+                        attrs.insert(key, value);
+                    }
+
+                    // Extract relevant attributes
+                    let text = attrs.get("text").and_then(|t| {
+                        if t.is_empty() {
+                            None
+                        } else {
+                            Some(t.clone())
                         }
+                    });
 
-                        // Extract relevant attributes
-                        let text = attrs.get("text").and_then(|t| {
-                            if t.is_empty() {
-                                None
-                            } else {
-                                Some(t.clone())
-                            }
-                        });
+                    let content_desc = attrs.get("content-desc").and_then(|c| {
+                        if c.is_empty() {
+                            None
+                        } else {
+                            Some(c.clone())
+                        }
+                    });
 
-                        let content_desc = attrs.get("content-desc").and_then(|c| {
-                            if c.is_empty() {
-                                None
-                            } else {
-                                Some(c.clone())
-                            }
-                        });
+                    let resource_id = attrs.get("resource-id").and_then(|r| {
+                        if r.is_empty() {
+                            None
+                        } else {
+                            Some(r.clone())
+                        }
+                    });
 
-                        let resource_id = attrs.get("resource-id").and_then(|r| {
-                            if r.is_empty() {
-                                None
-                            } else {
-                                Some(r.clone())
-                            }
-                        });
+                    let class = attrs.get("class").cloned().unwrap_or_default();
 
-                        let class = attrs.get("class").cloned().unwrap_or_default();
+                    let bounds_str = attrs.get("bounds").cloned().unwrap_or_default();
 
-                        let bounds_str = attrs.get("bounds").cloned().unwrap_or_default();
+                    let focused = attrs.get("focused").map(|f| f == "true");
 
-                        let focused = attrs.get("focused").map(|f| f == "true");
+                    // Parse bounds to get rect
+                    let rect = self.parse_bounds(&bounds_str).unwrap_or(ScreenElementRect {
+                        x: 0,
+                        y: 0,
+                        width: 0,
+                        height: 0,
+                    });
 
-                        // Parse bounds to get rect
-                        let rect = self.parse_bounds(&bounds_str).unwrap_or(ScreenElementRect {
-                            x: 0,
-                            y: 0,
-                            width: 0,
-                            height: 0,
+                    // Determine label - prefer text, then content-desc, then resource-id
+                    let label = text
+                        .clone()
+                        .or_else(|| content_desc.clone())
+                        .or_else(|| resource_id.clone())
+                        .unwrap_or_else(|| {
+                            if class.is_empty() {
+                                "Unknown".to_string()
+                            } else {
+                                class.clone()
+                            }
                         });
 
-                        // Determine label - prefer text, then content-desc, then resource-id
-                        let label = text
-                            .clone()
-                            .or_else(|| content_desc.clone())
-                            .or_else(|| resource_id.clone())
-                            .unwrap_or_else(|| {
-                                if class.is_empty() {
-                                    "Unknown".to_string()
-                                } else {
-                                    class.clone()
-                                }
-                            });
-
-                        // Apply filter if provided
-                        let should_include = if let Some(filter_text) = filter {
-                            text.as_ref()
-                                .map(|t| t.contains(filter_text))
+                    // Apply filter if provided
+                    let should_include = if let Some(filter_text) = filter {
+                        text.as_ref()
+                            .map(|t| t.contains(filter_text))
+                            .unwrap_or(false)
+                            || content_desc
+                                .as_ref()
+                                .map(|c| c.contains(filter_text))
                                 .unwrap_or(false)
-                                || content_desc
-                                    .as_ref()
-                                    .map(|c| c.contains(filter_text))
-                                    .unwrap_or(false)
-                                || resource_id
-                                    .as_ref()
-                                    .map(|r| r.contains(filter_text))
-                                    .unwrap_or(false)
-                        } else {
-                            true
-                        };
-
-                        // Only include elements that are visible (have non-zero size)
-                        if should_include && (rect.width > 0 && rect.height > 0) {
-                            elements.push(ScreenElement {
-                                element_type: class.clone(),
-                                text: text.clone(),
-                                label: label.clone(),
-                                rect,
-                                focused,
-                                identifier: resource_id,
-                            });
-                        }
+                            || resource_id
+                                .as_ref()
+                                .map(|r| r.contains(filter_text))
+                                .unwrap_or(false)
+                    } else {
+                        true
+                    };
+
+                    // Only include elements that are visible (have non-zero size)
+                    if should_include && (rect.width > 0 && rect.height > 0) {
+                        elements.push(ScreenElement {
+                            element_type: class.clone(),
+                            text: text.clone(),
+                            label: label.clone(),
+                            rect,
+                            focused,
+                            identifier: resource_id,
+                        });
                     }
                 }
                 Ok(Event::Eof) => break,
@@ -354,13 +645,16 @@ impl AndroidRobot {
     }
 
     pub fn get_screen_size(&mut self) -> Result<ScreenSize, String> {
+        self.capability(|c| &mut c.screen_size, Self::query_screen_size)
+    }
+
+    fn query_screen_size(&mut self) -> Result<ScreenSize, String> {
         self.log_debug("Getting screen size");
+        let density = self.get_density()?;
         let output = self.execute_shell_command_string(&["wm", "size"])?;
 
-        let screen_size_str = output
-            .split_whitespace()
-            .last()
-            .ok_or("Failed to parse screen size output")?;
+        let screen_size_str =
+            parse_wm_value(&output).ok_or("Failed to parse screen size output")?;
 
         let parts: Vec<&str> = screen_size_str.split('x').collect();
         if parts.len() != 2 {
@@ -377,11 +671,29 @@ impl AndroidRobot {
         Ok(ScreenSize {
             width,
             height,
-            scale: 1.0,
+            scale: density as f32 / 160.0,
         })
     }
 
+    /// Screen density in dpi (160 is Android's baseline)
+    pub fn get_density(&mut self) -> Result<u32, String> {
+        self.capability(
+            |c| &mut c.density,
+            |robot| {
+                robot.log_debug("Getting screen density");
+                let output = robot.execute_shell_command_string(&["wm", "density"])?;
+                parse_wm_value(&output)
+                    .and_then(|density| density.parse::<u32>().ok())
+                    .ok_or_else(|| format!("Failed to parse screen density: {}", output.trim()))
+            },
+        )
+    }
+
     pub fn list_apps(&mut self) -> Result<Vec<InstalledApp>, String> {
+        self.capability(|c| &mut c.launcher_apps, Self::query_launcher_apps)
+    }
+
+    fn query_launcher_apps(&mut self) -> Result<Vec<InstalledApp>, String> {
         self.log_debug("Listing installed apps with launcher activities");
         let output = self.execute_shell_command_string(&[
             "cmd",
@@ -416,7 +728,15 @@ impl AndroidRobot {
         self.log_debug(&format!("Launching app: {}", package_name));
 
         // First, check if the app is installed
-        let installed_apps = self.list_apps()?;
+        let mut installed_apps = self.list_apps()?;
+        if !installed_apps
+            .iter()
+            .any(|app| app.package_name == package_name)
+        {
+            // It may have been installed since the list was cached
+            self.session.forget_packages();
+            installed_apps = self.list_apps()?;
+        }
         let app_exists = installed_apps
             .iter()
             .any(|app| app.package_name == package_name);
@@ -580,32 +900,35 @@ impl AndroidRobot {
         Ok(())
     }
 
-    pub fn get_screenshot(&mut self) -> Result<Vec<u8>, String> {
+    pub fn get_screenshot(&mut self, progress: Progress) -> Result<Vec<u8>, String> {
         self.log_debug("Taking screenshot");
 
         // Try to determine if we need display ID for multi-display devices
-        let display_count = self.get_display_count()?;
+        progress(0.0, "Querying displays");
+        let display_count = self.get_display_ids()?.len();
 
         if display_count <= 1 {
             // Single display device - use simple screencap
+            progress(20.0, "Capturing screen");
             return self.execute_screencap_simple();
         }
 
         // Multi-display device - try to find the active display
+        progress(10.0, "Finding active display");
         if let Ok(display_id) = self.get_first_display_id() {
+            progress(20.0, "Capturing screen");
             return self.execute_screencap_with_display(&display_id);
         }
 
+        progress(20.0, "Capturing screen");
         // Fallback to simple screencap
         self.execute_screencap_simple()
     }
 
     fn execute_screencap_simple(&mut self) -> Result<Vec<u8>, String> {
-        let mut device = self.get_device()?;
-        let mut output = Vec::new();
-        device
-            .shell_command(&["screencap", "-p"], &mut output)
-            .map_err(|e| format!("Screenshot command failed: {:?}", e))?;
+        let output = self
+            .execute_shell_command(&["screencap", "-p"])
+            .map_err(|e| format!("Screenshot command failed: {}", e))?;
 
         // Validate PNG data
         if output.len() >= 8
@@ -621,32 +944,39 @@ impl AndroidRobot {
     }
 
     fn execute_screencap_with_display(&mut self, display_id: &str) -> Result<Vec<u8>, String> {
-        let mut device = self.get_device()?;
-        let mut output = Vec::new();
-        device
-            .shell_command(&["screencap", "-p", "-d", display_id], &mut output)
-            .map_err(|e| format!("Screenshot with display ID failed: {:?}", e))?;
-        Ok(output)
+        self.execute_shell_command(&["screencap", "-p", "-d", display_id])
+            .map_err(|e| format!("Screenshot with display ID failed: {}", e))
     }
 
-    fn get_display_count(&mut self) -> Result<u32, String> {
-        let output =
-            self.execute_shell_command_string(&["dumpsys", "SurfaceFlinger", "--display-id"])?;
-        let count = output
-            .lines()
-            .filter(|line| line.starts_with("Display "))
-            .count() as u32;
-        Ok(count)
+    fn get_display_ids(&mut self) -> Result<Vec<String>, String> {
+        self.capability(
+            |c| &mut c.display_ids,
+            |robot| {
+                let output = robot.execute_shell_command_string(&[
+                    "dumpsys",
+                    "SurfaceFlinger",
+                    "--display-id",
+                ])?;
+                Ok(parse_display_ids(&output))
+            },
+        )
     }
 
     fn get_first_display_id(&mut self) -> Result<String, String> {
-        // Try modern approach first (Android 11+)
-        if let Ok(display_id) = self.get_display_id_modern() {
-            return Ok(display_id);
-        }
+        self.capability(
+            |c| &mut c.active_display,
+            |robot| {
+                // `cmd display` lists displays from Android 11 on
+                if robot.get_sdk_level().map_or(true, |level| level >= 30) {
+                    if let Ok(display_id) = robot.get_display_id_modern() {
+                        return Ok(display_id);
+                    }
+                }
 
-        // Fallback to legacy dumpsys approach
-        self.get_display_id_legacy()
+                // Fallback to legacy dumpsys approach
+                robot.get_display_id_legacy()
+            },
+        )
     }
 
     fn get_display_id_modern(&mut self) -> Result<String, String> {
@@ -777,10 +1107,18 @@ impl AndroidRobot {
     }
 
     fn is_device_kit_installed(&mut self) -> Result<bool, String> {
-        let output = self.execute_shell_command_string(&["pm", "list", "packages"])?;
-        Ok(output
-            .lines()
-            .any(|line| line.contains("com.mobilenext.devicekit")))
+        let has_device_kit = |packages: Vec<String>| {
+            packages
+                .iter()
+                .any(|package| package.contains("com.mobilenext.devicekit"))
+        };
+        if has_device_kit(self.get_installed_packages()?) {
+            return Ok(true);
+        }
+
+        // It may have been installed since the list was cached
+        self.session.forget_packages();
+        Ok(has_device_kit(self.get_installed_packages()?))
     }
 
     fn send_keys_via_clipboard(&mut self, text: &str) -> Result<(), String> {
@@ -863,6 +1201,7 @@ impl AndroidRobot {
             "--bind",
             &format!("value:i:{}", value),
         ])?;
+        self.session.forget_screen_size();
 
         Ok(())
     }
@@ -879,6 +1218,23 @@ impl AndroidRobot {
         })
     }
 
+    /// Component of the resumed activity (e.g., "com.android.settings/.Settings")
+    pub fn get_foreground_activity(&mut self) -> Result<String, String> {
+        let output = self.execute_shell_command_string(&["dumpsys", "activity", "activities"])?;
+
+        // "mResumedActivity: ActivityRecord{..}" up to Android 9,
+        // "topResumedActivity=ActivityRecord{..}" from Android 10
+        output
+            .lines()
+            .filter(|line| line.contains("ResumedActivity"))
+            .find_map(|line| {
+                line.split_whitespace()
+                    .find(|token| token.contains('/'))
+                    .map(|token| token.trim_end_matches('}').to_string())
+            })
+            .ok_or_else(|| "No resumed activity found".to_string())
+    }
+
     pub fn open_url(&mut self, url: &str) -> Result<(), String> {
         self.log_debug(&format!("Opening URL: {}", url));
         self.execute_shell_command(&[
@@ -892,31 +1248,84 @@ impl AndroidRobot {
         Ok(())
     }
 
-    pub fn install_app(&mut self, apk_path: &str) -> Result<(), String> {
+    /// Install an APK
+    ///
+    /// A local APK is pushed to a temporary location on the device first
+    /// (reported as 0-40% progress), installed, and removed again. A path
+    /// that doesn't exist locally is treated as a path on the device.
+    pub fn install_app(&mut self, apk_path: &str, progress: Progress) -> Result<(), String> {
         self.log_debug(&format!("Installing APK: {}", apk_path));
-        let mut device = self.get_device()?;
-        // Note: This would need to be implemented with file transfer first
-        // For now, we'll use a simplified approach assuming the APK is on the device
-        let mut output = Vec::new();
-        device
-            .shell_command(&["pm", "install", "-r", apk_path], &mut output)
-            .map_err(|e| format!("APK installation failed: {:?}", e))?;
 
-        let result = String::from_utf8_lossy(&output);
+        let local_path = Path::new(apk_path);
+        let device_path = if local_path.is_file() {
+            let file_name = local_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "app.apk".to_string());
+            let device_path = format!("/data/local/tmp/mobile-mcp-{}", file_name);
+            self.push_file(local_path, &device_path, progress)?;
+            Some(device_path)
+        } else {
+            None
+        };
+
+        progress(50.0, "Installing");
+        let result = self.execute_shell_command_string(&[
+            "pm",
+            "install",
+            "-r",
+            device_path.as_deref().unwrap_or(apk_path),
+        ]);
+        self.session.forget_packages();
+
+        progress(90.0, "Verifying installation");
+        if let Some(device_path) = &device_path {
+            let _ = self.execute_shell_command(&["rm", "-f", device_path]);
+        }
+
+        let result = result.map_err(|e| format!("APK installation failed: {}", e))?;
         if result.contains("Success") {
             Ok(())
         } else {
-            Err(format!("Installation failed: {}", result))
+            Err(format!("Installation failed: {}", result.trim()))
+        }
+    }
+
+    /// Push a local file to the device, reporting 0-40% progress by bytes sent
+    fn push_file(
+        &mut self,
+        local_path: &Path,
+        device_path: &str,
+        progress: Progress,
+    ) -> Result<(), String> {
+        let file = File::open(local_path)
+            .map_err(|e| format!("Failed to open {}: {}", local_path.display(), e))?;
+        let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        progress(0.0, "Pushing APK to device");
+        let reader = ProgressReader {
+            inner: file,
+            sent: 0,
+            total,
+            progress,
+        };
+        let device_id = self.device_id.clone();
+        let result = recording::adb_push(&device_id, device_path, || {
+            self.get_device()
+                .push(reader, device_path)
+                .map_err(|e| format!("Failed to push {}: {:?}", local_path.display(), e))
+        });
+        if result.is_err() {
+            self.device = None;
         }
+        result
     }
 
     pub fn uninstall_app(&mut self, package_name: &str) -> Result<(), String> {
         self.log_debug(&format!("Uninstalling package: {}", package_name));
-        let mut device = self.get_device()?;
-        let mut output = Vec::new();
-        device
-            .shell_command(&["pm", "uninstall", package_name], &mut output)
-            .map_err(|e| format!("Uninstallation failed: {:?}", e))?;
+        let output = self.execute_shell_command(&["pm", "uninstall", package_name]);
+        self.session.forget_packages();
+        let output = output.map_err(|e| format!("Uninstallation failed: {}", e))?;
 
         let result = String::from_utf8_lossy(&output);
         if result.contains("Success") {
@@ -928,49 +1337,83 @@ impl AndroidRobot {
 }
 
 pub struct AndroidDeviceManager {
-    debug: bool,
     server: ADBServer,
+    sessions: AndroidSessions,
+}
+
+/// Clones share the device sessions but not the adb server connection, so
+/// each request thread gets its own
+impl Clone for AndroidDeviceManager {
+    fn clone(&self) -> Self {
+        Self {
+            server: ADBServer::default(),
+            sessions: self.sessions.clone(),
+        }
+    }
+}
+
+impl Default for AndroidDeviceManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AndroidDeviceManager {
-    pub fn new(debug: bool) -> Self {
+    pub fn new() -> Self {
         Self {
-            debug,
             server: ADBServer::default(),
+            sessions: AndroidSessions::default(),
         }
     }
 
     fn log_debug(&self, message: &str) {
-        if self.debug {
-            eprintln!("[DEBUG] Android Manager: {}", message);
-        }
+        logging::log(LogLevel::Debug, logging::ADB, message);
     }
 
     pub fn get_connected_devices(&mut self) -> Result<Vec<AndroidDevice>, String> {
         self.log_debug("Getting connected Android devices");
 
-        let devices = self
-            .server
-            .devices()
-            .map_err(|e| format!("Failed to get devices: {:?}", e))?;
+        // Transport ids aren't part of transcripts: replayed devices never reconnect
+        let mut transport_ids = HashMap::new();
+        let device_ids = recording::adb_devices(|| {
+            if let Ok(devices) = self.server.devices_long() {
+                return Ok(devices
+                    .into_iter()
+                    .filter(|device| matches!(device.state, DeviceState::Device))
+                    .map(|device| {
+                        transport_ids.insert(device.identifier.clone(), device.transport_id);
+                        device.identifier
+                    })
+                    .collect());
+            }
+            // Older adb servers may list devices in a format that doesn't parse
+            let devices = self
+                .server
+                .devices()
+                .map_err(|e| format!("Failed to get devices: {:?}", e))?;
+            Ok(devices
+                .into_iter()
+                .filter(|device| matches!(device.state, DeviceState::Device))
+                .map(|device| device.identifier)
+                .collect())
+        })?;
+        self.sessions.retain(&device_ids, &transport_ids);
 
         let mut android_devices = Vec::new();
 
-        for device in devices {
-            if matches!(device.state, DeviceState::Device) {
-                let device_type = self.get_device_type(&device.identifier)?;
-                android_devices.push(AndroidDevice {
-                    device_id: device.identifier,
-                    device_type,
-                });
-            }
+        for device_id in device_ids {
+            let device_type = self.get_device_type(&device_id)?;
+            android_devices.push(AndroidDevice {
+                device_id,
+                device_type,
+            });
         }
 
         Ok(android_devices)
     }
 
     fn get_device_type(&mut self, device_id: &str) -> Result<AndroidDeviceType, String> {
-        let mut robot = AndroidRobot::new(device_id.to_string(), self.debug);
+        let mut robot = self.create_robot(device_id.to_string());
         let features = robot.get_system_features()?;
 
         if features.contains(&"android.software.leanback".to_string())
@@ -983,10 +1426,21 @@ impl AndroidDeviceManager {
     }
 
     pub fn create_robot(&self, device_id: String) -> AndroidRobot {
-        AndroidRobot::new(device_id, self.debug)
+        let session = self.sessions.get(&device_id);
+        AndroidRobot::new(device_id, session)
+    }
+}
+
+impl DeviceBackend for AndroidDeviceManager {
+    fn platform(&self) -> &'static str {
+        "android"
+    }
+
+    fn clone_backend(&self) -> Box<dyn DeviceBackend> {
+        Box::new(self.clone())
     }
 
-    pub fn list_devices(&mut self) -> Result<Vec<DeviceInfo>, String> {
+    fn list_devices(&mut self) -> Result<Vec<DeviceInfo>, String> {
         let android_devices = self.get_connected_devices()?;
 
         Ok(android_devices
@@ -999,7 +1453,11 @@ impl AndroidDeviceManager {
 
                 DeviceInfo {
                     id: device.device_id.clone(),
-                    name: format!("Android {} ({})", device_type_str, &device.device_id[..8]),
+                    name: format!(
+                        "Android {} ({})",
+                        device_type_str,
+                        short_id(&device.device_id)
+                    ),
                     platform: "android".to_string(),
                     device_type: device_type_str.to_string(),
                     state: "connected".to_string(),
@@ -1008,59 +1466,314 @@ impl AndroidDeviceManager {
             .collect())
     }
 
-    pub fn take_screenshot(&mut self, device_id: &str) -> Result<Vec<u8>, String> {
+    fn take_screenshot(&mut self, device_id: &str, progress: Progress) -> Result<Vec<u8>, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.get_screenshot()
+        robot.get_screenshot(progress)
     }
 
-    pub fn tap_screen(&mut self, device_id: &str, x: f64, y: f64) -> Result<String, String> {
+    fn tap(&mut self, device_id: &str, x: f64, y: f64) -> Result<String, String> {
         let mut robot = self.create_robot(device_id.to_string());
         robot.tap(x as u32, y as u32)?;
         Ok(format!("Tapped at ({}, {}) on device {}", x, y, device_id))
     }
 
-    pub fn type_text(&mut self, device_id: &str, text: &str) -> Result<String, String> {
+    fn double_tap(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        progress: Progress,
+    ) -> Result<String, String> {
+        progress(0.0, "First tap");
+        self.tap(device_id, x, y)?;
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        progress(50.0, "Second tap");
+        self.tap(device_id, x, y)?;
+        Ok("Double tap executed".to_string())
+    }
+
+    fn long_press(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        _duration_ms: u32,
+    ) -> Result<String, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.send_keys(text)?;
-        Ok(format!("Typed '{}' on device {}", text, device_id))
+        robot.long_press(x as u32, y as u32)?;
+        Ok(format!("Long pressed at ({}, {})", x, y))
     }
 
-    #[allow(dead_code)]
-    pub fn swipe(&mut self, device_id: &str, direction: SwipeDirection) -> Result<String, String> {
+    fn swipe(
+        &mut self,
+        device_id: &str,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        duration_ms: u32,
+    ) -> Result<String, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.swipe(direction.clone())?;
-        Ok(format!("Swiped {:?} on device {}", direction, device_id))
+        robot.swipe_coordinates(
+            start_x as u32,
+            start_y as u32,
+            end_x as u32,
+            end_y as u32,
+            duration_ms,
+        )?;
+        Ok("Swipe executed".to_string())
     }
 
-    #[allow(dead_code)]
-    pub fn press_button(&mut self, device_id: &str, button: Button) -> Result<String, String> {
+    fn type_text(&mut self, device_id: &str, text: &str) -> Result<String, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.press_button(button.clone())?;
+        robot.send_keys(text)?;
+        Ok(format!("Typed '{}' on device {}", text, device_id))
+    }
+
+    fn press_button(&mut self, device_id: &str, button: Button) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.press_button(button)?;
         Ok(format!(
             "Pressed {:?} button on device {}",
             button, device_id
         ))
     }
 
-    #[allow(dead_code)]
-    pub fn launch_app(&mut self, device_id: &str, package_name: &str) -> Result<String, String> {
+    fn screen_size(&mut self, device_id: &str) -> Result<ScreenSize, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.launch_app(package_name)?;
-        Ok(format!(
-            "Launched app {} on device {}",
-            package_name, device_id
-        ))
+        robot.get_screen_size()
     }
 
-    #[allow(dead_code)]
-    pub fn list_apps(&mut self, device_id: &str) -> Result<Vec<InstalledApp>, String> {
+    fn orientation(&mut self, device_id: &str) -> Result<Orientation, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.list_apps()
+        robot.get_orientation()
     }
 
-    #[allow(dead_code)]
-    pub fn get_screen_size(&mut self, device_id: &str) -> Result<ScreenSize, String> {
+    fn set_orientation(
+        &mut self,
+        device_id: &str,
+        orientation: Orientation,
+    ) -> Result<String, String> {
         let mut robot = self.create_robot(device_id.to_string());
-        robot.get_screen_size()
+        let name = orientation.name();
+        robot.set_orientation(orientation)?;
+        Ok(format!("Set orientation to {}", name))
+    }
+
+    fn list_apps(&mut self, device_id: &str) -> Result<Vec<InstalledApp>, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.list_installed_apps()
+    }
+
+    fn list_elements(
+        &mut self,
+        device_id: &str,
+        filter: Option<&str>,
+        progress: Progress,
+    ) -> Result<Vec<ScreenElement>, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.list_screen_elements(filter, progress)
+    }
+
+    fn foreground_activity(&mut self, device_id: &str) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.get_foreground_activity()
+    }
+
+    fn launch_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.launch_app(app_id)?;
+        Ok(format!("Launched app: {}", app_id))
+    }
+
+    fn terminate_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.terminate_app(app_id)?;
+        Ok(format!("Terminated app: {}", app_id))
+    }
+
+    fn install_app(
+        &mut self,
+        device_id: &str,
+        app_path: &str,
+        progress: Progress,
+    ) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.install_app(app_path, progress)?;
+        Ok(format!("Installed app from: {}", app_path))
+    }
+
+    fn uninstall_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.uninstall_app(app_id)?;
+        Ok(format!("Uninstalled app: {}", app_id))
+    }
+
+    fn open_url(&mut self, device_id: &str, url: &str) -> Result<String, String> {
+        let mut robot = self.create_robot(device_id.to_string());
+        robot.open_url(url)?;
+        Ok(format!("Opened URL: {}", url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell_output(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut output = ShellOutput::new("emulator-5554");
+        for chunk in chunks {
+            output.write_all(chunk).unwrap();
+        }
+        output.into_bytes()
+    }
+
+    #[test]
+    fn test_shell_output_strips_pid_line() {
+        assert_eq!(shell_output(&[b"4242\nSuccess\n"]), b"Success\n");
+        // The PID line may be split across reads
+        assert_eq!(
+            shell_output(&[b"42", b"42\r\nSucc", b"ess\n"]),
+            b"Success\n"
+        );
+    }
+
+    #[test]
+    fn test_shell_output_keeps_unexpected_first_line() {
+        assert_eq!(shell_output(&[b"Error: boom\n"]), b"Error: boom\n");
+        assert_eq!(shell_output(&[b"no newline"]), b"no newline");
+    }
+
+    #[test]
+    fn test_capabilities_cached_until_forgotten() {
+        let session = Arc::new(AndroidSession::default());
+        let queries = std::cell::Cell::new(0);
+        let apps = |robot: &mut AndroidRobot| {
+            robot.capability(
+                |c| &mut c.launcher_apps,
+                |_| {
+                    queries.set(queries.get() + 1);
+                    Ok(Vec::new())
+                },
+            )
+        };
+
+        // Later requests for the device reuse the first answer
+        apps(&mut AndroidRobot::new(
+            "emulator-5554".to_string(),
+            session.clone(),
+        ))
+        .unwrap();
+        apps(&mut AndroidRobot::new(
+            "emulator-5554".to_string(),
+            session.clone(),
+        ))
+        .unwrap();
+        assert_eq!(queries.get(), 1);
+
+        session.forget_screen_size();
+        apps(&mut AndroidRobot::new(
+            "emulator-5554".to_string(),
+            session.clone(),
+        ))
+        .unwrap();
+        assert_eq!(queries.get(), 1);
+
+        session.forget_packages();
+        apps(&mut AndroidRobot::new(
+            "emulator-5554".to_string(),
+            session.clone(),
+        ))
+        .unwrap();
+        assert_eq!(queries.get(), 2);
+
+        session.forget_all();
+        apps(&mut AndroidRobot::new("emulator-5554".to_string(), session)).unwrap();
+        assert_eq!(queries.get(), 3);
+    }
+
+    #[test]
+    fn test_sessions_end_when_device_disconnects() {
+        let manager = AndroidDeviceManager::new();
+        let session = manager.sessions.get("emulator-5554");
+        assert!(Arc::ptr_eq(
+            &session,
+            &manager.clone().sessions.get("emulator-5554")
+        ));
+
+        manager
+            .sessions
+            .retain(&["R58M123ABC".to_string()], &HashMap::new());
+        assert!(!Arc::ptr_eq(
+            &session,
+            &manager.sessions.get("emulator-5554")
+        ));
+    }
+
+    #[test]
+    fn test_sessions_end_when_device_reconnects() {
+        let sessions = AndroidSessions::default();
+        let connected = ["emulator-5554".to_string()];
+        sessions.retain(
+            &connected,
+            &HashMap::from([("emulator-5554".to_string(), 3)]),
+        );
+        let session = sessions.get("emulator-5554");
+
+        sessions.retain(
+            &connected,
+            &HashMap::from([("emulator-5554".to_string(), 3)]),
+        );
+        assert!(Arc::ptr_eq(&session, &sessions.get("emulator-5554")));
+        // Without transport ids (replay, older adb) the session is kept
+        sessions.retain(&connected, &HashMap::new());
+        assert!(Arc::ptr_eq(&session, &sessions.get("emulator-5554")));
+
+        // The emulator restarted under the same serial
+        sessions.retain(
+            &connected,
+            &HashMap::from([("emulator-5554".to_string(), 7)]),
+        );
+        assert!(!Arc::ptr_eq(&session, &sessions.get("emulator-5554")));
+    }
+
+    #[test]
+    fn test_session_started_before_listing_takes_the_transport() {
+        let sessions = AndroidSessions::default();
+        let connected = ["R58M123ABC".to_string()];
+        let session = sessions.get("R58M123ABC");
+        session.capabilities().sdk_level = Some(34);
+
+        // The first listing doesn't throw away what the session cached
+        let listed = HashMap::from([("R58M123ABC".to_string(), 5)]);
+        sessions.retain(&connected, &listed);
+        assert!(Arc::ptr_eq(&session, &sessions.get("R58M123ABC")));
+        assert_eq!(session.capabilities().sdk_level, Some(34));
+        sessions.retain(&connected, &listed);
+        assert!(Arc::ptr_eq(&session, &sessions.get("R58M123ABC")));
+
+        // Once known, a different transport is a reconnect
+        sessions.retain(&connected, &HashMap::from([("R58M123ABC".to_string(), 6)]));
+        assert!(!Arc::ptr_eq(&session, &sessions.get("R58M123ABC")));
+    }
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(short_id("emulator-5554"), "emulator");
+        assert_eq!(short_id("R58M"), "R58M");
+    }
+
+    #[test]
+    fn test_parse_display_ids() {
+        let output = "Display 4619827259835644672 (HWC display 0): port=0 pnpId=GGL\n\
+                      Display 4619827551948147201 (HWC display 1): port=1 pnpId=GGL\n";
+        assert_eq!(
+            parse_display_ids(output),
+            vec!["4619827259835644672", "4619827551948147201"]
+        );
+        assert_eq!(
+            parse_wm_value("Physical density: 420\nOverride density: 480\n"),
+            Some("480")
+        );
     }
 }
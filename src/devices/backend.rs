@@ -0,0 +1,383 @@
+// mobile-mcp-zed-extension/src/devices/backend.rs
+// Device Backends
+//
+// Every platform (Android through adb, iOS through simctl/libimobiledevice)
+// implements DeviceBackend. MobileDeviceManager looks the backend up by
+// platform name in a BackendRegistry and calls it, so a new platform only
+// needs an implementation and a `register` call, not a new arm in every
+// dispatcher method. Platform arguments are checked against (and tool
+// schemas list) the registered platforms.
+//
+// The types shared by all backends (elements, buttons, orientation, apps)
+// live here too. Action methods return the backend's own summary of what it
+// did; tool handlers pass it on to the model.
+
+use std::sync::OnceLock;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::Progress;
+use crate::types::DeviceInfo;
+
+// ============================================================================
+// Shared Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InstalledApp {
+    pub package_name: String,
+    pub app_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenElement {
+    pub element_type: String,
+    pub text: Option<String>,
+    pub label: String,
+    pub rect: ScreenElementRect,
+    pub focused: Option<bool>,
+    pub identifier: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenElementRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct ScreenSize {
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Button {
+    Back,
+    Home,
+    Menu,
+    Power,
+    Camera,
+    VolumeUp,
+    VolumeDown,
+    Enter,
+    DpadCenter,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+impl Button {
+    /// Every button, in the order tool schemas list them
+    pub const ALL: [Button; 13] = [
+        Button::Home,
+        Button::Back,
+        Button::Menu,
+        Button::Power,
+        Button::VolumeUp,
+        Button::VolumeDown,
+        Button::Camera,
+        Button::Enter,
+        Button::DpadCenter,
+        Button::DpadUp,
+        Button::DpadDown,
+        Button::DpadLeft,
+        Button::DpadRight,
+    ];
+
+    /// Names accepted for buttons that don't have one of their own
+    ///
+    /// Kept for existing callers; completion only offers the real names.
+    pub const ALIASES: [(&'static str, Button); 2] =
+        [("search", Button::Back), ("app_switch", Button::Menu)];
+
+    /// Button for a press_button argument (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|button| button.name() == name)
+            .or_else(|| {
+                Self::ALIASES
+                    .into_iter()
+                    .find(|(alias, _)| *alias == name)
+                    .map(|(_, button)| button)
+            })
+    }
+
+    /// Name used in tool arguments
+    pub fn name(&self) -> &'static str {
+        match self {
+            Button::Back => "back",
+            Button::Home => "home",
+            Button::Menu => "menu",
+            Button::Power => "power",
+            Button::Camera => "camera",
+            Button::VolumeUp => "volume_up",
+            Button::VolumeDown => "volume_down",
+            Button::Enter => "enter",
+            Button::DpadCenter => "dpad_center",
+            Button::DpadUp => "dpad_up",
+            Button::DpadDown => "dpad_down",
+            Button::DpadLeft => "dpad_left",
+            Button::DpadRight => "dpad_right",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    /// Every orientation
+    pub const ALL: [Orientation; 2] = [Orientation::Portrait, Orientation::Landscape];
+
+    /// Orientation for a set_orientation argument
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|orientation| orientation.name() == name)
+    }
+
+    /// Name used in tool arguments and results
+    pub fn name(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        }
+    }
+}
+
+// ============================================================================
+// Backend Trait
+// ============================================================================
+
+/// A platform that devices can be driven through
+///
+/// Coordinates are in device pixels. Methods block until the device answers
+/// and should check `cancel::check()` (or use CancellableCommand) so a
+/// cancelled request stops early.
+pub trait DeviceBackend: Send + Sync {
+    /// Platform name used in tool arguments and DeviceInfo (e.g., "android")
+    fn platform(&self) -> &'static str;
+
+    /// Copy for another request thread
+    fn clone_backend(&self) -> Box<dyn DeviceBackend>;
+
+    /// Devices of this platform that can be used right now
+    fn list_devices(&mut self) -> Result<Vec<DeviceInfo>, String>;
+
+    /// PNG screenshot of the current screen
+    fn take_screenshot(&mut self, device_id: &str, progress: Progress) -> Result<Vec<u8>, String>;
+
+    fn tap(&mut self, device_id: &str, x: f64, y: f64) -> Result<String, String>;
+
+    fn double_tap(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        progress: Progress,
+    ) -> Result<String, String>;
+
+    fn long_press(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        duration_ms: u32,
+    ) -> Result<String, String>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn swipe(
+        &mut self,
+        device_id: &str,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        duration_ms: u32,
+    ) -> Result<String, String>;
+
+    /// Type text into the focused field
+    fn type_text(&mut self, device_id: &str, text: &str) -> Result<String, String>;
+
+    /// Press a hardware button, or fail if the platform doesn't have it
+    fn press_button(&mut self, device_id: &str, button: Button) -> Result<String, String>;
+
+    /// Buttons press_button accepts
+    fn buttons(&self) -> &'static [Button] {
+        &Button::ALL
+    }
+
+    fn screen_size(&mut self, device_id: &str) -> Result<ScreenSize, String>;
+
+    fn orientation(&mut self, device_id: &str) -> Result<Orientation, String>;
+
+    fn set_orientation(
+        &mut self,
+        device_id: &str,
+        orientation: Orientation,
+    ) -> Result<String, String>;
+
+    fn list_apps(&mut self, device_id: &str) -> Result<Vec<InstalledApp>, String>;
+
+    /// Visible elements, optionally only those whose text or id contains `filter`
+    fn list_elements(
+        &mut self,
+        device_id: &str,
+        filter: Option<&str>,
+        progress: Progress,
+    ) -> Result<Vec<ScreenElement>, String>;
+
+    /// Identify what is in the foreground (used to detect screen changes)
+    fn foreground_activity(&mut self, _device_id: &str) -> Result<String, String> {
+        Err("Not implemented for this platform".to_string())
+    }
+
+    fn launch_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String>;
+
+    fn terminate_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String>;
+
+    fn install_app(
+        &mut self,
+        device_id: &str,
+        app_path: &str,
+        progress: Progress,
+    ) -> Result<String, String>;
+
+    fn uninstall_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String>;
+
+    fn open_url(&mut self, device_id: &str, url: &str) -> Result<String, String>;
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// The backends a manager can dispatch to, by platform
+pub struct BackendRegistry {
+    backends: Vec<Box<dyn DeviceBackend>>,
+}
+
+impl Clone for BackendRegistry {
+    fn clone(&self) -> Self {
+        Self {
+            backends: self
+                .backends
+                .iter()
+                .map(|backend| backend.clone_backend())
+                .collect(),
+        }
+    }
+}
+
+impl Default for BackendRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(super::AndroidDeviceManager::new()));
+        registry.register(Box::new(super::IOSDeviceManager::new()));
+        if let Some(mock) = super::mock::backend() {
+            registry.register(Box::new(mock));
+        }
+        registry
+    }
+}
+
+impl BackendRegistry {
+    /// Registry without any backend
+    pub fn empty() -> Self {
+        Self {
+            backends: Vec::new(),
+        }
+    }
+
+    /// Add a backend, replacing one already registered for its platform
+    pub fn register(&mut self, backend: Box<dyn DeviceBackend>) {
+        self.backends
+            .retain(|existing| existing.platform() != backend.platform());
+        self.backends.push(backend);
+    }
+
+    /// Backend for a platform
+    pub fn get(&mut self, platform: &str) -> Option<&mut dyn DeviceBackend> {
+        self.backends
+            .iter_mut()
+            .find(|backend| backend.platform() == platform)
+            .map(|backend| backend.as_mut() as &mut dyn DeviceBackend)
+    }
+
+    /// All backends, in registration order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn DeviceBackend> + '_ {
+        self.backends
+            .iter_mut()
+            .map(|backend| backend.as_mut() as &mut dyn DeviceBackend)
+    }
+
+    /// Names of the registered platforms
+    pub fn platforms(&self) -> Vec<&'static str> {
+        self.backends
+            .iter()
+            .map(|backend| backend.platform())
+            .collect()
+    }
+
+    /// Names of the platforms of the default registry
+    ///
+    /// Tool schemas list these. They are looked up once, since creating the
+    /// backends probes the platform tools, and don't change after startup
+    /// (which enables the mock device, if at all).
+    pub fn default_platforms() -> &'static [&'static str] {
+        static PLATFORMS: OnceLock<Vec<&'static str>> = OnceLock::new();
+        PLATFORMS.get_or_init(|| Self::default().platforms())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_names() {
+        assert!(matches!(
+            Button::from_name("VOLUME_UP"),
+            Some(Button::VolumeUp)
+        ));
+        assert!(matches!(Button::from_name("dpad_up"), Some(Button::DpadUp)));
+        assert!(matches!(Button::from_name("search"), Some(Button::Back)));
+        assert!(matches!(
+            Button::from_name("app_switch"),
+            Some(Button::Menu)
+        ));
+        assert!(Button::from_name("selfie").is_none());
+    }
+
+    #[test]
+    fn test_orientation_names() {
+        for name in ["portrait", "landscape"] {
+            assert_eq!(Orientation::from_name(name).unwrap().name(), name);
+        }
+        assert!(Orientation::from_name("upside_down").is_none());
+    }
+
+    #[test]
+    fn test_default_registry_has_android_and_ios() {
+        let mut registry = BackendRegistry::default();
+        assert_eq!(registry.platforms(), vec!["android", "ios"]);
+        assert!(registry.get("ios").is_some());
+        assert!(registry.get("windows").is_none());
+
+        // Registering a platform again replaces its backend
+        registry.register(Box::new(super::super::IOSDeviceManager::new()));
+        assert_eq!(registry.platforms(), vec!["android", "ios"]);
+    }
+}
@@ -0,0 +1,323 @@
+// mobile-mcp-zed-extension/src/devices/cancel.rs
+// Cancelling In-Flight Device Operations
+//
+// Device operations are blocking (adb socket reads, xcrun child processes)
+// and run on a worker thread. The server runs each request inside
+// `CancellationToken::run`, which makes the token the thread's current one.
+// Device code then checks it between steps (`check`) and registers how to
+// abort the step that is running (`on_cancel`): killing the xcrun child or
+// the remote process behind an adb shell command. Once that process dies the
+// blocking read returns and the operation fails with `CANCELLED`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+/// Error message of an operation aborted by cancellation
+pub const CANCELLED: &str = "Request cancelled";
+
+/// How often a cancellable child process is polled
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    next_hook: AtomicU64,
+    hooks: Mutex<HashMap<u64, Hook>>,
+    notify: Notify,
+}
+
+/// Cancellation flag shared between a request and the code running it
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the operation and run every registered abort hook
+    ///
+    /// Cancelling twice is a no-op.
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.state.notify.notify_waiters();
+
+        let hooks: Vec<Hook> = {
+            let mut hooks = self.state.hooks.lock().unwrap_or_else(|e| e.into_inner());
+            hooks.drain().map(|(_, hook)| hook).collect()
+        };
+        for hook in hooks {
+            hook();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let notified = self.state.notify.notified();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Run blocking device code with this token as the thread's current one
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+
+    /// Register a hook run on cancellation, removed when the guard drops
+    ///
+    /// If the token is already cancelled the hook runs right away.
+    fn register(&self, hook: Hook) -> Option<CancelHook> {
+        let id = self.state.next_hook.fetch_add(1, Ordering::SeqCst);
+        self.state
+            .hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, hook);
+
+        // cancel() may have drained the hooks before this one was inserted
+        if self.is_cancelled() {
+            let hook = self
+                .state
+                .hooks
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&id);
+            if let Some(hook) = hook {
+                hook();
+            }
+            return None;
+        }
+
+        Some(CancelHook {
+            token: self.clone(),
+            id,
+        })
+    }
+}
+
+/// Keeps an abort hook registered while its operation runs
+pub struct CancelHook {
+    token: CancellationToken,
+    id: u64,
+}
+
+impl Drop for CancelHook {
+    fn drop(&mut self) {
+        self.token
+            .state
+            .hooks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+/// Token of the request running on this thread, if any
+fn current() -> Option<CancellationToken> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Fail with `CANCELLED` if the current request was cancelled
+pub fn check() -> Result<(), String> {
+    match current() {
+        Some(token) if token.is_cancelled() => Err(CANCELLED.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Abort the running step if the current request is cancelled
+///
+/// # Arguments
+/// * `hook` - Kills whatever the step is blocked on
+///
+/// # Returns
+/// A guard to keep alive until the step completes (None outside a request
+/// or when the hook already ran)
+pub fn on_cancel(hook: impl FnOnce() + Send + 'static) -> Option<CancelHook> {
+    current()?.register(Box::new(hook))
+}
+
+/// `Command::output` that kills the child when the current request is cancelled
+///
+/// Only the xcrun/libimobiledevice tools are run as child processes, so this
+/// is unused on other platforms.
+///
+/// The run is recorded (or replayed) with --record/--replay.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub trait CancellableCommand {
+    fn output_cancellable(&mut self) -> io::Result<Output>;
+}
+
+impl CancellableCommand for Command {
+    fn output_cancellable(&mut self) -> io::Result<Output> {
+        let program = self.get_program().to_string_lossy().into_owned();
+        let args = self
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        crate::recording::command(&program, args, || run_cancellable(self))
+    }
+}
+
+/// Run a child process, killing it if the current request is cancelled
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn run_cancellable(command: &mut Command) -> io::Result<Output> {
+    let Some(token) = current() else {
+        return command.output();
+    };
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes so a chatty child can't block on a full buffer
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = loop {
+        if token.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::Interrupted, CANCELLED));
+        }
+        match child.try_wait()? {
+            Some(status) => break status,
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Read a child pipe to the end on a helper thread
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_check_outside_request() {
+        assert!(check().is_ok());
+        assert!(on_cancel(|| {}).is_none());
+    }
+
+    #[test]
+    fn test_check_after_cancel() {
+        let token = CancellationToken::new();
+        token.run(|| assert!(check().is_ok()));
+        token.cancel();
+        token.run(|| assert_eq!(check(), Err(CANCELLED.to_string())));
+        // The thread's token is restored afterwards
+        assert!(check().is_ok());
+    }
+
+    #[test]
+    fn test_hooks_run_once_while_registered() {
+        let token = CancellationToken::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let counter = Arc::clone(&calls);
+        let finished = token.run(|| {
+            on_cancel(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+        drop(finished);
+
+        let counter = Arc::clone(&calls);
+        let _running = token.run(|| {
+            on_cancel(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+
+        token.cancel();
+        token.cancel();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_hook_after_cancel_runs_immediately() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let guard = token.run(|| {
+            on_cancel(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+        assert!(guard.is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancel_kills_child_process() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = token.run(|| Command::new("sleep").arg("10").output_cancellable());
+        handle.join().unwrap();
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_without_request() {
+        let output = Command::new("echo").arg("hi").output_cancellable().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hi");
+    }
+}
@@ -1,6 +1,9 @@
 // mobile-mcp-zed-extension/src/devices/ios.rs
 // Comprehensive iOS Device Management Module with full feature parity
 
+#[cfg(target_os = "macos")]
+use super::cancel::CancellableCommand;
+use crate::mcp::logging::{self, LogLevel};
 use crate::types::DeviceInfo;
 use std::fs;
 use std::path::Path;
@@ -9,27 +12,29 @@ use std::process::{Command, Stdio};
 #[cfg(all(target_os = "macos", feature = "ios-support"))]
 use idevice::usbmuxd::UsbmuxdConnection;
 
-// Re-export Android types for iOS compatibility
-use crate::devices::android::{
-    Button, InstalledApp, Orientation, ScreenElement, ScreenSize, SwipeDirection,
-};
+use super::backend::{Button, DeviceBackend, InstalledApp, Orientation, ScreenElement, ScreenSize};
+use super::Progress;
 
+#[derive(Clone)]
 pub struct IOSDeviceManager {
-    #[allow(dead_code)]
-    debug: bool,
     #[allow(dead_code)]
     idevice_available: bool,
     #[allow(dead_code)]
     xcrun_available: bool,
 }
 
+impl Default for IOSDeviceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl IOSDeviceManager {
-    pub fn new(debug: bool) -> Self {
+    pub fn new() -> Self {
         let idevice_available = Self::is_idevice_available();
         let xcrun_available = Self::is_xcrun_available();
 
         Self {
-            debug,
             idevice_available,
             xcrun_available,
         }
@@ -37,9 +42,7 @@ impl IOSDeviceManager {
 
     #[allow(dead_code)]
     fn log_debug(&self, message: &str) {
-        if self.debug {
-            eprintln!("[DEBUG] iOS: {}", message);
-        }
+        logging::log(LogLevel::Debug, logging::SIMCTL, message);
     }
 
     // ============================================================================
@@ -78,6 +81,11 @@ impl IOSDeviceManager {
             return Ok(devices);
         }
 
+        // usbmuxd isn't part of recorded sessions
+        if crate::recording::is_replaying() {
+            return Ok(devices);
+        }
+
         self.log_debug("Querying real iOS devices with idevice crate");
 
         // Try to connect to usbmuxd to get device list
@@ -166,7 +174,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "list", "devices", "available", "--json"])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -285,7 +293,7 @@ impl IOSDeviceManager {
 
         match Command::new("idevicescreenshot")
             .args(["-u", device_id, &temp_path])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() && Path::new(&temp_path).exists() {
@@ -321,7 +329,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "io", device_id, "screenshot", "--type=png", "-"])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() && !output.stdout.is_empty() {
@@ -365,7 +373,7 @@ impl IOSDeviceManager {
                     &x.to_string(),
                     &y.to_string(),
                 ])
-                .output()
+                .output_cancellable()
             {
                 if output.status.success() {
                     self.log_debug("Simulator tap executed successfully");
@@ -441,7 +449,7 @@ impl IOSDeviceManager {
                 &x.to_string(),
                 &y.to_string(),
             ])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -502,7 +510,7 @@ impl IOSDeviceManager {
                 &end_x.to_string(),
                 &end_y.to_string(),
             ])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -550,7 +558,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "io", device_id, "type", text])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -597,7 +605,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "io", device_id, "press", button_name])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -638,7 +646,7 @@ impl IOSDeviceManager {
         // Get device info including screen dimensions
         match Command::new("xcrun")
             .args(["simctl", "list", "devices", "-j"])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -762,7 +770,7 @@ impl IOSDeviceManager {
         // Check device status including orientation
         match Command::new("xcrun")
             .args(["simctl", "status_bar", device_id, "list"])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -810,7 +818,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "io", device_id, "orientation", orientation_str])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -851,7 +859,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "listapps", device_id])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -905,7 +913,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "launch", device_id, bundle_id])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -941,7 +949,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "terminate", device_id, bundle_id])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -982,7 +990,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "install", device_id, app_path])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -1018,7 +1026,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "uninstall", device_id, bundle_id])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -1055,7 +1063,7 @@ impl IOSDeviceManager {
 
         match Command::new("xcrun")
             .args(["simctl", "openurl", device_id, url])
-            .output()
+            .output_cancellable()
         {
             Ok(output) => {
                 if output.status.success() {
@@ -1148,3 +1156,134 @@ impl IOSDeviceManager {
         false
     }
 }
+
+/// iOS as a DeviceBackend
+///
+/// Calls are spelled `IOSDeviceManager::name(self, ..)` so they reach the
+/// inherent methods above, not these trait methods of the same name.
+impl DeviceBackend for IOSDeviceManager {
+    fn platform(&self) -> &'static str {
+        "ios"
+    }
+
+    fn buttons(&self) -> &'static [Button] {
+        &[
+            Button::Home,
+            Button::Power,
+            Button::VolumeUp,
+            Button::VolumeDown,
+        ]
+    }
+
+    fn clone_backend(&self) -> Box<dyn DeviceBackend> {
+        Box::new(self.clone())
+    }
+
+    fn list_devices(&mut self) -> Result<Vec<DeviceInfo>, String> {
+        IOSDeviceManager::list_devices(self)
+    }
+
+    fn take_screenshot(&mut self, device_id: &str, progress: Progress) -> Result<Vec<u8>, String> {
+        progress(0.0, "Capturing screen");
+        IOSDeviceManager::take_screenshot(self, device_id)
+    }
+
+    fn tap(&mut self, device_id: &str, x: f64, y: f64) -> Result<String, String> {
+        self.tap_screen(device_id, x, y)
+    }
+
+    fn double_tap(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        _progress: Progress,
+    ) -> Result<String, String> {
+        self.double_tap_screen(device_id, x, y)
+    }
+
+    fn long_press(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        duration_ms: u32,
+    ) -> Result<String, String> {
+        self.long_press_screen(device_id, x, y, duration_ms as u64)
+    }
+
+    fn swipe(
+        &mut self,
+        device_id: &str,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+        _duration_ms: u32,
+    ) -> Result<String, String> {
+        self.swipe_screen(device_id, start_x, start_y, end_x, end_y)
+    }
+
+    fn type_text(&mut self, device_id: &str, text: &str) -> Result<String, String> {
+        IOSDeviceManager::type_text(self, device_id, text)
+    }
+
+    fn press_button(&mut self, device_id: &str, button: Button) -> Result<String, String> {
+        IOSDeviceManager::press_button(self, device_id, button)
+    }
+
+    fn screen_size(&mut self, device_id: &str) -> Result<ScreenSize, String> {
+        self.get_screen_size(device_id)
+    }
+
+    fn orientation(&mut self, device_id: &str) -> Result<Orientation, String> {
+        self.get_orientation(device_id)
+    }
+
+    fn set_orientation(
+        &mut self,
+        device_id: &str,
+        orientation: Orientation,
+    ) -> Result<String, String> {
+        IOSDeviceManager::set_orientation(self, device_id, orientation)
+    }
+
+    fn list_apps(&mut self, device_id: &str) -> Result<Vec<InstalledApp>, String> {
+        IOSDeviceManager::list_apps(self, device_id)
+    }
+
+    fn list_elements(
+        &mut self,
+        device_id: &str,
+        filter: Option<&str>,
+        _progress: Progress,
+    ) -> Result<Vec<ScreenElement>, String> {
+        self.list_elements_on_screen(device_id, filter)
+    }
+
+    fn launch_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        IOSDeviceManager::launch_app(self, device_id, app_id)
+    }
+
+    fn terminate_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        IOSDeviceManager::terminate_app(self, device_id, app_id)
+    }
+
+    fn install_app(
+        &mut self,
+        device_id: &str,
+        app_path: &str,
+        progress: Progress,
+    ) -> Result<String, String> {
+        progress(0.0, "Installing");
+        IOSDeviceManager::install_app(self, device_id, app_path)
+    }
+
+    fn uninstall_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        IOSDeviceManager::uninstall_app(self, device_id, app_id)
+    }
+
+    fn open_url(&mut self, device_id: &str, url: &str) -> Result<String, String> {
+        IOSDeviceManager::open_url(self, device_id, url)
+    }
+}
@@ -0,0 +1,55 @@
+// mobile-mcp-zed-extension/src/devices/locks.rs
+// Per-Device Locks
+//
+// Requests for different devices run concurrently, but two operations on the
+// same device would interleave their taps, dumps and installs. Each device id
+// gets its own async lock, held for the whole operation.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Lock per device id, created on first use
+#[derive(Clone, Default)]
+pub struct DeviceLocks {
+    locks: Arc<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>>,
+}
+
+impl DeviceLocks {
+    /// Wait until no other operation uses the device
+    ///
+    /// # Returns
+    /// A guard that releases the device when dropped
+    pub async fn acquire(&self, device_id: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(locks.entry(device_id.to_string()).or_default())
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_same_device_is_exclusive() {
+        let locks = DeviceLocks::default();
+        let guard = locks.acquire("emulator-5554").await;
+
+        // A different device is not blocked
+        let _other = locks.acquire("emulator-5556").await;
+
+        let waiting = tokio::spawn({
+            let locks = locks.clone();
+            async move { locks.acquire("emulator-5554").await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(guard);
+        assert!(waiting.await.is_ok());
+    }
+}
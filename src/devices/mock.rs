@@ -0,0 +1,897 @@
+// mobile-mcp-zed-extension/src/devices/mock.rs
+// In-Memory Mock Device
+//
+// With MOBILE_PLATFORM=mock the server drives a simulated device instead of
+// adb or simctl, so every tool can be exercised in CI or while developing
+// prompts, with nothing installed. The device is described by a JSON or YAML
+// fixture (MOBILE_DEVICE_MCP_MOCK_FIXTURE, YAML for .yaml/.yml files), or a
+// small built-in demo without one:
+//
+// ```json
+// {
+//   "device": {"id": "mock-device", "name": "Mock Phone", "width": 1080, "height": 2400},
+//   "apps": [{"package_name": "com.example.shop", "app_name": "Shop", "screen": "shop"}],
+//   "start": "home",
+//   "screens": {
+//     "home": {
+//       "screenshot": "home.png",
+//       "elements": [
+//         {"type": "Icon", "text": "Shop", "rect": {"x": 100, "y": 400, "width": 200, "height": 200}, "on_tap": "shop"}
+//       ]
+//     },
+//     "shop": {"app": "com.example.shop", "back": "home", "elements": []}
+//   }
+// }
+// ```
+//
+// Screenshots are PNG files relative to the fixture. Tapping inside an
+// element's rect focuses it (type_keys then fills it in) and moves to its
+// `on_tap` screen; back goes to the screen's `back`, home to `start`, and
+// launching an app to the app's `screen`. Elements may nest in `children`.
+//
+// The state (current screen, installed apps, typed text) is shared by every
+// request of the process, like a real device.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use super::backend::{
+    Button, DeviceBackend, InstalledApp, Orientation, ScreenElement, ScreenElementRect, ScreenSize,
+};
+use super::Progress;
+use crate::types::DeviceInfo;
+
+/// Screenshot of screens without one: a 1x1 gray PNG
+const PLACEHOLDER_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x7e, 0x9b,
+    0x55, 0x00, 0x00, 0x00, 0x0a, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0x68, 0x00, 0x00, 0x00,
+    0x82, 0x00, 0x81, 0x77, 0xcd, 0x72, 0xb6, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+    0x42, 0x60, 0x82,
+];
+
+/// Device used when no fixture is given
+const DEMO_FIXTURE: &str = r#"{
+  "apps": [
+    {"package_name": "com.android.settings", "app_name": "Settings", "screen": "settings"},
+    {"package_name": "com.google.android.youtube", "app_name": "YouTube", "screen": "youtube"}
+  ],
+  "start": "home",
+  "screens": {
+    "home": {
+      "app": "com.android.launcher3",
+      "elements": [
+        {
+          "type": "android.widget.EditText",
+          "text": "Search",
+          "identifier": "com.android.launcher3:id/search_box",
+          "rect": {"x": 60, "y": 150, "width": 960, "height": 120}
+        },
+        {
+          "type": "android.widget.TextView",
+          "text": "Settings",
+          "rect": {"x": 100, "y": 400, "width": 200, "height": 200},
+          "on_tap": "settings"
+        },
+        {
+          "type": "android.widget.TextView",
+          "text": "YouTube",
+          "rect": {"x": 400, "y": 400, "width": 200, "height": 200},
+          "on_tap": "youtube"
+        }
+      ]
+    },
+    "settings": {
+      "app": "com.android.settings",
+      "back": "home",
+      "elements": [
+        {
+          "type": "android.widget.FrameLayout",
+          "identifier": "com.android.settings:id/main_content",
+          "rect": {"x": 0, "y": 200, "width": 1080, "height": 2200},
+          "children": [
+            {
+              "type": "android.widget.TextView",
+              "text": "Network & internet",
+              "rect": {"x": 0, "y": 300, "width": 1080, "height": 150}
+            },
+            {
+              "type": "android.widget.TextView",
+              "text": "Display",
+              "rect": {"x": 0, "y": 450, "width": 1080, "height": 150}
+            },
+            {
+              "type": "android.widget.TextView",
+              "text": "About phone",
+              "rect": {"x": 0, "y": 600, "width": 1080, "height": 150},
+              "on_tap": "about"
+            }
+          ]
+        }
+      ]
+    },
+    "about": {
+      "app": "com.android.settings",
+      "back": "settings",
+      "elements": [
+        {
+          "type": "android.widget.TextView",
+          "text": "Android version",
+          "rect": {"x": 0, "y": 300, "width": 1080, "height": 150}
+        },
+        {
+          "type": "android.widget.TextView",
+          "text": "14",
+          "rect": {"x": 0, "y": 450, "width": 1080, "height": 100}
+        }
+      ]
+    },
+    "youtube": {
+      "app": "com.google.android.youtube",
+      "back": "home",
+      "elements": [
+        {
+          "type": "android.widget.EditText",
+          "label": "Search YouTube",
+          "identifier": "com.google.android.youtube:id/search_edit_text",
+          "rect": {"x": 60, "y": 150, "width": 960, "height": 120}
+        }
+      ]
+    }
+  }
+}"#;
+
+static DEVICE: OnceLock<Arc<MockDevice>> = OnceLock::new();
+
+/// Load the mock device for this process
+///
+/// # Arguments
+/// * `fixture` - Fixture file, or None for the built-in demo
+pub fn enable(fixture: Option<&Path>) -> Result<(), String> {
+    let device = match fixture {
+        Some(path) => MockDevice::load(path)?,
+        None => MockDevice::demo(),
+    };
+    DEVICE
+        .set(Arc::new(device))
+        .map_err(|_| "Mock device already loaded".to_string())
+}
+
+/// Backend for the loaded mock device, if any
+pub fn backend() -> Option<MockBackend> {
+    DEVICE.get().cloned().map(MockBackend::new)
+}
+
+// ============================================================================
+// Fixture Format
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fixture {
+    #[serde(default)]
+    device: FixtureDevice,
+    #[serde(default)]
+    apps: Vec<FixtureApp>,
+    start: String,
+    screens: HashMap<String, FixtureScreen>,
+    /// URL to the screen open_url shows
+    #[serde(default)]
+    urls: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct FixtureDevice {
+    id: String,
+    name: String,
+    width: u32,
+    height: u32,
+}
+
+impl Default for FixtureDevice {
+    fn default() -> Self {
+        Self {
+            id: "mock-device".to_string(),
+            name: "Mock Phone".to_string(),
+            width: 1080,
+            height: 2400,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureApp {
+    package_name: String,
+    #[serde(default)]
+    app_name: Option<String>,
+    /// Screen shown when the app is launched
+    #[serde(default)]
+    screen: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct FixtureScreen {
+    /// PNG file, relative to the fixture
+    screenshot: Option<String>,
+    /// Package of the app this screen belongs to
+    app: Option<String>,
+    /// Screen the back button goes to
+    back: Option<String>,
+    elements: Vec<FixtureElement>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureElement {
+    #[serde(rename = "type", default)]
+    element_type: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    identifier: Option<String>,
+    rect: ScreenElementRect,
+    /// Screen a tap inside the rect goes to
+    #[serde(default)]
+    on_tap: Option<String>,
+    #[serde(default)]
+    children: Vec<FixtureElement>,
+}
+
+// ============================================================================
+// Device State
+// ============================================================================
+
+/// A loaded fixture and the device's current state
+pub struct MockDevice {
+    info: FixtureDevice,
+    /// Apps installed when the server starts
+    apps: Vec<FixtureApp>,
+    start: String,
+    screens: HashMap<String, Screen>,
+    urls: HashMap<String, String>,
+    state: Mutex<State>,
+}
+
+struct Screen {
+    screenshot: Vec<u8>,
+    app: Option<String>,
+    back: Option<String>,
+    /// The element tree in document order (parents before children)
+    elements: Vec<Element>,
+}
+
+struct Element {
+    element: ScreenElement,
+    on_tap: Option<String>,
+}
+
+struct State {
+    screen: String,
+    orientation: Orientation,
+    installed: Vec<InstalledApp>,
+    /// Index of the focused element on the current screen
+    focused: Option<usize>,
+    /// Text typed into elements, by screen and element index
+    typed: HashMap<(String, usize), String>,
+}
+
+impl MockDevice {
+    /// Load a fixture file (YAML for .yaml/.yml, JSON otherwise);
+    /// screenshots are read relative to it
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read mock fixture {}: {}", path.display(), e))?;
+        let is_yaml = path
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml");
+        let fixture: Result<Fixture, String> = if is_yaml {
+            serde_yaml::from_str(&text).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        };
+        let fixture =
+            fixture.map_err(|e| format!("Invalid mock fixture {}: {}", path.display(), e))?;
+        Self::new(fixture, path.parent().unwrap_or(Path::new(".")))
+            .map_err(|e| format!("Invalid mock fixture {}: {}", path.display(), e))
+    }
+
+    /// The built-in demo: a launcher with Settings and YouTube
+    pub fn demo() -> Self {
+        let fixture = serde_json::from_str(DEMO_FIXTURE).expect("built-in mock fixture is valid");
+        Self::new(fixture, Path::new(".")).expect("built-in mock fixture is valid")
+    }
+
+    /// The built-in demo under another device id
+    #[cfg(test)]
+    pub fn demo_with_id(id: &str) -> Self {
+        let mut fixture: Fixture =
+            serde_json::from_str(DEMO_FIXTURE).expect("built-in mock fixture is valid");
+        fixture.device.id = id.to_string();
+        Self::new(fixture, Path::new(".")).expect("built-in mock fixture is valid")
+    }
+
+    fn new(fixture: Fixture, dir: &Path) -> Result<Self, String> {
+        let check_screen = |name: &str, used_by: &str| {
+            if fixture.screens.contains_key(name) {
+                Ok(())
+            } else {
+                Err(format!("{} refers to unknown screen \"{}\"", used_by, name))
+            }
+        };
+        check_screen(&fixture.start, "start")?;
+        for app in &fixture.apps {
+            if let Some(screen) = &app.screen {
+                check_screen(screen, &format!("app {}", app.package_name))?;
+            }
+        }
+        for (url, screen) in &fixture.urls {
+            check_screen(screen, &format!("url {}", url))?;
+        }
+
+        let mut screens = HashMap::new();
+        for (name, screen) in &fixture.screens {
+            let mut elements = Vec::new();
+            flatten(&screen.elements, &mut elements);
+            for target in elements
+                .iter()
+                .filter_map(|element| element.on_tap.as_deref())
+                .chain(screen.back.as_deref())
+            {
+                check_screen(target, &format!("screen {}", name))?;
+            }
+
+            let screenshot = match &screen.screenshot {
+                Some(file) => std::fs::read(dir.join(file))
+                    .map_err(|e| format!("screen {}: cannot read {}: {}", name, file, e))?,
+                None => PLACEHOLDER_PNG.to_vec(),
+            };
+            screens.insert(
+                name.clone(),
+                Screen {
+                    screenshot,
+                    app: screen.app.clone(),
+                    back: screen.back.clone(),
+                    elements,
+                },
+            );
+        }
+
+        let state = State {
+            screen: fixture.start.clone(),
+            orientation: Orientation::Portrait,
+            installed: fixture.apps.iter().map(installed_app).collect(),
+            focused: None,
+            typed: HashMap::new(),
+        };
+        Ok(Self {
+            info: fixture.device,
+            apps: fixture.apps,
+            start: fixture.start,
+            screens,
+            urls: fixture.urls,
+            state: Mutex::new(state),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The device's state, or an error for any other device id
+    fn state(&self, device_id: &str) -> Result<MutexGuard<'_, State>, String> {
+        if device_id == self.info.id {
+            Ok(self.lock())
+        } else {
+            Err(format!("Device not found: {}", device_id))
+        }
+    }
+
+    fn screen(&self, name: &str) -> &Screen {
+        // Every screen name in the state was checked when loading
+        &self.screens[name]
+    }
+
+    /// Index of the topmost element at a point on the current screen
+    fn element_at(&self, state: &State, x: f64, y: f64) -> Option<usize> {
+        let (x, y) = (x as i32, y as i32);
+        self.screen(&state.screen)
+            .elements
+            .iter()
+            .rposition(|Element { element, .. }| {
+                let rect = &element.rect;
+                x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
+            })
+    }
+
+    /// Focus the element under a point and follow its `on_tap`
+    fn tap(&self, state: &mut State, x: f64, y: f64) {
+        let Some(index) = self.element_at(state, x, y) else {
+            state.focused = None;
+            return;
+        };
+        state.focused = Some(index);
+        if let Some(target) = &self.screen(&state.screen).elements[index].on_tap {
+            go_to(state, target);
+        }
+    }
+}
+
+/// Flatten an element tree, parents before their children
+fn flatten(elements: &[FixtureElement], out: &mut Vec<Element>) {
+    for element in elements {
+        out.push(Element {
+            element: ScreenElement {
+                element_type: element.element_type.clone(),
+                text: element.text.clone(),
+                label: element
+                    .label
+                    .clone()
+                    .or_else(|| element.text.clone())
+                    .or_else(|| element.identifier.clone())
+                    .unwrap_or_else(|| element.element_type.clone()),
+                rect: element.rect.clone(),
+                focused: Some(false),
+                identifier: element.identifier.clone(),
+            },
+            on_tap: element.on_tap.clone(),
+        });
+        flatten(&element.children, out);
+    }
+}
+
+fn installed_app(app: &FixtureApp) -> InstalledApp {
+    InstalledApp {
+        package_name: app.package_name.clone(),
+        app_name: app
+            .app_name
+            .clone()
+            .unwrap_or_else(|| app.package_name.clone()),
+    }
+}
+
+fn go_to(state: &mut State, screen: &str) {
+    state.screen = screen.to_string();
+    state.focused = None;
+}
+
+// ============================================================================
+// Backend
+// ============================================================================
+
+/// DeviceBackend for the "mock" platform
+#[derive(Clone)]
+pub struct MockBackend {
+    device: Arc<MockDevice>,
+    platform: &'static str,
+}
+
+impl MockBackend {
+    pub fn new(device: Arc<MockDevice>) -> Self {
+        Self {
+            device,
+            platform: "mock",
+        }
+    }
+
+    /// The backend under another platform name, for tests that need
+    /// devices on two platforms
+    #[cfg(test)]
+    pub fn on_platform(self, platform: &'static str) -> Self {
+        Self { platform, ..self }
+    }
+}
+
+impl DeviceBackend for MockBackend {
+    fn platform(&self) -> &'static str {
+        self.platform
+    }
+
+    fn clone_backend(&self) -> Box<dyn DeviceBackend> {
+        Box::new(self.clone())
+    }
+
+    fn list_devices(&mut self) -> Result<Vec<DeviceInfo>, String> {
+        let info = &self.device.info;
+        Ok(vec![DeviceInfo {
+            id: info.id.clone(),
+            name: info.name.clone(),
+            platform: self.platform.to_string(),
+            device_type: "mock".to_string(),
+            state: "connected".to_string(),
+        }])
+    }
+
+    fn take_screenshot(&mut self, device_id: &str, progress: Progress) -> Result<Vec<u8>, String> {
+        progress(0.0, "Capturing screen");
+        let state = self.device.state(device_id)?;
+        Ok(self.device.screen(&state.screen).screenshot.clone())
+    }
+
+    fn tap(&mut self, device_id: &str, x: f64, y: f64) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        self.device.tap(&mut state, x, y);
+        Ok(format!("Tapped at ({}, {}) on device {}", x, y, device_id))
+    }
+
+    fn double_tap(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        _progress: Progress,
+    ) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        self.device.tap(&mut state, x, y);
+        Ok(format!(
+            "Double tapped at ({}, {}) on device {}",
+            x, y, device_id
+        ))
+    }
+
+    fn long_press(
+        &mut self,
+        device_id: &str,
+        x: f64,
+        y: f64,
+        _duration_ms: u32,
+    ) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        self.device.tap(&mut state, x, y);
+        Ok(format!("Long pressed at ({}, {})", x, y))
+    }
+
+    fn swipe(
+        &mut self,
+        device_id: &str,
+        _start_x: f64,
+        _start_y: f64,
+        _end_x: f64,
+        _end_y: f64,
+        _duration_ms: u32,
+    ) -> Result<String, String> {
+        let _state = self.device.state(device_id)?;
+        Ok("Swipe executed".to_string())
+    }
+
+    fn type_text(&mut self, device_id: &str, text: &str) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        if let Some(index) = state.focused {
+            let key = (state.screen.clone(), index);
+            state.typed.entry(key).or_default().push_str(text);
+        }
+        Ok(format!("Typed '{}' on device {}", text, device_id))
+    }
+
+    fn press_button(&mut self, device_id: &str, button: Button) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        match button {
+            Button::Home => go_to(&mut state, &self.device.start),
+            Button::Back => {
+                if let Some(back) = &self.device.screen(&state.screen).back {
+                    go_to(&mut state, back);
+                }
+            }
+            _ => {}
+        }
+        Ok(format!(
+            "Pressed {:?} button on device {}",
+            button, device_id
+        ))
+    }
+
+    fn screen_size(&mut self, device_id: &str) -> Result<ScreenSize, String> {
+        let _state = self.device.state(device_id)?;
+        Ok(ScreenSize {
+            width: self.device.info.width,
+            height: self.device.info.height,
+            scale: 1.0,
+        })
+    }
+
+    fn orientation(&mut self, device_id: &str) -> Result<Orientation, String> {
+        Ok(self.device.state(device_id)?.orientation.clone())
+    }
+
+    fn set_orientation(
+        &mut self,
+        device_id: &str,
+        orientation: Orientation,
+    ) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        let name = orientation.name();
+        state.orientation = orientation;
+        Ok(format!("Set orientation to {}", name))
+    }
+
+    fn list_apps(&mut self, device_id: &str) -> Result<Vec<InstalledApp>, String> {
+        Ok(self.device.state(device_id)?.installed.clone())
+    }
+
+    fn list_elements(
+        &mut self,
+        device_id: &str,
+        filter: Option<&str>,
+        _progress: Progress,
+    ) -> Result<Vec<ScreenElement>, String> {
+        let state = self.device.state(device_id)?;
+        let screen = self.device.screen(&state.screen);
+        Ok(screen
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(index, Element { element, .. })| {
+                let mut element = element.clone();
+                if let Some(typed) = state.typed.get(&(state.screen.clone(), index)) {
+                    element.text = Some(typed.clone());
+                }
+                element.focused = Some(state.focused == Some(index));
+                element
+            })
+            .filter(|element| {
+                filter.is_none_or(|filter| {
+                    [&element.text, &element.identifier]
+                        .into_iter()
+                        .flatten()
+                        .chain([&element.label])
+                        .any(|value| value.contains(filter))
+                })
+            })
+            .collect())
+    }
+
+    fn foreground_activity(&mut self, device_id: &str) -> Result<String, String> {
+        let state = self.device.state(device_id)?;
+        Ok(match &self.device.screen(&state.screen).app {
+            Some(app) => format!("{}/{}", app, state.screen),
+            None => state.screen.clone(),
+        })
+    }
+
+    fn launch_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        if !state.installed.iter().any(|app| app.package_name == app_id) {
+            let available: Vec<String> = state
+                .installed
+                .iter()
+                .map(|app| format!("  - {} ({})", app.app_name, app.package_name))
+                .collect();
+            return Err(format!(
+                "App '{}' is not installed on this device.\n\nAvailable apps:\n{}",
+                app_id,
+                available.join("\n")
+            ));
+        }
+
+        let screen = self
+            .device
+            .apps
+            .iter()
+            .find(|app| app.package_name == app_id)
+            .and_then(|app| app.screen.clone());
+        if let Some(screen) = screen {
+            go_to(&mut state, &screen);
+        }
+        Ok(format!("Launched app: {}", app_id))
+    }
+
+    fn terminate_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        if self.device.screen(&state.screen).app.as_deref() == Some(app_id) {
+            go_to(&mut state, &self.device.start);
+        }
+        Ok(format!("Terminated app: {}", app_id))
+    }
+
+    fn install_app(
+        &mut self,
+        device_id: &str,
+        app_path: &str,
+        progress: Progress,
+    ) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        progress(0.0, "Installing");
+        // The package name is taken from the file name (com.example.app.apk)
+        let package_name = Path::new(app_path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("Installation failed: no file name in {}", app_path))?;
+        if !state
+            .installed
+            .iter()
+            .any(|app| app.package_name == package_name)
+        {
+            state.installed.push(InstalledApp {
+                app_name: package_name.clone(),
+                package_name,
+            });
+        }
+        Ok(format!("Installed app from: {}", app_path))
+    }
+
+    fn uninstall_app(&mut self, device_id: &str, app_id: &str) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        let before = state.installed.len();
+        state.installed.retain(|app| app.package_name != app_id);
+        if state.installed.len() == before {
+            return Err(format!(
+                "Uninstallation failed: Unknown package: {}",
+                app_id
+            ));
+        }
+        Ok(format!("Uninstalled app: {}", app_id))
+    }
+
+    fn open_url(&mut self, device_id: &str, url: &str) -> Result<String, String> {
+        let mut state = self.device.state(device_id)?;
+        if let Some(screen) = self.device.urls.get(url) {
+            go_to(&mut state, screen);
+        }
+        Ok(format!("Opened URL: {}", url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::no_progress;
+
+    const DEVICE: &str = "mock-device";
+
+    fn demo() -> MockBackend {
+        MockBackend::new(Arc::new(MockDevice::demo()))
+    }
+
+    fn texts(backend: &mut MockBackend) -> Vec<String> {
+        backend
+            .list_elements(DEVICE, None, &no_progress)
+            .unwrap()
+            .into_iter()
+            .map(|element| element.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_tap_follows_transitions_and_back_returns() {
+        let mut backend = demo();
+        assert!(texts(&mut backend).contains(&"Settings".to_string()));
+
+        backend.tap(DEVICE, 150.0, 450.0).unwrap();
+        assert_eq!(
+            backend.foreground_activity(DEVICE).unwrap(),
+            "com.android.settings/settings"
+        );
+        // Nested elements are listed with their parents
+        assert!(texts(&mut backend).contains(&"About phone".to_string()));
+
+        // The innermost element under the point gets the tap
+        backend.tap(DEVICE, 500.0, 650.0).unwrap();
+        assert!(texts(&mut backend).contains(&"Android version".to_string()));
+
+        backend.press_button(DEVICE, Button::Back).unwrap();
+        backend.press_button(DEVICE, Button::Home).unwrap();
+        assert_eq!(
+            backend.foreground_activity(DEVICE).unwrap(),
+            "com.android.launcher3/home"
+        );
+    }
+
+    #[test]
+    fn test_typed_text_fills_focused_element() {
+        let mut backend = demo();
+        backend.tap(DEVICE, 100.0, 200.0).unwrap();
+        backend.type_text(DEVICE, "weather").unwrap();
+
+        let elements = backend
+            .list_elements(DEVICE, Some("weather"), &no_progress)
+            .unwrap();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].focused, Some(true));
+    }
+
+    #[test]
+    fn test_apps_install_launch_uninstall() {
+        let mut backend = demo();
+        assert!(backend.launch_app(DEVICE, "com.example.shop").is_err());
+
+        backend
+            .install_app(DEVICE, "/builds/com.example.shop.apk", &no_progress)
+            .unwrap();
+        assert!(backend.launch_app(DEVICE, "com.example.shop").is_ok());
+
+        backend
+            .launch_app(DEVICE, "com.google.android.youtube")
+            .unwrap();
+        backend
+            .terminate_app(DEVICE, "com.google.android.youtube")
+            .unwrap();
+        assert_eq!(
+            backend.foreground_activity(DEVICE).unwrap(),
+            "com.android.launcher3/home"
+        );
+
+        backend.uninstall_app(DEVICE, "com.example.shop").unwrap();
+        assert!(backend.uninstall_app(DEVICE, "com.example.shop").is_err());
+    }
+
+    #[test]
+    fn test_other_device_ids_not_found() {
+        let mut backend = demo();
+        assert_eq!(
+            backend.tap("emulator-5554", 0.0, 0.0),
+            Err("Device not found: emulator-5554".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fixture_with_unknown_screen_rejected() {
+        let fixture: Fixture = serde_json::from_value(serde_json::json!({
+            "start": "home",
+            "screens": {"home": {"back": "nowhere"}}
+        }))
+        .unwrap();
+        let error = MockDevice::new(fixture, Path::new(".")).err().unwrap();
+        assert!(error.contains("nowhere"), "{}", error);
+    }
+
+    #[test]
+    fn test_screenshot_read_relative_to_fixture() {
+        let dir = std::env::temp_dir().join(format!("mock-fixture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("home.png"), PLACEHOLDER_PNG).unwrap();
+        std::fs::write(
+            dir.join("fixture.json"),
+            r#"{"start": "home", "screens": {"home": {"screenshot": "home.png"}}}"#,
+        )
+        .unwrap();
+
+        let device = MockDevice::load(&dir.join("fixture.json"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let mut backend = MockBackend::new(Arc::new(device.unwrap()));
+        assert_eq!(
+            backend.take_screenshot(DEVICE, &no_progress).unwrap(),
+            PLACEHOLDER_PNG
+        );
+    }
+
+    #[test]
+    fn test_yaml_fixture() {
+        let dir = std::env::temp_dir().join(format!("mock-yaml-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("home.png"), PLACEHOLDER_PNG).unwrap();
+        std::fs::write(
+            dir.join("fixture.yaml"),
+            r#"
+start: home
+screens:
+  home:
+    screenshot: home.png
+    elements:
+      - type: Button
+        text: Open
+        rect: {x: 0, y: 0, width: 100, height: 100}
+        on_tap: detail
+  detail:
+    back: home
+"#,
+        )
+        .unwrap();
+
+        let device = MockDevice::load(&dir.join("fixture.yaml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let mut backend = MockBackend::new(Arc::new(device.unwrap()));
+        assert_eq!(
+            backend.take_screenshot(DEVICE, &no_progress).unwrap(),
+            PLACEHOLDER_PNG
+        );
+        assert!(texts(&mut backend).contains(&"Open".to_string()));
+        backend.tap(DEVICE, 50.0, 50.0).unwrap();
+        assert!(texts(&mut backend).is_empty());
+    }
+}
@@ -2,7 +2,28 @@
 // Device Management Modules
 
 pub mod android;
+pub mod backend;
+pub mod cancel;
 pub mod ios;
+pub mod locks;
+pub mod mock;
+pub mod registry;
+pub mod selection;
 
 pub use android::AndroidDeviceManager;
+pub use backend::{
+    BackendRegistry, Button, DeviceBackend, InstalledApp, Orientation, ScreenElement, ScreenSize,
+};
 pub use ios::IOSDeviceManager;
+pub use locks::DeviceLocks;
+pub use registry::DeviceRegistry;
+pub use selection::DeviceSelection;
+
+/// Progress callback for multi-step device operations
+///
+/// Called with the completed percentage (0-100, increasing) and a short
+/// description of the step that is starting.
+pub type Progress<'a> = &'a dyn Fn(f64, &str);
+
+/// Progress callback that ignores all updates
+pub fn no_progress(_percent: f64, _step: &str) {}
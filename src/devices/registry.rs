@@ -0,0 +1,63 @@
+// mobile-mcp-zed-extension/src/devices/registry.rs
+// Device Registry
+//
+// Remembers which platform each listed device belongs to, so tool calls only
+// need a device_id. Every device listing updates it; an id that isn't known
+// yet triggers a fresh listing before giving up. Clones share the registry,
+// so a device listed by one request is known to all the others.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::types::DeviceInfo;
+
+/// Platform of every device seen so far, by device id
+#[derive(Clone, Default)]
+pub struct DeviceRegistry {
+    platforms: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl DeviceRegistry {
+    /// Record the platform of listed devices
+    pub fn update(&self, devices: &[DeviceInfo]) {
+        let mut platforms = self.platforms.lock().unwrap_or_else(|e| e.into_inner());
+        for device in devices {
+            platforms.insert(device.id.clone(), device.platform.clone());
+        }
+    }
+
+    /// Platform a device was last listed under
+    pub fn platform_of(&self, device_id: &str) -> Option<String> {
+        let platforms = self.platforms.lock().unwrap_or_else(|e| e.into_inner());
+        platforms.get(device_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, platform: &str) -> DeviceInfo {
+        DeviceInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            platform: platform.to_string(),
+            device_type: "emulator".to_string(),
+            state: "connected".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_clones_share_listed_devices() {
+        let registry = DeviceRegistry::default();
+        let other = registry.clone();
+        assert_eq!(registry.platform_of("emulator-5554"), None);
+
+        other.update(&[device("emulator-5554", "android"), device("ABC-123", "ios")]);
+        assert_eq!(
+            registry.platform_of("emulator-5554").as_deref(),
+            Some("android")
+        );
+        assert_eq!(registry.platform_of("ABC-123").as_deref(), Some("ios"));
+    }
+}
@@ -0,0 +1,111 @@
+// mobile-mcp-zed-extension/src/devices/selection.rs
+// Device Selection
+//
+// Tool calls don't always name their device. The target is, in order:
+//
+// 1. The device_id argument, which may be an alias from the settings
+//    (e.g., "pixel" for "emulator-5554")
+// 2. The device chosen for the session with select_device
+// 3. The only connected device (found by listing devices)
+//
+// An empty device_id counts as omitted. Each session has its own selection;
+// the aliases come from the settings and are the same everywhere.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Aliases and the session's selected device
+#[derive(Clone, Default)]
+pub struct DeviceSelection {
+    aliases: Arc<HashMap<String, String>>,
+    selected: Arc<Mutex<Option<String>>>,
+}
+
+impl DeviceSelection {
+    /// Selection of a new session, with nothing selected yet
+    ///
+    /// # Arguments
+    /// * `aliases` - Device id of each alias
+    pub fn new(aliases: HashMap<String, String>) -> Self {
+        Self {
+            aliases: Arc::new(aliases),
+            selected: Arc::default(),
+        }
+    }
+
+    /// Device id an alias stands for, or the argument itself
+    pub fn resolve_alias<'a>(&'a self, device_id: &'a str) -> &'a str {
+        self.aliases
+            .get(device_id)
+            .map(String::as_str)
+            .unwrap_or(device_id)
+    }
+
+    /// Names of the aliases, sorted
+    pub fn aliases(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.aliases.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Use a device for calls that don't name one
+    pub fn select(&self, device_id: &str) {
+        *self.lock() = Some(device_id.to_string());
+    }
+
+    /// Device a call is for without listing devices
+    ///
+    /// # Returns
+    /// The named (or selected) device, or None if the call has to go to the
+    /// only connected device
+    pub fn target(&self, requested: Option<&str>) -> Option<String> {
+        match requested.filter(|device_id| !device_id.is_empty()) {
+            Some(device_id) => Some(self.resolve_alias(device_id).to_string()),
+            None => self.lock().clone(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.selected.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection() -> DeviceSelection {
+        DeviceSelection::new(HashMap::from([(
+            "pixel".to_string(),
+            "emulator-5554".to_string(),
+        )]))
+    }
+
+    #[test]
+    fn test_aliases_resolve_to_device_ids() {
+        let selection = selection();
+        assert_eq!(selection.resolve_alias("pixel"), "emulator-5554");
+        assert_eq!(selection.resolve_alias("R58M123ABC"), "R58M123ABC");
+        assert_eq!(selection.aliases(), vec!["pixel".to_string()]);
+        assert_eq!(
+            selection.target(Some("pixel")).as_deref(),
+            Some("emulator-5554")
+        );
+    }
+
+    #[test]
+    fn test_selected_device_used_when_omitted() {
+        let selection = selection();
+        assert_eq!(selection.target(None), None);
+        assert_eq!(selection.target(Some("")), None);
+
+        // Every request of the session sees the selection
+        selection.clone().select("ABC-123");
+        assert_eq!(selection.target(None).as_deref(), Some("ABC-123"));
+        assert_eq!(selection.target(Some("")).as_deref(), Some("ABC-123"));
+        assert_eq!(
+            selection.target(Some("pixel")).as_deref(),
+            Some("emulator-5554")
+        );
+    }
+}
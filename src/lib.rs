@@ -1,5 +1,6 @@
 use schemars::JsonSchema;
 use serde::Deserialize;
+use std::collections::HashMap;
 use zed_extension_api::{
     self as zed, serde_json, settings::ContextServerSettings, Command as ZedCommand,
     ContextServerConfiguration, ContextServerId, Project, Result,
@@ -12,9 +13,25 @@ struct MobileMcpSettings {
     #[serde(default)]
     debug: bool,
 
-    /// Preferred platform (ios, android, or auto)
+    /// Preferred platform (ios, android, mock, or auto)
     #[serde(default = "default_platform")]
     platform: String,
+
+    /// Share one server process between all Zed windows (macOS and Linux)
+    #[serde(default)]
+    shared_daemon: bool,
+
+    /// Run destructive tools on emulators and simulators without asking
+    #[serde(default)]
+    auto_approve_virtual_devices: bool,
+
+    /// JSON or YAML fixture describing the simulated device of the "mock" platform
+    #[serde(default)]
+    mock_fixture: Option<String>,
+
+    /// Short names for device ids (e.g., "pixel": "emulator-5554")
+    #[serde(default)]
+    device_aliases: HashMap<String, String>,
 }
 
 fn default_platform() -> String {
@@ -26,6 +43,10 @@ impl Default for MobileMcpSettings {
         Self {
             debug: false,
             platform: default_platform(),
+            shared_daemon: false,
+            auto_approve_virtual_devices: false,
+            mock_fixture: None,
+            device_aliases: HashMap::new(),
         }
     }
 }
@@ -62,6 +83,28 @@ impl zed::Extension for MobileMcpExtension {
             env.push(("MOBILE_PLATFORM".to_string(), settings.platform.clone()));
         }
 
+        if settings.auto_approve_virtual_devices {
+            env.push((
+                "MOBILE_DEVICE_MCP_AUTO_APPROVE_VIRTUAL".to_string(),
+                "1".to_string(),
+            ));
+        }
+
+        if let Some(fixture) = &settings.mock_fixture {
+            env.push((
+                "MOBILE_DEVICE_MCP_MOCK_FIXTURE".to_string(),
+                fixture.clone(),
+            ));
+        }
+
+        if !settings.device_aliases.is_empty() {
+            env.push((
+                "MOBILE_DEVICE_MCP_DEVICE_ALIASES".to_string(),
+                serde_json::to_string(&settings.device_aliases)
+                    .map_err(|e| format!("Invalid device aliases: {}", e))?,
+            ));
+        }
+
         // Use absolute path to the binary in ~/.cargo/bin
         // Zed runs MCP servers from the work directory, so we need the full path
         let home = std::env::var("HOME")
@@ -76,11 +119,18 @@ impl zed::Extension for MobileMcpExtension {
             });
         let binary_path = format!("{}/.cargo/bin/mobile-device-mcp-server", home);
 
-        // Simply launch the native binary
+        // With a shared daemon, this process only proxies to it
+        let args = if settings.shared_daemon {
+            vec!["--proxy".to_string()]
+        } else {
+            vec![]
+        };
+
+        // Launch the native binary
         // The binary must be installed via `cargo install mobile-device-mcp-server`
         Ok(ZedCommand {
             command: binary_path,
-            args: vec![],
+            args,
             env,
         })
     }
@@ -4,68 +4,166 @@
 #![allow(unused_imports)]
 
 use std::io::{self, BufRead, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::task::JoinSet;
+
+mod completion;
 mod devices;
 mod mcp;
+mod prompts;
+mod recording;
+mod resources;
 mod tools;
+mod transport;
 mod types;
 
 use crate::types::DeviceInfo;
-use devices::{AndroidDeviceManager, IOSDeviceManager};
-use mcp::{InitializeResult, McpErrorResponse, McpRequest, McpResponse, ToolCallParams};
-use tools::{get_all_tools, handlers};
+use devices::{
+    BackendRegistry, Button, DeviceBackend, DeviceLocks, DeviceRegistry, DeviceSelection,
+    InstalledApp, Orientation, Progress, ScreenElement,
+};
+use mcp::logging::{self, LogLevel};
+use mcp::roots::{PathAccess, Roots, RootsCache};
+use mcp::{
+    CancelledParams, ClientRequests, CompleteParams, Elicitation, GetPromptParams,
+    InitializeParams, InitializeResult, McpError, McpErrorResponse, McpRequest, McpResponse,
+    MessageWriter, ProgressReporter, ProtocolVersion, RequestTracker, ResourceUriParams,
+    SessionLogger, SessionState, SetLevelParams, ToolCallParams,
+};
+use resources::watcher::{ResourceWatcher, Subscriptions, WatchHandle};
+use resources::{DeviceResource, ResourceError};
+use tools::args::{
+    AppArgs, DeviceArgs, InstallAppArgs, LaunchAppArgs, ListDevicesArgs, ListElementsArgs,
+    LongPressArgs, OpenUrlArgs, PressButtonArgs, SaveScreenshotArgs, SelectDeviceArgs,
+    SetOrientationArgs, SwipeArgs, TapArgs, TypeKeysArgs,
+};
+use tools::{find_tool, get_all_tools, handlers, ToolDefinition, ToolError};
 use types::MobileDeviceMcpSettings;
 
 // ============================================================================
 // MobileDeviceManager - Consolidated device management
 // ============================================================================
 
+#[derive(Clone, Default)]
 pub struct MobileDeviceManager {
-    android_manager: AndroidDeviceManager,
-    ios_manager: IOSDeviceManager,
+    backends: BackendRegistry,
+    devices: DeviceRegistry,
 }
 impl MobileDeviceManager {
-    pub fn new(debug: bool) -> Self {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Manager dispatching to the given backends only
+    #[allow(dead_code)]
+    pub fn with_backends(backends: BackendRegistry) -> Self {
         Self {
-            android_manager: AndroidDeviceManager::new(debug),
-            ios_manager: IOSDeviceManager::new(debug),
+            backends,
+            devices: DeviceRegistry::default(),
+        }
+    }
+
+    /// Backend of a device, or an error naming the unknown platform
+    ///
+    /// For "auto" the platform the device is listed under is used.
+    fn backend(
+        &mut self,
+        device_id: &str,
+        platform: &str,
+    ) -> Result<&mut dyn DeviceBackend, String> {
+        let platform = match platform {
+            "auto" => self.resolve_platform(device_id)?,
+            platform => platform.to_string(),
+        };
+        self.backends
+            .get(&platform)
+            .ok_or_else(|| format!("Unknown platform: {}", platform))
+    }
+
+    /// Platforms there are backends for
+    pub fn platforms(&self) -> Vec<&'static str> {
+        self.backends.platforms()
+    }
+
+    /// Buttons of a platform's devices (none for an unknown platform)
+    pub fn buttons(&mut self, platform: &str) -> &'static [Button] {
+        self.backends
+            .get(platform)
+            .map(|backend| backend.buttons())
+            .unwrap_or(&[])
+    }
+
+    /// Platform of a device, listing the devices again if it isn't known yet
+    pub fn resolve_platform(&mut self, device_id: &str) -> Result<String, String> {
+        if let Some(platform) = self.devices.platform_of(device_id) {
+            return Ok(platform);
+        }
+        self.find_device(device_id).map(|device| device.platform)
+    }
+
+    /// A connected device, or an error naming the connected ones
+    pub fn find_device(&mut self, device_id: &str) -> Result<DeviceInfo, String> {
+        let devices = self.list_all_devices("auto");
+        match devices.iter().find(|device| device.id == device_id) {
+            Some(device) => Ok(device.clone()),
+            None if devices.is_empty() => Err(format!(
+                "Device not found: {}. No devices are connected",
+                device_id
+            )),
+            None => Err(format!(
+                "Device not found: {}. Available devices: {}",
+                device_id,
+                device_ids(&devices)
+            )),
+        }
+    }
+
+    /// The device of calls that don't name one: the only one connected
+    pub fn only_device(&mut self, platform: &str) -> Result<String, String> {
+        match self.list_all_devices(platform).as_slice() {
+            [device] => Ok(device.id.clone()),
+            [] => Err(
+                "No devices are connected. Start an emulator or simulator, or connect a device"
+                    .to_string(),
+            ),
+            devices => Err(format!(
+                "{} devices are connected ({}). Pass device_id, or choose one with mobile_device_mcp_select_device",
+                devices.len(),
+                device_ids(devices)
+            )),
         }
     }
 
+    /// Devices of one platform, or of every platform for "auto"
     pub fn list_all_devices(&mut self, platform: &str) -> Vec<DeviceInfo> {
         let mut all_devices = Vec::new();
 
-        match platform {
-            "android" => {
-                if let Ok(devices) = self.android_manager.list_devices() {
-                    all_devices.extend(devices);
-                }
-            }
-            "ios" => {
-                if let Ok(devices) = self.ios_manager.list_devices() {
-                    all_devices.extend(devices);
-                }
+        for backend in self.backends.iter_mut() {
+            if platform != "auto" && backend.platform() != platform {
+                continue;
             }
-            _ => {
-                if let Ok(devices) = self.android_manager.list_devices() {
-                    all_devices.extend(devices);
-                }
-                if let Ok(devices) = self.ios_manager.list_devices() {
-                    all_devices.extend(devices);
-                }
+            if let Ok(devices) = backend.list_devices() {
+                all_devices.extend(devices);
             }
         }
 
+        self.devices.update(&all_devices);
         all_devices
     }
 
-    // Delegate to handlers - these are just thin wrappers
-    pub fn take_screenshot(&mut self, device_id: &str, platform: &str) -> Result<Vec<u8>, String> {
-        match platform {
-            "android" => self.android_manager.take_screenshot(device_id),
-            "ios" => self.ios_manager.take_screenshot(device_id),
-            _ => Err(format!("Unknown platform: {}", platform)),
-        }
+    pub fn take_screenshot(
+        &mut self,
+        device_id: &str,
+        platform: &str,
+        progress: Progress,
+    ) -> Result<Vec<u8>, String> {
+        self.backend(device_id, platform)?
+            .take_screenshot(device_id, progress)
     }
 
     pub fn tap_screen(
@@ -75,11 +173,7 @@ impl MobileDeviceManager {
         x: f64,
         y: f64,
     ) -> Result<String, String> {
-        match platform {
-            "android" => self.android_manager.tap_screen(device_id, x, y),
-            "ios" => self.ios_manager.tap_screen(device_id, x, y),
-            _ => Err(format!("Unknown platform: {}", platform)),
-        }
+        self.backend(device_id, platform)?.tap(device_id, x, y)
     }
 
     pub fn type_text(
@@ -88,47 +182,24 @@ impl MobileDeviceManager {
         platform: &str,
         text: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => self.android_manager.type_text(device_id, text),
-            "ios" => self.ios_manager.type_text(device_id, text),
-            _ => Err(format!("Unknown platform: {}", platform)),
-        }
+        self.backend(device_id, platform)?
+            .type_text(device_id, text)
     }
 
-    // Additional methods using AndroidRobot and IOSDeviceManager
     pub fn get_screen_size(
         &mut self,
         device_id: &str,
         platform: &str,
     ) -> Result<(u32, u32), String> {
-        match platform {
-            "android" => {
-                use devices::android::{AndroidRobot, ScreenSize};
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.get_screen_size().map(|s| (s.width, s.height))
-            }
-            "ios" => self
-                .ios_manager
-                .get_screen_size(device_id)
-                .map(|s| (s.width, s.height)),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .screen_size(device_id)
+            .map(|s| (s.width, s.height))
     }
 
     pub fn get_orientation(&mut self, device_id: &str, platform: &str) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot
-                    .get_orientation()
-                    .map(|o| format!("{:?}", o).to_lowercase())
-            }
-            "ios" => self
-                .ios_manager
-                .get_orientation(device_id)
-                .map(|o| format!("{:?}", o).to_lowercase()),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .orientation(device_id)
+            .map(|o| o.name().to_string())
     }
 
     pub fn set_orientation(
@@ -137,29 +208,10 @@ impl MobileDeviceManager {
         platform: &str,
         orientation: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                use devices::android::Orientation;
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                let orient = match orientation {
-                    "portrait" => Orientation::Portrait,
-                    "landscape" => Orientation::Landscape,
-                    _ => return Err(format!("Invalid orientation: {}", orientation)),
-                };
-                robot.set_orientation(orient)?;
-                Ok(format!("Set orientation to {}", orientation))
-            }
-            "ios" => {
-                use devices::android::Orientation;
-                let orient = match orientation {
-                    "portrait" => Orientation::Portrait,
-                    "landscape" => Orientation::Landscape,
-                    _ => return Err(format!("Invalid orientation: {}", orientation)),
-                };
-                self.ios_manager.set_orientation(device_id, orient)
-            }
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        let orientation = Orientation::from_name(orientation)
+            .ok_or_else(|| format!("Invalid orientation: {}", orientation))?;
+        self.backend(device_id, platform)?
+            .set_orientation(device_id, orientation)
     }
 
     pub fn double_tap_screen(
@@ -168,17 +220,10 @@ impl MobileDeviceManager {
         platform: &str,
         x: f64,
         y: f64,
+        progress: Progress,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                self.tap_screen(device_id, platform, x, y)?;
-                std::thread::sleep(std::time::Duration::from_millis(50));
-                self.tap_screen(device_id, platform, x, y)?;
-                Ok("Double tap executed".to_string())
-            }
-            "ios" => self.ios_manager.double_tap_screen(device_id, x, y),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .double_tap(device_id, x, y, progress)
     }
 
     pub fn long_press_screen(
@@ -189,17 +234,8 @@ impl MobileDeviceManager {
         y: f64,
         duration: u32,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.long_press(x as u32, y as u32)?;
-                Ok(format!("Long pressed at ({}, {})", x, y))
-            }
-            "ios" => self
-                .ios_manager
-                .long_press_screen(device_id, x, y, duration as u64),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .long_press(device_id, x, y, duration)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -213,23 +249,8 @@ impl MobileDeviceManager {
         end_y: f64,
         duration: u32,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.swipe_coordinates(
-                    start_x as u32,
-                    start_y as u32,
-                    end_x as u32,
-                    end_y as u32,
-                    duration,
-                )?;
-                Ok("Swipe executed".to_string())
-            }
-            "ios" => self
-                .ios_manager
-                .swipe_screen(device_id, start_x, start_y, end_x, end_y),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .swipe(device_id, start_x, start_y, end_x, end_y, duration)
     }
 
     pub fn press_button(
@@ -238,54 +259,18 @@ impl MobileDeviceManager {
         platform: &str,
         button: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                use devices::android::Button;
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                let btn = match button.to_lowercase().as_str() {
-                    "home" => Button::Home,
-                    "back" => Button::Back,
-                    "menu" => Button::Menu,
-                    "power" => Button::Power,
-                    "volume_up" => Button::VolumeUp,
-                    "volume_down" => Button::VolumeDown,
-                    "camera" => Button::Camera,
-                    "enter" => Button::Enter,
-                    "search" => Button::Back,     // Fallback
-                    "app_switch" => Button::Menu, // Fallback
-                    _ => return Err(format!("Unknown button: {}", button)),
-                };
-                robot.press_button(btn)?;
-                Ok(format!("Pressed button: {}", button))
-            }
-            "ios" => {
-                use devices::android::Button;
-                let btn = match button.to_lowercase().as_str() {
-                    "home" => Button::Home,
-                    "power" => Button::Power,
-                    "volume_up" => Button::VolumeUp,
-                    "volume_down" => Button::VolumeDown,
-                    _ => return Err(format!("Button '{}' not supported on iOS. Available: home, power, volume_up, volume_down", button)),
-                };
-                self.ios_manager.press_button(device_id, btn)
-            }
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        let button =
+            Button::from_name(button).ok_or_else(|| format!("Unknown button: {}", button))?;
+        self.backend(device_id, platform)?
+            .press_button(device_id, button)
     }
 
     pub fn list_apps(
         &mut self,
         device_id: &str,
         platform: &str,
-    ) -> Result<Vec<devices::android::InstalledApp>, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.list_installed_apps()
-            }
-            "ios" => self.ios_manager.list_apps(device_id),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+    ) -> Result<Vec<InstalledApp>, String> {
+        self.backend(device_id, platform)?.list_apps(device_id)
     }
 
     pub fn list_elements_on_screen(
@@ -293,15 +278,20 @@ impl MobileDeviceManager {
         device_id: &str,
         platform: &str,
         filter: Option<&str>,
-    ) -> Result<Vec<devices::android::ScreenElement>, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.list_screen_elements(filter)
-            }
-            "ios" => self.ios_manager.list_elements_on_screen(device_id, filter),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        progress: Progress,
+    ) -> Result<Vec<ScreenElement>, String> {
+        self.backend(device_id, platform)?
+            .list_elements(device_id, filter, progress)
+    }
+
+    /// Identify what is in the foreground (used to detect screen changes)
+    pub fn get_foreground_activity(
+        &mut self,
+        device_id: &str,
+        platform: &str,
+    ) -> Result<String, String> {
+        self.backend(device_id, platform)?
+            .foreground_activity(device_id)
     }
 
     pub fn launch_app(
@@ -310,15 +300,8 @@ impl MobileDeviceManager {
         platform: &str,
         app_id: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.launch_app(app_id)?;
-                Ok(format!("Launched app: {}", app_id))
-            }
-            "ios" => self.ios_manager.launch_app(device_id, app_id),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .launch_app(device_id, app_id)
     }
 
     pub fn terminate_app(
@@ -327,15 +310,8 @@ impl MobileDeviceManager {
         platform: &str,
         app_id: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.terminate_app(app_id)?;
-                Ok(format!("Terminated app: {}", app_id))
-            }
-            "ios" => self.ios_manager.terminate_app(device_id, app_id),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .terminate_app(device_id, app_id)
     }
 
     pub fn install_app(
@@ -343,16 +319,10 @@ impl MobileDeviceManager {
         device_id: &str,
         platform: &str,
         app_path: &str,
+        progress: Progress,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.install_app(app_path)?;
-                Ok(format!("Installed app from: {}", app_path))
-            }
-            "ios" => self.ios_manager.install_app(device_id, app_path),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .install_app(device_id, app_path, progress)
     }
 
     pub fn uninstall_app(
@@ -361,15 +331,8 @@ impl MobileDeviceManager {
         platform: &str,
         app_id: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.uninstall_app(app_id)?;
-                Ok(format!("Uninstalled app: {}", app_id))
-            }
-            "ios" => self.ios_manager.uninstall_app(device_id, app_id),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?
+            .uninstall_app(device_id, app_id)
     }
 
     pub fn open_url(
@@ -378,304 +341,1271 @@ impl MobileDeviceManager {
         platform: &str,
         url: &str,
     ) -> Result<String, String> {
-        match platform {
-            "android" => {
-                let mut robot = self.android_manager.create_robot(device_id.to_string());
-                robot.open_url(url)?;
-                Ok(format!("Opened URL: {}", url))
-            }
-            "ios" => self.ios_manager.open_url(device_id, url),
-            _ => Err("Not implemented for this platform".to_string()),
-        }
+        self.backend(device_id, platform)?.open_url(device_id, url)
     }
 }
 
+/// Comma-separated ids of devices, for error messages
+fn device_ids(devices: &[DeviceInfo]) -> String {
+    devices
+        .iter()
+        .map(|device| device.id.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // ============================================================================
 // MCP Server Implementation
 // ============================================================================
 
+/// How long in-flight requests get to finish when the session ends
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// How long a cancelled request may keep its device locked
+const CANCEL_GRACE: Duration = Duration::from_secs(2);
+
+/// Device state shared by every session of the process
+///
+/// Sessions of the HTTP server and the daemon clone it, so they see the same
+/// backends, device registry and Android sessions (with their cached
+/// capabilities), and never drive one device at the same time. One watcher
+/// polls the devices for all of them.
+#[derive(Clone)]
+pub struct SharedDevices {
+    manager: MobileDeviceManager,
+    locks: DeviceLocks,
+    watcher: ResourceWatcher,
+}
+
+impl Default for SharedDevices {
+    fn default() -> Self {
+        Self::new(MobileDeviceManager::new())
+    }
+}
+
+impl SharedDevices {
+    /// Shared state driving devices through the given manager
+    pub fn new(manager: MobileDeviceManager) -> Self {
+        Self {
+            watcher: ResourceWatcher::new(manager.clone()),
+            manager,
+            locks: DeviceLocks::default(),
+        }
+    }
+}
+
+/// Device a request works on; requests for the same device run one at a time
+enum RequestDevice {
+    /// The request isn't about one device
+    None,
+    /// A device id (aliases already resolved)
+    Id(String),
+    /// The only connected device of a platform ("auto" for any), found by
+    /// listing devices before locking it
+    OnlyConnected(String),
+}
+
 struct McpServer {
+    /// Cloned into every request task
     manager: MobileDeviceManager,
     settings: MobileDeviceMcpSettings,
+    state: SessionState,
+    /// Protocol revision agreed on during initialize
+    protocol_version: ProtocolVersion,
+    /// Shared by responses and background notifications
+    writer: MessageWriter,
+    /// Sends notifications/message at the level the client chose
+    logger: SessionLogger,
+    /// Resource URIs the client subscribed to
+    subscriptions: Subscriptions,
+    /// Process-wide device watcher
+    watcher: ResourceWatcher,
+    /// Registration with the watcher, made once the session is ready
+    watching: Option<WatchHandle>,
+    /// Device requests still running, for notifications/cancelled
+    requests: RequestTracker,
+    /// Requests sent to the client (elicitation) waiting for an answer
+    client_requests: ClientRequests,
+    /// The client declared the elicitation capability
+    client_elicitation: bool,
+    /// The client declared the roots capability
+    client_roots: bool,
+    /// Workspace roots from roots/list, fetched on first use
+    roots: RootsCache,
+    /// Serializes requests for the same device
+    device_locks: DeviceLocks,
+    /// Device aliases and the device chosen with select_device
+    selection: DeviceSelection,
+    /// Tasks running device requests
+    tasks: JoinSet<()>,
 }
 
 impl McpServer {
-    fn new(settings: MobileDeviceMcpSettings) -> Self {
+    /// Create a session
+    ///
+    /// # Arguments
+    /// * `settings` - Server settings
+    /// * `writer` - Where responses and notifications are written
+    /// * `devices` - Device state shared with every other session of this process
+    fn new(
+        settings: MobileDeviceMcpSettings,
+        writer: MessageWriter,
+        devices: &SharedDevices,
+    ) -> Self {
         Self {
-            manager: MobileDeviceManager::new(settings.debug),
+            manager: devices.manager.clone(),
+            logger: SessionLogger::new(writer.clone(), settings.debug),
+            selection: DeviceSelection::new(settings.device_aliases.clone()),
             settings,
+            state: SessionState::default(),
+            protocol_version: ProtocolVersion::LATEST,
+            writer,
+            subscriptions: Subscriptions::default(),
+            watcher: devices.watcher.clone(),
+            watching: None,
+            requests: RequestTracker::default(),
+            client_requests: ClientRequests::default(),
+            client_elicitation: false,
+            client_roots: false,
+            roots: RootsCache::default(),
+            device_locks: devices.locks.clone(),
+            tasks: JoinSet::new(),
         }
     }
 
+    /// Whether initialize succeeded
+    fn is_initialized(&self) -> bool {
+        self.state != SessionState::AwaitingInitialize
+    }
+
     fn send_response(&self, id: serde_json::Value, result: serde_json::Value) {
-        let response = McpResponse::success(id, result);
-        if let Ok(json) = response.to_json() {
-            println!("{}", json);
-        }
+        let _ = self.writer.write_message(&McpResponse::success(id, result));
+    }
+
+    fn send_error(&self, id: serde_json::Value, error: McpError) {
+        let _ = self
+            .writer
+            .write_message(&McpErrorResponse::from_error(id, error));
     }
 
-    fn send_error(&self, id: serde_json::Value, message: &str) {
-        let error = McpErrorResponse::error(id, -1, message.to_string());
-        if let Ok(json) = error.to_json() {
-            println!("{}", json);
+    /// Negotiate the protocol version and answer "initialize"
+    ///
+    /// Returns true if the handshake response was sent successfully.
+    fn handle_initialize(
+        &mut self,
+        id: serde_json::Value,
+        params: Option<serde_json::Value>,
+    ) -> bool {
+        let params = match params {
+            Some(params) => match serde_json::from_value::<InitializeParams>(params) {
+                Ok(params) => params,
+                Err(e) => {
+                    self.send_error(
+                        id,
+                        McpError::invalid_params(format!("Invalid initialize params: {}", e)),
+                    );
+                    return false;
+                }
+            },
+            None => InitializeParams::default(),
+        };
+
+        // Clients predating version negotiation only speak the first revision
+        self.protocol_version = params
+            .protocol_version
+            .as_deref()
+            .map(ProtocolVersion::negotiate)
+            .unwrap_or(ProtocolVersion::V2024_11_05);
+        self.client_elicitation = params.capabilities.elicitation.is_some();
+        self.client_roots = params.capabilities.roots.is_some();
+
+        let client = params
+            .client_info
+            .as_ref()
+            .map(|info| format!("{} {}", info.name, info.version))
+            .unwrap_or_else(|| "unknown client".to_string());
+        self.logger.log(
+            LogLevel::Info,
+            logging::PROTOCOL,
+            &format!(
+                "Initialize from {} (requested {:?}, using {})",
+                client,
+                params.protocol_version,
+                self.protocol_version.as_str()
+            ),
+        );
+
+        match serde_json::to_value(InitializeResult::new(self.protocol_version)) {
+            Ok(result) => {
+                self.send_response(id, result);
+                true
+            }
+            Err(e) => {
+                self.send_error(id, McpError::internal_error(e.to_string()));
+                false
+            }
         }
     }
 
-    fn handle_initialize(&self, id: serde_json::Value) {
-        let result = InitializeResult::new();
-        self.send_response(id, serde_json::to_value(result).unwrap());
+    /// Change the minimum level of notifications/message; unknown levels are -32602
+    fn handle_set_level(&self, id: serde_json::Value, params: Option<serde_json::Value>) {
+        let params = params.map(serde_json::from_value::<SetLevelParams>);
+        match params {
+            Some(Ok(params)) => {
+                self.logger.set_level(params.level);
+                self.send_response(id, serde_json::json!({}));
+            }
+            Some(Err(e)) => self.send_error(
+                id,
+                McpError::invalid_params(format!("Invalid params: {}", e)),
+            ),
+            None => self.send_error(
+                id,
+                McpError::invalid_params("Missing params for logging/setLevel"),
+            ),
+        }
     }
 
+    /// List the tools
+    ///
+    /// Fields newer than the negotiated revision are left out: annotations
+    /// before 2025-03-26, output schemas before 2025-06-18.
     fn handle_tools_list(&self, id: serde_json::Value) {
-        let tools: Vec<_> = get_all_tools().iter().map(|t| t.to_json()).collect();
+        let version = self.protocol_version;
+        let tools: Vec<_> = get_all_tools()
+            .iter()
+            .map(|tool| {
+                let mut json = tool.to_json();
+                if let Some(json) = json.as_object_mut() {
+                    if !version.supports_tool_annotations() {
+                        json.remove("annotations");
+                    }
+                    if !version.supports_structured_output() {
+                        json.remove("outputSchema");
+                    }
+                }
+                json
+            })
+            .collect();
         self.send_response(id, serde_json::json!({ "tools": tools }));
     }
 
-    fn handle_tool_call(&mut self, id: serde_json::Value, params: ToolCallParams) {
-        let result = self.dispatch_tool(&params.name, params.arguments);
+    fn handle_resources_list(&mut self, id: serde_json::Value) {
+        self.spawn_request(id, RequestDevice::None, |manager, _| {
+            Ok(resources::handle_list_resources(manager))
+        });
+    }
 
-        match result {
-            Ok(response) => self.send_response(id, response),
-            Err(e) => self.send_error(id, &e),
-        }
+    fn handle_resource_templates_list(&self, id: serde_json::Value) {
+        self.send_response(id, resources::handle_list_resource_templates());
+    }
+
+    /// Read a device resource
+    ///
+    /// Unknown URIs (or devices that are no longer connected) are reported as
+    /// -32002; failures while reading from the device become -32603.
+    fn handle_resource_read(&mut self, id: serde_json::Value, params: ResourceUriParams) {
+        let device = match DeviceResource::parse(&params.uri)
+            .and_then(|resource| resource.device_id().map(str::to_string))
+        {
+            Some(device_id) => RequestDevice::Id(device_id),
+            None => RequestDevice::None,
+        };
+
+        self.spawn_request(id, device, move |manager, _| {
+            resources::handle_read_resource(manager, &params.uri).map_err(|e| match e {
+                ResourceError::NotFound(uri) => McpError::resource_not_found(&uri),
+                ResourceError::Read(message) => McpError::internal_error(message),
+            })
+        });
     }
 
-    fn dispatch_tool(
+    /// Add or remove a resource subscription
+    ///
+    /// Subscribing to a device that isn't connected yet is allowed: its
+    /// resources are updated once it shows up.
+    fn handle_resource_subscription(
         &mut self,
-        tool_name: &str,
-        args: serde_json::Value,
-    ) -> Result<serde_json::Value, String> {
-        // Extract common parameters
-        let device_id = args.get("device_id").and_then(|v| v.as_str()).unwrap_or("");
-        let platform = args
-            .get("platform")
-            .and_then(|v| v.as_str())
-            .unwrap_or(&self.settings.platform);
-
-        match tool_name {
-            // Device Info
-            "mobile_device_mcp_list_available_devices" => {
-                handlers::handle_list_devices(&mut self.manager, platform)
-            }
-            "mobile_device_mcp_get_screen_size" => {
-                handlers::handle_get_screen_size(&mut self.manager, device_id, platform)
-            }
-            "mobile_device_mcp_get_orientation" => {
-                handlers::handle_get_orientation(&mut self.manager, device_id, platform)
-            }
-            "mobile_device_mcp_list_apps" => {
-                handlers::handle_list_apps(&mut self.manager, device_id, platform)
-            }
-            "mobile_device_mcp_list_elements_on_screen" => {
-                let filter = args.get("filter").and_then(|v| v.as_str());
-                handlers::handle_list_elements(&mut self.manager, device_id, platform, filter)
-            }
+        id: serde_json::Value,
+        params: ResourceUriParams,
+        subscribe: bool,
+    ) {
+        if DeviceResource::parse(&params.uri).is_none() {
+            self.send_error(id, McpError::resource_not_found(&params.uri));
+            return;
+        }
 
-            // Screen Interaction
-            "mobile_device_mcp_take_screenshot" => {
-                handlers::handle_take_screenshot(&mut self.manager, device_id, platform)
-            }
-            "mobile_device_mcp_save_screenshot" => {
-                let output = args
-                    .get("output_path")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing output_path")?;
-                handlers::handle_save_screenshot(&mut self.manager, device_id, platform, output)
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            if subscribe {
+                subscriptions.insert(params.uri);
+            } else {
+                subscriptions.remove(&params.uri);
             }
-            "mobile_device_mcp_click_on_screen_at_coordinates" => {
-                let x = args
-                    .get("x")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing x coordinate")?;
-                let y = args
-                    .get("y")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing y coordinate")?;
-                handlers::handle_click_screen(&mut self.manager, device_id, platform, x, y)
+        }
+        self.send_response(id, serde_json::json!({}));
+    }
+
+    fn handle_prompts_list(&self, id: serde_json::Value) {
+        self.send_response(id, prompts::handle_list_prompts());
+    }
+
+    /// Render a prompt; unknown prompts and missing arguments are -32602
+    fn handle_prompt_get(&mut self, id: serde_json::Value, params: GetPromptParams) {
+        self.spawn_request(id, RequestDevice::None, move |manager, _| {
+            prompts::handle_get_prompt(manager, &params.name, &params.arguments)
+                .map_err(McpError::invalid_params)
+        });
+    }
+
+    /// Suggest values for an argument; unknown prompts, tools and templates are -32602
+    fn handle_complete(&mut self, id: serde_json::Value, mut params: CompleteParams) {
+        // Complete for the device the tool call would use
+        let arguments = &mut params.context.arguments;
+        let device = match self
+            .selection
+            .target(arguments.get("device_id").map(String::as_str))
+        {
+            Some(device_id) => {
+                arguments.insert("device_id".to_string(), device_id.clone());
+                RequestDevice::Id(device_id)
             }
-            "mobile_device_mcp_double_tap_on_screen" => {
-                let x = args
-                    .get("x")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing x coordinate")?;
-                let y = args
-                    .get("y")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing y coordinate")?;
-                handlers::handle_double_tap(&mut self.manager, device_id, platform, x, y)
+            None => RequestDevice::None,
+        };
+        let selection = self.selection.clone();
+        let default_platform = self.settings.platform.clone();
+        self.spawn_request(id, device, move |manager, _| {
+            completion::handle_complete(manager, &params, &selection, &default_platform)
+                .map_err(McpError::invalid_params)
+        });
+    }
+
+    fn handle_tool_call(&mut self, id: serde_json::Value, params: ToolCallParams) {
+        let progress = ProgressReporter::new(
+            self.writer.clone(),
+            params.meta.and_then(|meta| meta.progress_token),
+            self.protocol_version,
+        );
+        let device = tool_call_device(
+            &self.selection,
+            &self.settings,
+            &params.name,
+            &params.arguments,
+        );
+        // Requests to the client are made on behalf of this call
+        let client_requests = self.client_requests.for_request(&id);
+        let elicitation = Elicitation::new(
+            self.writer.clone(),
+            client_requests.clone(),
+            self.client_elicitation,
+            self.protocol_version,
+        );
+        let roots = Roots::new(
+            self.writer.clone(),
+            client_requests,
+            self.roots.clone(),
+            self.client_roots,
+        );
+        let settings = self.settings.clone();
+        let selection = self.selection.clone();
+        let structured = self.protocol_version.supports_structured_output();
+
+        self.spawn_request(id, device, move |manager, device_id| {
+            // The tool runs on the device that was locked for it
+            let mut arguments = params.arguments;
+            if let Some(device_id) = device_id {
+                // Clients may leave out the arguments of a call without any
+                if arguments.is_null() {
+                    arguments = serde_json::json!({});
+                }
+                if let Some(arguments) = arguments.as_object_mut() {
+                    arguments.insert("device_id".to_string(), device_id.into());
+                }
             }
-            "mobile_device_mcp_long_press_on_screen_at_coordinates" => {
-                let x = args
-                    .get("x")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing x coordinate")?;
-                let y = args
-                    .get("y")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing y coordinate")?;
-                let duration = args
-                    .get("duration")
-                    .and_then(|v| v.as_u64())
-                    .map(|d| d as u32);
-                handlers::handle_long_press(&mut self.manager, device_id, platform, x, y, duration)
+            let mut result = dispatch_tool(
+                manager,
+                &settings,
+                &selection,
+                &params.name,
+                arguments,
+                &progress,
+                &elicitation,
+                &roots,
+            )?;
+            // Older clients only understand the content array
+            if !structured {
+                if let Some(result) = result.as_object_mut() {
+                    result.remove("structuredContent");
+                }
             }
-            "mobile_device_mcp_swipe_on_screen" => {
-                let start_x = args
-                    .get("start_x")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing start_x")?;
-                let start_y = args
-                    .get("start_y")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing start_y")?;
-                let end_x = args
-                    .get("end_x")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing end_x")?;
-                let end_y = args
-                    .get("end_y")
-                    .and_then(|v| v.as_f64())
-                    .ok_or("Missing end_y")?;
-                let duration = args
-                    .get("duration")
-                    .and_then(|v| v.as_u64())
-                    .map(|d| d as u32);
-                handlers::handle_swipe(
-                    &mut self.manager,
-                    device_id,
-                    platform,
-                    start_x,
-                    start_y,
-                    end_x,
-                    end_y,
-                    duration,
-                )
+            Ok(result)
+        });
+    }
+
+    /// Run a request that talks to devices without blocking the session
+    ///
+    /// The work runs on tokio's blocking pool with its own clone of the
+    /// device manager and the session logger as its thread's logger.
+    /// Requests for the same device wait for each other, everything else
+    /// runs concurrently, so responses may arrive out of order. A cancelled
+    /// request gets no response.
+    ///
+    /// # Arguments
+    /// * `id` - Request id (also used by notifications/cancelled)
+    /// * `device` - Device the request operates on
+    /// * `work` - Produces the result or error response, given the device
+    ///   that was locked (None if there is none, or no single device is
+    ///   connected)
+    fn spawn_request<F>(&mut self, id: serde_json::Value, device: RequestDevice, work: F)
+    where
+        F: FnOnce(&mut MobileDeviceManager, Option<&str>) -> Result<serde_json::Value, McpError>
+            + Send
+            + 'static,
+    {
+        let token = self.requests.start(&id);
+        let requests = self.requests.clone();
+        let device_locks = self.device_locks.clone();
+        let writer = self.writer.clone();
+        let logger = self.logger.clone();
+        let mut manager = self.manager.clone();
+
+        self.tasks.spawn(async move {
+            let device_id = match device {
+                RequestDevice::None => None,
+                RequestDevice::Id(device_id) => Some(device_id).filter(|id| !id.is_empty()),
+                // Listing devices blocks, so it runs on the blocking pool too
+                RequestDevice::OnlyConnected(platform) => {
+                    let mut manager = manager.clone();
+                    tokio::task::spawn_blocking(move || manager.only_device(&platform).ok())
+                        .await
+                        .ok()
+                        .flatten()
+                }
+            };
+            let _device = match &device_id {
+                Some(device_id) => tokio::select! {
+                    guard = device_locks.acquire(device_id) => Some(guard),
+                    _ = token.cancelled() => None,
+                },
+                None => None,
+            };
+
+            let result = if token.is_cancelled() {
+                None
+            } else {
+                let task_token = token.clone();
+                let mut task = tokio::task::spawn_blocking(move || {
+                    logger.run(|| task_token.run(|| work(&mut manager, device_id.as_deref())))
+                });
+                tokio::select! {
+                    result = &mut task => Some(result),
+                    _ = token.cancelled() => {
+                        // The abort hooks should end the device operation
+                        // quickly; don't hold the device lock forever if not
+                        let _ = tokio::time::timeout(CANCEL_GRACE, &mut task).await;
+                        None
+                    }
+                }
+            };
+            requests.finish(&id);
+
+            // The client stopped waiting for a cancelled request
+            if token.is_cancelled() {
+                return;
             }
+            let _ = match result {
+                Some(Ok(Ok(result))) => writer.write_message(&McpResponse::success(id, result)),
+                Some(Ok(Err(error))) => {
+                    writer.write_message(&McpErrorResponse::from_error(id, error))
+                }
+                _ => writer.write_message(&McpErrorResponse::from_error(
+                    id,
+                    McpError::internal_error("Request failed unexpectedly"),
+                )),
+            };
+        });
+    }
+
+    /// Wait (up to SHUTDOWN_GRACE) for running requests to respond
+    async fn wait_for_requests(&mut self) {
+        let tasks = &mut self.tasks;
+        let all_done = async { while tasks.join_next().await.is_some() {} };
+        if tokio::time::timeout(SHUTDOWN_GRACE, all_done)
+            .await
+            .is_err()
+        {
+            self.requests.cancel_all();
+        }
+    }
+
+    /// Serve newline-delimited JSON until the input closes or the client
+    /// shuts down (stdin for the stdio transport, a socket in daemon mode)
+    async fn run(&mut self, input: impl AsyncBufRead + Unpin) {
+        let mut lines = input.lines();
+
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(l)) => l,
+                Ok(None) => break,
+                // Non-UTF-8 input can't be JSON; report it instead of leaving the client waiting
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    self.send_error(
+                        serde_json::Value::Null,
+                        McpError::parse_error(format!("Parse error: {}", e)),
+                    );
+                    continue;
+                }
+                Err(_) => break,
+            };
 
-            // Input
-            "mobile_device_mcp_type_keys" => {
-                let text = args
-                    .get("text")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing text")?;
-                handlers::handle_type_keys(&mut self.manager, device_id, platform, text)
+            if !self.handle_message(&line).await {
+                break;
             }
-            "mobile_device_mcp_press_button" => {
-                let button = args
-                    .get("button")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing button")?;
-                handlers::handle_press_button(&mut self.manager, device_id, platform, button)
+        }
+
+        // Input closed (or shutdown requested): the client is gone, exit cleanly
+        self.close().await;
+    }
+
+    /// Handle one JSON-RPC message from the client
+    ///
+    /// Lifecycle and listing requests are answered inline; requests that
+    /// talk to devices are spawned (see spawn_request) so a slow device never
+    /// blocks the session. Every response goes to the session's writer.
+    ///
+    /// # Returns
+    /// false once the client has requested shutdown
+    async fn handle_message(&mut self, line: &str) -> bool {
+        // Reap requests that have completed
+        while self.tasks.try_join_next().is_some() {}
+
+        if line.trim().is_empty() {
+            return true;
+        }
+        recording::received(line);
+
+        // The client answering one of our requests (e.g., a confirmation)
+        if self.client_requests.handle_response(line) {
+            return true;
+        }
+
+        let request = match McpRequest::from_json(line) {
+            Ok(req) => req,
+            Err(response) => {
+                let _ = self.writer.write_message(&response);
+                return true;
             }
+        };
+
+        if request.is_notification() {
+            self.handle_notification(&request);
+            return true;
+        }
+
+        let id = request.id.unwrap_or(serde_json::Value::Null);
 
-            // App Management
-            "mobile_device_mcp_launch_app" => {
-                let app_id = args
-                    .get("app_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing app_id")?;
-                handlers::handle_launch_app(&mut self.manager, device_id, platform, app_id)
+        if let Err(e) = self.state.check_request(&request.method) {
+            self.send_error(id, e);
+            return true;
+        }
+
+        match request.method.as_str() {
+            "initialize" => {
+                if self.handle_initialize(id, request.params) {
+                    self.state.on_initialize();
+                }
             }
-            "mobile_device_mcp_terminate_app" => {
-                let app_id = args
-                    .get("app_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing app_id")?;
-                handlers::handle_terminate_app(&mut self.manager, device_id, platform, app_id)
+            "ping" => self.send_response(id, serde_json::json!({})),
+            "shutdown" => {
+                self.wait_for_requests().await;
+                self.send_response(id, serde_json::json!({}));
+                self.state.on_shutdown();
+                return false;
+            }
+            "logging/setLevel" => self.handle_set_level(id, request.params),
+            "tools/list" => self.handle_tools_list(id),
+            "tools/call" => {
+                if let Some(params) = request.params {
+                    match serde_json::from_value::<ToolCallParams>(params) {
+                        Ok(tool_call) => self.handle_tool_call(id, tool_call),
+                        Err(e) => self.send_error(
+                            id,
+                            McpError::invalid_params(format!("Invalid params: {}", e)),
+                        ),
+                    }
+                } else {
+                    self.send_error(
+                        id,
+                        McpError::invalid_params("Missing params for tools/call"),
+                    );
+                }
             }
-            "mobile_device_mcp_install_app" => {
-                let app_path = args
-                    .get("app_path")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing app_path")?;
-                handlers::handle_install_app(&mut self.manager, device_id, platform, app_path)
+            "resources/list" => self.handle_resources_list(id),
+            "resources/templates/list" => self.handle_resource_templates_list(id),
+            "resources/read" => match resource_params(request.params, "resources/read") {
+                Ok(params) => self.handle_resource_read(id, params),
+                Err(e) => self.send_error(id, e),
+            },
+            "resources/subscribe" => match resource_params(request.params, "resources/subscribe") {
+                Ok(params) => self.handle_resource_subscription(id, params, true),
+                Err(e) => self.send_error(id, e),
+            },
+            "resources/unsubscribe" => {
+                match resource_params(request.params, "resources/unsubscribe") {
+                    Ok(params) => self.handle_resource_subscription(id, params, false),
+                    Err(e) => self.send_error(id, e),
+                }
             }
-            "mobile_device_mcp_uninstall_app" => {
-                let app_id = args
-                    .get("app_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing app_id")?;
-                handlers::handle_uninstall_app(&mut self.manager, device_id, platform, app_id)
+            "prompts/list" => self.handle_prompts_list(id),
+            "prompts/get" => match request
+                .params
+                .map(serde_json::from_value::<GetPromptParams>)
+            {
+                Some(Ok(params)) => self.handle_prompt_get(id, params),
+                Some(Err(e)) => self.send_error(
+                    id,
+                    McpError::invalid_params(format!("Invalid params: {}", e)),
+                ),
+                None => self.send_error(
+                    id,
+                    McpError::invalid_params("Missing params for prompts/get"),
+                ),
+            },
+            // Completions arrived in 2025-03-26; older sessions don't know the method
+            "completion/complete" if self.protocol_version.supports_completions() => {
+                match request.params.map(serde_json::from_value::<CompleteParams>) {
+                    Some(Ok(params)) => self.handle_complete(id, params),
+                    Some(Err(e)) => self.send_error(
+                        id,
+                        McpError::invalid_params(format!("Invalid params: {}", e)),
+                    ),
+                    None => self.send_error(
+                        id,
+                        McpError::invalid_params("Missing params for completion/complete"),
+                    ),
+                }
             }
+            method => self.send_error(id, McpError::method_not_found(method)),
+        }
+        true
+    }
 
-            // Navigation
-            "mobile_device_mcp_open_url" => {
-                let url = args
-                    .get("url")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing url")?;
-                handlers::handle_open_url(&mut self.manager, device_id, platform, url)
+    /// End the session: cancel running requests and stop background work
+    async fn close(&mut self) {
+        self.state.on_shutdown();
+        self.requests.cancel_all();
+        self.client_requests.cancel_all();
+        self.wait_for_requests().await;
+        self.watching = None;
+        let _ = self.writer.flush();
+    }
+
+    /// Handle a notification from the client
+    ///
+    /// Notifications never get a response, not even an error - unknown ones
+    /// are ignored as the JSON-RPC spec requires.
+    fn handle_notification(&mut self, notification: &McpRequest) {
+        match notification.method.as_str() {
+            "notifications/initialized" => {
+                self.state.on_initialized();
+                if self.state == SessionState::Ready && self.watching.is_none() {
+                    self.logger.enable(if self.settings.debug {
+                        LogLevel::Debug
+                    } else {
+                        LogLevel::Info
+                    });
+                    self.watching = Some(self.watcher.watch(
+                        self.writer.clone(),
+                        Arc::clone(&self.subscriptions),
+                        self.logger.clone(),
+                    ));
+                }
             }
-            "mobile_device_mcp_set_orientation" => {
-                let orientation = args
-                    .get("orientation")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing orientation")?;
-                handlers::handle_set_orientation(
-                    &mut self.manager,
-                    device_id,
-                    platform,
-                    orientation,
-                )
+            "notifications/roots/list_changed" => self.roots.invalidate(),
+            "notifications/cancelled" => {
+                let Some(params) = notification
+                    .params
+                    .clone()
+                    .and_then(|params| serde_json::from_value::<CancelledParams>(params).ok())
+                else {
+                    return;
+                };
+                // Unknown ids are requests that already completed: nothing to do
+                let cancelled = self.requests.cancel(&params.request_id);
+                self.logger.log(
+                    LogLevel::Info,
+                    logging::PROTOCOL,
+                    &format!(
+                        "Cancel request {} ({}): {}",
+                        params.request_id,
+                        params.reason.as_deref().unwrap_or("no reason"),
+                        if cancelled {
+                            "cancelled"
+                        } else {
+                            "not running"
+                        }
+                    ),
+                );
             }
-
-            _ => Err(format!("Unknown tool: {}", tool_name)),
+            method => self.logger.log(
+                LogLevel::Debug,
+                logging::PROTOCOL,
+                &format!("Ignoring notification: {}", method),
+            ),
         }
     }
+}
+
+/// Device a tools/call works on
+///
+/// Tools taking a device_id run on the named device (or alias), the device
+/// chosen with select_device, or else the only connected one. select_device
+/// itself doesn't use a device.
+fn tool_call_device(
+    selection: &DeviceSelection,
+    settings: &MobileDeviceMcpSettings,
+    tool_name: &str,
+    args: &serde_json::Value,
+) -> RequestDevice {
+    let takes_device = find_tool(tool_name)
+        .is_some_and(|tool| tool.input_schema["properties"].get("device_id").is_some());
+    if !takes_device || tool_name == "mobile_device_mcp_select_device" {
+        return RequestDevice::None;
+    }
 
-    fn run(&mut self) {
-        let stdin = io::stdin();
-        let reader = stdin.lock();
+    let requested = args.get("device_id").and_then(|v| v.as_str());
+    match selection.target(requested) {
+        Some(device_id) => RequestDevice::Id(device_id),
+        None => RequestDevice::OnlyConnected(
+            args.get("platform")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&settings.platform)
+                .to_string(),
+        ),
+    }
+}
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
+/// Route a tools/call to its handler
+///
+/// Arguments are validated against the tool's input schema first. Invalid
+/// arguments and unknown tool names are reported as -32602 (invalid params);
+/// failures inside a handler become a tool result with `isError: true`, and
+/// so does a destructive call the user declined. File paths are resolved
+/// against the client's workspace roots.
+///
+/// The device_id of device tools is already resolved (see tool_call_device);
+/// it is missing only when no single device is connected.
+#[allow(clippy::too_many_arguments)]
+fn dispatch_tool(
+    manager: &mut MobileDeviceManager,
+    settings: &MobileDeviceMcpSettings,
+    selection: &DeviceSelection,
+    tool_name: &str,
+    args: serde_json::Value,
+    progress: &ProgressReporter,
+    elicitation: &Elicitation,
+    roots: &Roots,
+) -> Result<serde_json::Value, McpError> {
+    let tool = find_tool(tool_name)
+        .ok_or_else(|| McpError::invalid_params(format!("Unknown tool: {}", tool_name)))?;
+    tools::validation::validate(&tool.input_schema, &args).map_err(invalid_arguments)?;
+    if let Err(message) = confirm_destructive(manager, settings, elicitation, tool, &args) {
+        return Ok(handlers::error_result(&message));
+    }
 
-            if line.trim().is_empty() {
-                continue;
-            }
+    let progress = &|percent: f64, step: &str| progress.report(percent, step);
+    let platform = |requested: &Option<String>| -> String {
+        requested
+            .clone()
+            .unwrap_or_else(|| settings.platform.clone())
+    };
+    // The device of a device tool and its platform: the requested one, the
+    // configured one, or the one the device is listed under
+    let target = |manager: &mut MobileDeviceManager,
+                  device_id: &Option<String>,
+                  requested: &Option<String>|
+     -> Result<(String, String), ToolError> {
+        let device_id = match device_id.as_deref().filter(|id| !id.is_empty()) {
+            Some(device_id) => device_id.to_string(),
+            None => manager
+                .only_device(&platform(requested))
+                .map_err(ToolError::Execution)?,
+        };
+        let platform = match platform(requested).as_str() {
+            "auto" => manager
+                .resolve_platform(&device_id)
+                .map_err(ToolError::Execution)?,
+            platform => platform.to_string(),
+        };
+        Ok((device_id, platform))
+    };
 
-            let request = match McpRequest::from_json(&line) {
-                Ok(req) => req,
-                Err(e) => {
-                    eprintln!("Failed to parse request: {}", e);
-                    continue;
-                }
-            };
+    let result = match tool_name {
+        // Device Info
+        "mobile_device_mcp_list_available_devices" => {
+            let a: ListDevicesArgs = decode_args(args)?;
+            handlers::handle_list_devices(manager, &platform(&a.platform))
+        }
+        "mobile_device_mcp_select_device" => {
+            let a: SelectDeviceArgs = decode_args(args)?;
+            handlers::handle_select_device(manager, selection, &a.device_id)
+        }
+        "mobile_device_mcp_get_screen_size" => {
+            let a: DeviceArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_get_screen_size(manager, &device_id, &platform)
+            })
+        }
+        "mobile_device_mcp_get_orientation" => {
+            let a: DeviceArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_get_orientation(manager, &device_id, &platform)
+            })
+        }
+        "mobile_device_mcp_list_apps" => {
+            let a: DeviceArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_list_apps(manager, &device_id, &platform)
+            })
+        }
+        "mobile_device_mcp_list_elements_on_screen" => {
+            let a: ListElementsArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_list_elements(
+                    manager,
+                    &device_id,
+                    &platform,
+                    a.filter.as_deref(),
+                    progress,
+                )
+            })
+        }
 
-            let id = request.id.unwrap_or(serde_json::Value::Null);
+        // Screen Interaction
+        "mobile_device_mcp_take_screenshot" => {
+            let a: DeviceArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_take_screenshot(manager, &device_id, &platform, progress)
+            })
+        }
+        "mobile_device_mcp_save_screenshot" => {
+            let a: SaveScreenshotArgs = decode_args(args)?;
+            resolve_path(roots, &a.output_path, PathAccess::Write).and_then(|output_path| {
+                target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                    handlers::handle_save_screenshot(
+                        manager,
+                        &device_id,
+                        &platform,
+                        &output_path,
+                        progress,
+                    )
+                })
+            })
+        }
+        "mobile_device_mcp_click_on_screen_at_coordinates" => {
+            let a: TapArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_click_screen(manager, &device_id, &platform, a.x, a.y)
+            })
+        }
+        "mobile_device_mcp_double_tap_on_screen" => {
+            let a: TapArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_double_tap(manager, &device_id, &platform, a.x, a.y, progress)
+            })
+        }
+        "mobile_device_mcp_long_press_on_screen_at_coordinates" => {
+            let a: LongPressArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_long_press(manager, &device_id, &platform, a.x, a.y, a.duration)
+            })
+        }
+        "mobile_device_mcp_swipe_on_screen" => {
+            let a: SwipeArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_swipe(
+                    manager, &device_id, &platform, a.start_x, a.start_y, a.end_x, a.end_y,
+                    a.duration,
+                )
+            })
+        }
 
-            match request.method.as_str() {
-                "initialize" => self.handle_initialize(id),
-                "tools/list" => self.handle_tools_list(id),
-                "tools/call" => {
-                    if let Some(params) = request.params {
-                        match serde_json::from_value::<ToolCallParams>(params) {
-                            Ok(tool_call) => self.handle_tool_call(id, tool_call),
-                            Err(e) => self.send_error(id, &format!("Invalid params: {}", e)),
-                        }
-                    } else {
-                        self.send_error(id, "Missing params for tools/call");
-                    }
-                }
-                _ => self.send_error(id, &format!("Unknown method: {}", request.method)),
-            }
+        // Input
+        "mobile_device_mcp_type_keys" => {
+            let a: TypeKeysArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_type_keys(manager, &device_id, &platform, &a.text)
+            })
+        }
+        "mobile_device_mcp_press_button" => {
+            let a: PressButtonArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_press_button(manager, &device_id, &platform, &a.button)
+            })
+        }
+
+        // App Management
+        "mobile_device_mcp_launch_app" => {
+            let a: LaunchAppArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_launch_app(manager, &device_id, &platform, &a.app_id)
+            })
+        }
+        "mobile_device_mcp_terminate_app" => {
+            let a: AppArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_terminate_app(manager, &device_id, &platform, &a.app_id)
+            })
         }
+        "mobile_device_mcp_install_app" => {
+            let a: InstallAppArgs = decode_args(args)?;
+            resolve_path(roots, &a.app_path, PathAccess::Read).and_then(|app_path| {
+                target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                    handlers::handle_install_app(
+                        manager, &device_id, &platform, &app_path, progress,
+                    )
+                })
+            })
+        }
+        "mobile_device_mcp_uninstall_app" => {
+            let a: AppArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_uninstall_app(manager, &device_id, &platform, &a.app_id)
+            })
+        }
+
+        // Navigation
+        "mobile_device_mcp_open_url" => {
+            let a: OpenUrlArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_open_url(manager, &device_id, &platform, &a.url)
+            })
+        }
+        "mobile_device_mcp_set_orientation" => {
+            let a: SetOrientationArgs = decode_args(args)?;
+            target(manager, &a.device_id, &a.platform).and_then(|(device_id, platform)| {
+                handlers::handle_set_orientation(manager, &device_id, &platform, &a.orientation)
+            })
+        }
+
+        _ => {
+            return Err(McpError::invalid_params(format!(
+                "Unknown tool: {}",
+                tool_name
+            )))
+        }
+    };
+
+    // Execution failures are a normal result so the model can see them and recover
+    match result {
+        Ok(response) => Ok(response),
+        Err(ToolError::Execution(message)) => Ok(handlers::error_result(&message)),
+        Err(ToolError::InvalidArguments(message)) => Err(McpError::invalid_params(message)),
     }
 }
 
+/// Ask the user before a destructive tool call touches a device
+///
+/// Only possible when the client supports elicitation; other clients rely on
+/// the tool's destructiveHint to ask on their own.
+///
+/// # Returns
+/// Err with the reason to report if the call must not run
+fn confirm_destructive(
+    manager: &mut MobileDeviceManager,
+    settings: &MobileDeviceMcpSettings,
+    elicitation: &Elicitation,
+    tool: &ToolDefinition,
+    args: &serde_json::Value,
+) -> Result<(), String> {
+    if !elicitation.is_supported() || !tool.is_destructive() {
+        return Ok(());
+    }
+    // Without a device the call fails before doing anything
+    let Some(device_id) = args.get("device_id").and_then(|v| v.as_str()) else {
+        return Ok(());
+    };
+    let platform = args
+        .get("platform")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&settings.platform);
+    let device = manager
+        .list_all_devices(platform)
+        .into_iter()
+        .find(|device| device.id == device_id);
+    let Some(message) = tools::confirmation::confirmation_message(
+        tool,
+        args,
+        device.as_ref(),
+        settings.auto_approve_virtual_devices,
+    ) else {
+        return Ok(());
+    };
+
+    match elicitation.confirm(&message) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Not confirmed by the user: {}", message)),
+        Err(e) => Err(format!("Could not get confirmation: {}", e)),
+    }
+}
+
+/// Resolve a tool's file path against the workspace roots
+///
+/// A refused path is reported to the model like any other tool failure.
+fn resolve_path(roots: &Roots, path: &str, access: PathAccess) -> Result<String, ToolError> {
+    roots
+        .resolve(path, access)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(ToolError::Execution)
+}
+
+/// Report arguments that don't match the tool's input schema (-32602)
+///
+/// The message lists every mismatch; `data.errors` has them as
+/// `{path, message}` objects for clients that want to point at the argument.
+fn invalid_arguments(errors: Vec<tools::validation::ValidationError>) -> McpError {
+    let message = errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    let data: Vec<_> = errors
+        .iter()
+        .map(|e| serde_json::json!({ "path": e.path, "message": e.message }))
+        .collect();
+    McpError::invalid_params(format!("Invalid arguments: {}", message))
+        .with_data(serde_json::json!({ "errors": data }))
+}
+
+/// Decode tools/call arguments into the tool's argument type (-32602 if they don't fit)
+fn decode_args<T: serde::de::DeserializeOwned>(args: serde_json::Value) -> Result<T, McpError> {
+    tools::args::decode(args).map_err(McpError::invalid_params)
+}
+
+/// Decode the {uri} params shared by the resources/* methods
+fn resource_params(
+    params: Option<serde_json::Value>,
+    method: &str,
+) -> Result<ResourceUriParams, McpError> {
+    let params =
+        params.ok_or_else(|| McpError::invalid_params(format!("Missing params for {}", method)))?;
+    serde_json::from_value(params)
+        .map_err(|e| McpError::invalid_params(format!("Invalid params: {}", e)))
+}
+
 // ============================================================================
 // Main Entry Point
 // ============================================================================
 
+/// Command line usage
+const USAGE: &str =
+    "Usage: mobile-device-mcp-server [--http <addr> [--allow-host <host>]... | --daemon | --proxy]
+                               [--socket <path>]
+                               [--record <file> | --replay <file>]
+
+Without options the server speaks MCP over stdin/stdout.
+
+Options:
+  --http <addr>    Serve MCP over Streamable HTTP on <addr> (e.g., 127.0.0.1:8080)
+  --allow-host <host>
+                   Also accept HTTP requests addressed to <host> (repeatable)
+  --daemon         Serve MCP sessions on a Unix socket, shared by all clients
+  --proxy          Forward stdin/stdout to the daemon, starting it if needed
+  --socket <path>  Socket for --daemon and --proxy (default: per-user socket)
+  --record <file>  Write MCP traffic and raw device output to a JSONL transcript
+  --replay <file>  Answer device calls from a transcript instead of devices
+  -h, --help       Show this help";
+
+/// Command line options
+#[derive(Debug, Default)]
+struct Options {
+    /// Serve Streamable HTTP on this address instead of stdio
+    http: Option<SocketAddr>,
+    /// Host names HTTP requests may use besides the loopback ones
+    allowed_hosts: Vec<String>,
+    /// Serve sessions on a Unix socket
+    daemon: bool,
+    /// Forward stdio to the daemon
+    proxy: bool,
+    /// Socket for daemon and proxy mode
+    socket: Option<PathBuf>,
+    /// Transcript to record the session to
+    record: Option<PathBuf>,
+    /// Transcript to replay device calls from
+    replay: Option<PathBuf>,
+    help: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--http" => {
+                let addr = args.next().ok_or("--http needs an address")?;
+                let addr = addr
+                    .parse()
+                    .map_err(|e| format!("Invalid --http address '{}': {}", addr, e))?;
+                options.http = Some(addr);
+            }
+            "--allow-host" => {
+                let host = args.next().ok_or("--allow-host needs a host name")?;
+                options.allowed_hosts.push(host);
+            }
+            "--daemon" => options.daemon = true,
+            "--proxy" => options.proxy = true,
+            "--socket" => {
+                let path = args.next().ok_or("--socket needs a path")?;
+                options.socket = Some(PathBuf::from(path));
+            }
+            "--record" => {
+                let path = args.next().ok_or("--record needs a file")?;
+                options.record = Some(PathBuf::from(path));
+            }
+            "--replay" => {
+                let path = args.next().ok_or("--replay needs a file")?;
+                options.replay = Some(PathBuf::from(path));
+            }
+            "-h" | "--help" => options.help = true,
+            other => return Err(format!("Unknown option: {}", other)),
+        }
+    }
+
+    let modes = [options.http.is_some(), options.daemon, options.proxy];
+    if modes.iter().filter(|&&mode| mode).count() > 1 {
+        return Err("--http, --daemon and --proxy are mutually exclusive".to_string());
+    }
+    if !options.allowed_hosts.is_empty() && options.http.is_none() {
+        return Err("--allow-host needs --http".to_string());
+    }
+    if options.socket.is_some() && !options.daemon && !options.proxy {
+        return Err("--socket needs --daemon or --proxy".to_string());
+    }
+    if options.record.is_some() && options.replay.is_some() {
+        return Err("--record and --replay are mutually exclusive".to_string());
+    }
+    // The proxy only forwards bytes; the daemon does the device work
+    if options.proxy && (options.record.is_some() || options.replay.is_some()) {
+        return Err("--record and --replay don't apply to --proxy".to_string());
+    }
+    Ok(options)
+}
+
 fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let device_aliases = match std::env::var("MOBILE_DEVICE_MCP_DEVICE_ALIASES") {
+        Ok(aliases) => match serde_json::from_str(&aliases) {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                eprintln!("Invalid MOBILE_DEVICE_MCP_DEVICE_ALIASES: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => Default::default(),
+    };
     let settings = MobileDeviceMcpSettings {
-        debug: std::env::var("MOBILE_DEVICE_MCP_DEBUG").is_ok(),
+        debug: logging::debug_enabled(),
         platform: std::env::var("MOBILE_PLATFORM").unwrap_or_else(|_| "auto".to_string()),
+        auto_approve_virtual_devices: std::env::var_os("MOBILE_DEVICE_MCP_AUTO_APPROVE_VIRTUAL")
+            .is_some(),
+        device_aliases,
+    };
+
+    if settings.platform == "mock" {
+        let fixture = std::env::var_os("MOBILE_DEVICE_MCP_MOCK_FIXTURE").map(PathBuf::from);
+        if let Err(e) = devices::mock::enable(fixture.as_deref()) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let transcript = match (&options.record, &options.replay) {
+        (Some(path), _) => Some((path, recording::record_to(path))),
+        (_, Some(path)) => Some((path, recording::replay_from(path))),
+        _ => None,
+    };
+    if let Some((path, Err(e))) = transcript {
+        eprintln!("Cannot open transcript {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start async runtime: {}", e);
+            std::process::exit(1);
+        }
     };
 
-    let mut server = McpServer::new(settings);
-    server.run();
+    if let Some(addr) = options.http {
+        if let Err(e) = runtime.block_on(transport::http::serve(
+            addr,
+            settings,
+            &options.allowed_hosts,
+        )) {
+            eprintln!("HTTP server failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if options.daemon || options.proxy {
+        run_daemon_mode(&runtime, &options, settings);
+        return;
+    }
+
+    let mut server = McpServer::new(settings, MessageWriter::stdout(), &SharedDevices::default());
+    runtime.block_on(server.run(BufReader::new(tokio::io::stdin())));
+
+    // Don't wait for the stdin reader or device operations that outlived
+    // their cancellation
+    runtime.shutdown_background();
+}
+
+/// Run as the shared daemon or as a proxy to it
+#[cfg(unix)]
+fn run_daemon_mode(
+    runtime: &tokio::runtime::Runtime,
+    options: &Options,
+    settings: MobileDeviceMcpSettings,
+) {
+    let socket = match options.socket.clone() {
+        Some(socket) => socket,
+        None => match transport::daemon::default_socket_path() {
+            Ok(socket) => socket,
+            Err(e) => {
+                eprintln!("Cannot create the daemon socket directory: {}", e);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let result = if options.daemon {
+        runtime.block_on(transport::daemon::serve(&socket, settings))
+    } else {
+        runtime.block_on(transport::daemon::proxy(&socket))
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(unix))]
+fn run_daemon_mode(
+    _runtime: &tokio::runtime::Runtime,
+    _options: &Options,
+    _settings: MobileDeviceMcpSettings,
+) {
+    eprintln!("--daemon and --proxy need Unix domain sockets, which this platform lacks");
+    std::process::exit(2);
 }
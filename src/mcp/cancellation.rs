@@ -0,0 +1,112 @@
+// mobile-mcp-zed-extension/src/mcp/cancellation.rs
+// Request Cancellation
+//
+// Requests that talk to a device run concurrently and may take a long time.
+// Each one is tracked by its JSON-RPC id until its response is sent, so a
+// "notifications/cancelled" from the client can find and cancel it. The
+// token's abort hooks then kill the adb/xcrun process the request is waiting
+// on (see devices/cancel.rs).
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::devices::cancel::CancellationToken;
+
+/// Requests that are still running, by id
+#[derive(Clone, Default)]
+pub struct RequestTracker {
+    requests: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl RequestTracker {
+    /// Start tracking a request
+    ///
+    /// # Returns
+    /// The token the request's device code runs under
+    pub fn start(&self, id: &Value) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.lock().insert(Self::key(id), token.clone());
+        token
+    }
+
+    /// Stop tracking a request once it has completed
+    pub fn finish(&self, id: &Value) {
+        self.lock().remove(&Self::key(id));
+    }
+
+    /// Cancel a running request
+    ///
+    /// # Returns
+    /// false if no request with this id is running (it may already have
+    /// completed, which the spec allows)
+    pub fn cancel(&self, id: &Value) -> bool {
+        let token = self.lock().remove(&Self::key(id));
+        match token {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every running request (the client went away)
+    pub fn cancel_all(&self) {
+        let tokens: Vec<_> = self.lock().drain().map(|(_, token)| token).collect();
+        for token in tokens {
+            token.cancel();
+        }
+    }
+
+    /// Ids compare by their JSON text, so 1 and "1" stay distinct
+    fn key(id: &Value) -> String {
+        id.to_string()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cancel_running_request() {
+        let tracker = RequestTracker::default();
+        let token = tracker.start(&json!(7));
+        assert!(tracker.cancel(&json!(7)));
+        assert!(token.is_cancelled());
+        // Already gone
+        assert!(!tracker.cancel(&json!(7)));
+    }
+
+    #[test]
+    fn test_cancel_after_finish_is_ignored() {
+        let tracker = RequestTracker::default();
+        let token = tracker.start(&json!("abc"));
+        tracker.finish(&json!("abc"));
+        assert!(!tracker.cancel(&json!("abc")));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_ids_are_not_coerced() {
+        let tracker = RequestTracker::default();
+        let number = tracker.start(&json!(1));
+        assert!(!tracker.cancel(&json!("1")));
+        assert!(!number.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_all() {
+        let tracker = RequestTracker::default();
+        let first = tracker.start(&json!(1));
+        let second = tracker.start(&json!(2));
+        tracker.cancel_all();
+        assert!(first.is_cancelled() && second.is_cancelled());
+    }
+}
@@ -0,0 +1,375 @@
+// mobile-mcp-zed-extension/src/mcp/elicitation.rs
+// Requests to the Client (Elicitation)
+//
+// Clients that declare the `elicitation` capability (2025-06-18 and later)
+// accept "elicitation/create" requests: the server asks the user a question
+// and waits for the answer. This server uses it to confirm destructive tool
+// calls before they run.
+//
+// The request is written like any other message; the client's response comes
+// back on the same input as its own requests. ClientRequests matches it to
+// the waiting request by id. Tool handlers run on worker threads, so waiting
+// blocks the thread, polling the request's cancellation token meanwhile.
+//
+// Each request remembers the client request it was made for, so the HTTP
+// transport can send it on that request's stream. A transport with nowhere to
+// send it fails it right away instead of letting it wait for the timeout.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::protocol::ProtocolVersion;
+use super::writer::MessageWriter;
+use crate::devices::cancel;
+
+/// How long the user gets to answer a question
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How often a waiting request checks for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Result of a request to the client: its `result`, or its `error.message`
+type ClientResult = Result<Value, String>;
+
+/// A request sent to the client
+struct Pending {
+    sender: Sender<ClientResult>,
+    /// Id of the client request it was sent for (JSON)
+    origin: Option<String>,
+}
+
+/// Requests sent to the client that are still waiting for a response
+#[derive(Clone, Default)]
+pub struct ClientRequests {
+    next_id: Arc<AtomicU64>,
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    /// Client request that requests sent through this handle are for
+    origin: Option<String>,
+}
+
+impl ClientRequests {
+    /// Handle for requests made while handling a client request
+    ///
+    /// # Arguments
+    /// * `id` - Id of the client request
+    pub fn for_request(&self, id: &Value) -> Self {
+        Self {
+            origin: Some(id.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Send a request to the client and block until it answers
+    ///
+    /// # Arguments
+    /// * `writer` - Where the request is written
+    /// * `method` - Request method (e.g., "elicitation/create")
+    /// * `params` - Request params
+    ///
+    /// # Returns
+    /// The response's `result`, or an error if the client answered with an
+    /// error, didn't answer in time, went away or the request was cancelled
+    pub fn send(&self, writer: &MessageWriter, method: &str, params: Value) -> ClientResult {
+        let id = Value::String(format!(
+            "server-{}",
+            self.next_id.fetch_add(1, Ordering::SeqCst)
+        ));
+        let (sender, receiver) = mpsc::channel();
+        self.lock().insert(
+            id.to_string(),
+            Pending {
+                sender,
+                origin: self.origin.clone(),
+            },
+        );
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+        if let Err(e) = writer.write_message(&request) {
+            self.lock().remove(&id.to_string());
+            return Err(format!("Failed to send {}: {}", method, e));
+        }
+
+        let deadline = Instant::now() + ANSWER_TIMEOUT;
+        let result = loop {
+            if let Err(e) = cancel::check() {
+                break Err(e);
+            }
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(result) => break result,
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Err("The client went away".to_string())
+                }
+                Err(RecvTimeoutError::Timeout) if Instant::now() >= deadline => {
+                    break Err(format!("No answer to {} in time", method))
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+        };
+        self.lock().remove(&id.to_string());
+        result
+    }
+
+    /// Deliver a message from the client if it is a response
+    ///
+    /// # Returns
+    /// false if the message is not a JSON-RPC response (it is a request or
+    /// notification, or not valid JSON) and must be handled as usual.
+    /// Responses to unknown ids (e.g., after a timeout) are dropped.
+    pub fn handle_response(&self, line: &str) -> bool {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            return false;
+        };
+        if message.get("method").is_some() {
+            return false;
+        }
+        let result = match (message.get("result"), message.get("error")) {
+            (Some(result), _) => Ok(result.clone()),
+            (None, Some(error)) => Err(error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown error")
+                .to_string()),
+            (None, None) => return false,
+        };
+
+        let id = message.get("id").unwrap_or(&Value::Null).to_string();
+        if let Some(pending) = self.lock().remove(&id) {
+            let _ = pending.sender.send(result);
+        }
+        true
+    }
+
+    /// Client request a waiting request was sent for
+    ///
+    /// # Arguments
+    /// * `id` - Id of the request to the client (JSON)
+    pub fn origin_of(&self, id: &str) -> Option<String> {
+        self.lock()
+            .get(id)
+            .and_then(|pending| pending.origin.clone())
+    }
+
+    /// Fail a waiting request that couldn't be delivered
+    ///
+    /// # Arguments
+    /// * `id` - Id of the request to the client (JSON)
+    /// * `message` - Error the waiting request returns
+    pub fn fail(&self, id: &str, message: &str) {
+        if let Some(pending) = self.lock().remove(id) {
+            let _ = pending.sender.send(Err(message.to_string()));
+        }
+    }
+
+    /// Fail every waiting request (the client went away)
+    pub fn cancel_all(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Pending>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Asks the user questions through "elicitation/create"
+pub struct Elicitation {
+    writer: MessageWriter,
+    requests: ClientRequests,
+    /// The client declared the capability and the protocol has it
+    supported: bool,
+}
+
+impl Elicitation {
+    /// Create an elicitation handle for one request
+    ///
+    /// # Arguments
+    /// * `writer` - Shared writer for outgoing messages
+    /// * `requests` - The session's pending client requests
+    /// * `client_supports` - The client declared `capabilities.elicitation`
+    /// * `version` - Negotiated protocol version
+    pub fn new(
+        writer: MessageWriter,
+        requests: ClientRequests,
+        client_supports: bool,
+        version: ProtocolVersion,
+    ) -> Self {
+        Self {
+            writer,
+            requests,
+            supported: client_supports && version.supports_elicitation(),
+        }
+    }
+
+    /// Whether questions can be asked at all
+    pub fn is_supported(&self) -> bool {
+        self.supported
+    }
+
+    /// Ask the user to confirm an action
+    ///
+    /// # Arguments
+    /// * `message` - The question shown to the user
+    ///
+    /// # Returns
+    /// true if the user accepted, false if they declined or dismissed it
+    pub fn confirm(&self, message: &str) -> Result<bool, String> {
+        let result =
+            self.requests
+                .send(&self.writer, "elicitation/create", Self::params(message))?;
+        Ok(result.get("action").and_then(Value::as_str) == Some("accept"))
+    }
+
+    /// Params of a yes/no question: the answer is the action, no form fields
+    fn params(message: &str) -> Value {
+        serde_json::json!({
+            "message": message,
+            "requestedSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Answer the next request written to the channel with `result`
+    fn answer(
+        requests: &ClientRequests,
+        mut messages: tokio::sync::mpsc::UnboundedReceiver<String>,
+        result: Value,
+    ) -> thread::JoinHandle<Value> {
+        let requests = requests.clone();
+        thread::spawn(move || {
+            let request: Value = serde_json::from_str(&messages.blocking_recv().unwrap()).unwrap();
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": result
+            });
+            assert!(requests.handle_response(&response.to_string()));
+            request
+        })
+    }
+
+    #[test]
+    fn test_confirm_accepted() {
+        let (writer, messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+        let elicitation = Elicitation::new(writer, requests.clone(), true, ProtocolVersion::LATEST);
+
+        let client = answer(&requests, messages, serde_json::json!({"action": "accept"}));
+        assert_eq!(elicitation.confirm("Uninstall?"), Ok(true));
+        let request = client.join().unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+        assert_eq!(request["params"]["message"], "Uninstall?");
+        assert_eq!(request["params"]["requestedSchema"]["type"], "object");
+    }
+
+    #[test]
+    fn test_confirm_declined() {
+        let (writer, messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+        let elicitation = Elicitation::new(writer, requests.clone(), true, ProtocolVersion::LATEST);
+
+        let client = answer(
+            &requests,
+            messages,
+            serde_json::json!({"action": "decline"}),
+        );
+        assert_eq!(elicitation.confirm("Uninstall?"), Ok(false));
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn test_error_response_fails_request() {
+        let (writer, mut messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+
+        let responder = requests.clone();
+        let client = thread::spawn(move || {
+            let request: Value = serde_json::from_str(&messages.blocking_recv().unwrap()).unwrap();
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32601, "message": "Method not found"}
+            });
+            responder.handle_response(&response.to_string());
+        });
+
+        let result = requests.send(&writer, "elicitation/create", serde_json::json!({}));
+        client.join().unwrap();
+        assert_eq!(result, Err("Method not found".to_string()));
+    }
+
+    #[test]
+    fn test_requests_and_notifications_are_not_responses() {
+        let requests = ClientRequests::default();
+        assert!(!requests.handle_response(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#));
+        assert!(
+            !requests.handle_response(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+        );
+        assert!(!requests.handle_response("not json"));
+        // Unknown ids are still consumed
+        assert!(requests.handle_response(r#"{"jsonrpc":"2.0","id":"x","result":{}}"#));
+    }
+
+    #[test]
+    fn test_cancelled_request_stops_waiting() {
+        let (writer, _messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+        let token = cancel::CancellationToken::new();
+        token.cancel();
+
+        let result = token.run(|| requests.send(&writer, "elicitation/create", Value::Null));
+        assert_eq!(result, Err(cancel::CANCELLED.to_string()));
+    }
+
+    #[test]
+    fn test_undeliverable_request_fails_at_once() {
+        let (writer, mut messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+        let for_call = requests.for_request(&serde_json::json!(7));
+
+        let router = requests.clone();
+        let transport = thread::spawn(move || {
+            let request: Value = serde_json::from_str(&messages.blocking_recv().unwrap()).unwrap();
+            let id = request["id"].to_string();
+            assert_eq!(router.origin_of(&id).as_deref(), Some("7"));
+            router.fail(&id, "No stream");
+        });
+
+        let started = Instant::now();
+        let result = for_call.send(&writer, "elicitation/create", Value::Null);
+        transport.join().unwrap();
+        assert_eq!(result, Err("No stream".to_string()));
+        assert!(started.elapsed() < ANSWER_TIMEOUT);
+    }
+
+    #[test]
+    fn test_unsupported_without_capability_or_old_protocol() {
+        let writer = MessageWriter::new(std::io::sink());
+        let requests = ClientRequests::default();
+        assert!(!Elicitation::new(
+            writer.clone(),
+            requests.clone(),
+            false,
+            ProtocolVersion::LATEST
+        )
+        .is_supported());
+        assert!(
+            !Elicitation::new(writer, requests, true, ProtocolVersion::V2025_03_26).is_supported()
+        );
+    }
+}
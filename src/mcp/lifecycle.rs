@@ -0,0 +1,128 @@
+// mobile-mcp-zed-extension/src/mcp/lifecycle.rs
+// MCP Session Lifecycle
+//
+// An MCP session moves through a fixed sequence of phases:
+//
+//   AwaitingInitialize --initialize--> Initializing --notifications/initialized--> Ready
+//                                                                                   |
+//   (any phase) ------------------------------shutdown / stdin EOF-----------> ShuttingDown
+//
+// Only "initialize" and "ping" are accepted before the handshake. Requests that
+// arrive in the wrong phase are rejected with -32600 (invalid request) so the
+// client gets an answer instead of waiting forever.
+
+use super::protocol::McpError;
+
+/// Lifecycle phase of an MCP session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionState {
+    /// Waiting for the client's "initialize" request
+    #[default]
+    AwaitingInitialize,
+    /// "initialize" has been answered; waiting for "notifications/initialized"
+    Initializing,
+    /// Handshake complete, all methods are available
+    Ready,
+    /// "shutdown" was received; no further requests are served
+    ShuttingDown,
+}
+
+impl SessionState {
+    /// Check whether a request may be served in the current phase
+    ///
+    /// Notifications are never checked here - they get no response, so there
+    /// is nothing to reject.
+    ///
+    /// # Arguments
+    /// * `method` - JSON-RPC method of the incoming request
+    ///
+    /// # Returns
+    /// Ok if the request may be dispatched, or the error to send back
+    pub fn check_request(&self, method: &str) -> Result<(), McpError> {
+        match (self, method) {
+            (SessionState::ShuttingDown, _) => {
+                Err(McpError::invalid_request("Server is shutting down"))
+            }
+            (_, "ping") => Ok(()),
+            (SessionState::AwaitingInitialize, "initialize") => Ok(()),
+            (SessionState::AwaitingInitialize, _) => Err(McpError::invalid_request(
+                "Server not initialized: send \"initialize\" first",
+            )),
+            (_, "initialize") => Err(McpError::invalid_request("Server already initialized")),
+            // Clients may send requests as soon as the initialize response arrives,
+            // so Initializing behaves like Ready for incoming requests
+            (SessionState::Initializing | SessionState::Ready, _) => Ok(()),
+        }
+    }
+
+    /// Transition after the "initialize" response has been sent
+    pub fn on_initialize(&mut self) {
+        if *self == SessionState::AwaitingInitialize {
+            *self = SessionState::Initializing;
+        }
+    }
+
+    /// Transition on the client's "notifications/initialized"
+    pub fn on_initialized(&mut self) {
+        if *self == SessionState::Initializing {
+            *self = SessionState::Ready;
+        }
+    }
+
+    /// Transition on "shutdown" or end of input
+    pub fn on_shutdown(&mut self) {
+        *self = SessionState::ShuttingDown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_calls_rejected_before_initialize() {
+        let state = SessionState::default();
+        let error = state.check_request("tools/call").unwrap_err();
+        assert_eq!(error.code, McpError::INVALID_REQUEST);
+        assert!(state.check_request("initialize").is_ok());
+        assert!(state.check_request("ping").is_ok());
+    }
+
+    #[test]
+    fn test_handshake_transitions() {
+        let mut state = SessionState::default();
+        state.on_initialize();
+        assert_eq!(state, SessionState::Initializing);
+        assert!(state.check_request("tools/list").is_ok());
+
+        state.on_initialized();
+        assert_eq!(state, SessionState::Ready);
+        assert!(state.check_request("tools/call").is_ok());
+    }
+
+    #[test]
+    fn test_second_initialize_rejected() {
+        let mut state = SessionState::default();
+        state.on_initialize();
+        state.on_initialized();
+        assert_eq!(
+            state.check_request("initialize").unwrap_err().code,
+            McpError::INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_initialized_notification_ignored_before_initialize() {
+        let mut state = SessionState::default();
+        state.on_initialized();
+        assert_eq!(state, SessionState::AwaitingInitialize);
+    }
+
+    #[test]
+    fn test_everything_rejected_after_shutdown() {
+        let mut state = SessionState::Ready;
+        state.on_shutdown();
+        assert!(state.check_request("ping").is_err());
+        assert!(state.check_request("tools/list").is_err());
+    }
+}
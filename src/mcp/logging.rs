@@ -0,0 +1,257 @@
+// mobile-mcp-zed-extension/src/mcp/logging.rs
+// Log Messages to the Client
+//
+// Implements the MCP logging capability. The client picks a minimum level
+// with "logging/setLevel" and receives every message at or above it as a
+// "notifications/message", tagged with the subsystem that wrote it:
+//
+//   adb      - Android device operations
+//   simctl   - iOS simulator and device operations
+//   protocol - Session handling (handshake, cancellation, notifications)
+//
+// Device code runs on worker threads that know nothing about the session, so
+// the server makes the session's logger the thread's current one (like
+// CancellationToken) and device code logs through the free `log` function.
+// With MOBILE_DEVICE_MCP_DEBUG set every message is also written to stderr.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::protocol::McpNotification;
+use super::writer::MessageWriter;
+
+/// Logger name for Android (adb) operations
+pub const ADB: &str = "adb";
+/// Logger name for iOS (simctl / libimobiledevice) operations
+pub const SIMCTL: &str = "simctl";
+/// Logger name for session and protocol handling
+pub const PROTOCOL: &str = "protocol";
+
+/// Environment variable that mirrors every message to stderr
+pub const DEBUG_ENV: &str = "MOBILE_DEVICE_MCP_DEBUG";
+
+/// Log severity, as defined by RFC 5424 (lowest first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Critical => "critical",
+            LogLevel::Alert => "alert",
+            LogLevel::Emergency => "emergency",
+        }
+    }
+}
+
+/// Sends log messages for one session
+///
+/// Clones share the minimum level, so "logging/setLevel" takes effect for
+/// requests that are already running.
+#[derive(Clone)]
+pub struct SessionLogger {
+    writer: MessageWriter,
+    /// None until the handshake completes: nothing is sent before that
+    level: Arc<Mutex<Option<LogLevel>>>,
+    /// Mirror every message to stderr
+    stderr: bool,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<SessionLogger>> = const { RefCell::new(None) };
+}
+
+impl SessionLogger {
+    /// Create a logger for a session
+    ///
+    /// # Arguments
+    /// * `writer` - Shared writer for outgoing messages
+    /// * `stderr` - Also write every message to stderr (debug mode)
+    pub fn new(writer: MessageWriter, stderr: bool) -> Self {
+        Self {
+            writer,
+            level: Arc::new(Mutex::new(None)),
+            stderr,
+        }
+    }
+
+    /// Minimum level sent to the client, None while logging is off
+    pub fn level(&self) -> Option<LogLevel> {
+        *self.level.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the minimum level sent to the client ("logging/setLevel")
+    pub fn set_level(&self, level: LogLevel) {
+        *self.level.lock().unwrap_or_else(|e| e.into_inner()) = Some(level);
+    }
+
+    /// Start sending messages once the session is ready
+    ///
+    /// Keeps the level the client already chose, if any.
+    pub fn enable(&self, default_level: LogLevel) {
+        self.level
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert(default_level);
+    }
+
+    /// Log a message
+    ///
+    /// # Arguments
+    /// * `level` - Severity of the message
+    /// * `logger` - Subsystem writing it (ADB, SIMCTL or PROTOCOL)
+    /// * `message` - The message itself
+    pub fn log(&self, level: LogLevel, logger: &str, message: &str) {
+        if self.stderr {
+            write_stderr(level, logger, message);
+        }
+        if let Some(notification) = self.notification(level, logger, message) {
+            let _ = self.writer.write_message(&notification);
+        }
+    }
+
+    /// Run blocking code with this logger as the thread's current one
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+
+    /// Build the notification for a message, or None if it is filtered out
+    fn notification(
+        &self,
+        level: LogLevel,
+        logger: &str,
+        message: &str,
+    ) -> Option<McpNotification> {
+        if level < self.level()? {
+            return None;
+        }
+        Some(McpNotification::new(
+            "notifications/message",
+            Some(serde_json::json!({
+                "level": level,
+                "logger": logger,
+                "data": message
+            })),
+        ))
+    }
+}
+
+fn write_stderr(level: LogLevel, logger: &str, message: &str) {
+    eprintln!(
+        "[{}] {}: {}",
+        level.as_str().to_uppercase(),
+        logger,
+        message
+    );
+}
+
+/// Log through the current thread's session logger
+///
+/// Outside a session (or on a thread the server didn't set up) the message
+/// only goes to stderr, and only in debug mode.
+///
+/// # Arguments
+/// * `level` - Severity of the message
+/// * `logger` - Subsystem writing it (ADB, SIMCTL or PROTOCOL)
+/// * `message` - The message itself
+pub fn log(level: LogLevel, logger: &str, message: &str) {
+    let handled = CURRENT.with(|current| match current.borrow().as_ref() {
+        Some(session) => {
+            session.log(level, logger, message);
+            true
+        }
+        None => false,
+    });
+    if !handled && debug_enabled() {
+        write_stderr(level, logger, message);
+    }
+}
+
+/// Whether MOBILE_DEVICE_MCP_DEBUG is set
+pub fn debug_enabled() -> bool {
+    static DEBUG: OnceLock<bool> = OnceLock::new();
+    *DEBUG.get_or_init(|| std::env::var_os(DEBUG_ENV).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logger() -> (SessionLogger, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let (writer, messages) = MessageWriter::channel();
+        (SessionLogger::new(writer, false), messages)
+    }
+
+    #[test]
+    fn test_level_order_and_names() {
+        assert!(LogLevel::Debug < LogLevel::Info);
+        assert!(LogLevel::Warning < LogLevel::Emergency);
+        assert_eq!(
+            serde_json::from_value::<LogLevel>(serde_json::json!("warning")).unwrap(),
+            LogLevel::Warning
+        );
+        assert!(serde_json::from_value::<LogLevel>(serde_json::json!("verbose")).is_err());
+    }
+
+    #[test]
+    fn test_silent_until_enabled() {
+        let (logger, mut messages) = logger();
+        logger.log(LogLevel::Emergency, PROTOCOL, "too early");
+        assert!(messages.try_recv().is_err());
+
+        logger.enable(LogLevel::Info);
+        logger.log(LogLevel::Info, PROTOCOL, "ready");
+        let json: serde_json::Value = serde_json::from_str(&messages.try_recv().unwrap()).unwrap();
+        assert_eq!(json["method"], "notifications/message");
+        assert_eq!(json["params"]["level"], "info");
+        assert_eq!(json["params"]["logger"], "protocol");
+        assert_eq!(json["params"]["data"], "ready");
+    }
+
+    #[test]
+    fn test_set_level_filters_and_is_shared() {
+        let (logger, mut messages) = logger();
+        let clone = logger.clone();
+        logger.set_level(LogLevel::Warning);
+        // The client's choice survives the handshake default
+        logger.enable(LogLevel::Info);
+
+        clone.log(LogLevel::Info, ADB, "dropped");
+        assert!(messages.try_recv().is_err());
+
+        logger.set_level(LogLevel::Debug);
+        clone.log(LogLevel::Debug, ADB, "sent");
+        assert!(messages.try_recv().unwrap().contains("\"sent\""));
+    }
+
+    #[test]
+    fn test_free_function_uses_current_logger() {
+        let (logger, mut messages) = logger();
+        logger.set_level(LogLevel::Debug);
+
+        logger.run(|| log(LogLevel::Debug, SIMCTL, "inside"));
+        log(LogLevel::Debug, SIMCTL, "outside");
+
+        assert!(messages.try_recv().unwrap().contains("\"inside\""));
+        assert!(messages.try_recv().is_err());
+    }
+}
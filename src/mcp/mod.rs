@@ -1,9 +1,26 @@
 // mobile-mcp-zed-extension/src/mcp/mod.rs
 // MCP protocol handling module entry point
 
+pub mod cancellation;
+pub mod elicitation;
+pub mod lifecycle;
+pub mod logging;
+pub mod progress;
 pub mod protocol;
+pub mod roots;
+pub mod writer;
 
+pub use cancellation::RequestTracker;
+pub use elicitation::{ClientRequests, Elicitation};
+pub use lifecycle::SessionState;
+pub use logging::{LogLevel, SessionLogger};
+pub use progress::ProgressReporter;
 pub use protocol::{
-    Capabilities, InitializeResult, McpError, McpErrorResponse, McpRequest, McpResponse,
-    ServerInfo, ToolCallParams, ToolsCapability,
+    CancelledParams, Capabilities, ClientCapabilities, ClientInfo, CompleteParams,
+    CompletionArgument, CompletionContext, CompletionReference, CompletionsCapability,
+    GetPromptParams, InitializeParams, InitializeResult, LoggingCapability, McpError,
+    McpErrorResponse, McpNotification, McpRequest, McpResponse, PromptsCapability, ProtocolVersion,
+    RequestMeta, ResourceUriParams, ResourcesCapability, ServerInfo, SetLevelParams,
+    ToolCallParams, ToolsCapability,
 };
+pub use writer::MessageWriter;
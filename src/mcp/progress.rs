@@ -0,0 +1,134 @@
+// mobile-mcp-zed-extension/src/mcp/progress.rs
+// Progress Notifications
+//
+// A request may carry `_meta.progressToken`. While such a request runs, the
+// server sends "notifications/progress" with that token so the client can
+// show what a slow operation (APK push, uiautomator dump...) is doing. Without
+// a token every report is dropped.
+
+use serde_json::Value;
+use std::sync::Mutex;
+
+use super::protocol::{McpNotification, ProtocolVersion};
+use super::writer::MessageWriter;
+
+/// All progress is reported as a percentage
+const TOTAL: f64 = 100.0;
+
+/// Minimum increase between two notifications for the same step
+const MIN_STEP: f64 = 1.0;
+
+/// Sends progress notifications for one request
+pub struct ProgressReporter {
+    writer: MessageWriter,
+    token: Option<Value>,
+    /// `message` was added to progress notifications in 2025-03-26
+    with_message: bool,
+    /// Last percentage and step sent (progress must always increase)
+    last: Mutex<Option<(f64, String)>>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter for a request
+    ///
+    /// # Arguments
+    /// * `writer` - Shared writer for outgoing messages
+    /// * `token` - The request's `_meta.progressToken`, if any
+    /// * `version` - Negotiated protocol version
+    pub fn new(writer: MessageWriter, token: Option<Value>, version: ProtocolVersion) -> Self {
+        Self {
+            writer,
+            token,
+            with_message: version.supports_progress_message(),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Report that a step is starting
+    ///
+    /// Reports that don't increase the percentage are dropped, and so are
+    /// increases below one point within the same step to avoid flooding the
+    /// client during file transfers.
+    ///
+    /// # Arguments
+    /// * `percent` - Completed percentage (0-100)
+    /// * `step` - Short description of the step
+    pub fn report(&self, percent: f64, step: &str) {
+        let Some(notification) = self.notification(percent, step) else {
+            return;
+        };
+        let _ = self.writer.write_message(&notification);
+    }
+
+    /// Build the notification for a report, or None if it should be dropped
+    fn notification(&self, percent: f64, step: &str) -> Option<McpNotification> {
+        let token = self.token.as_ref()?;
+        let percent = percent.clamp(0.0, TOTAL);
+
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((last_percent, last_step)) = last.as_ref() {
+            let min_increase = if last_step == step { MIN_STEP } else { 0.0 };
+            if percent <= *last_percent || percent - last_percent < min_increase {
+                return None;
+            }
+        }
+        *last = Some((percent, step.to_string()));
+
+        let mut params = serde_json::json!({
+            "progressToken": token,
+            "progress": percent,
+            "total": TOTAL
+        });
+        if self.with_message {
+            params["message"] = Value::String(step.to_string());
+        }
+        Some(McpNotification::new("notifications/progress", Some(params)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reporter(token: Option<Value>, version: ProtocolVersion) -> ProgressReporter {
+        ProgressReporter::new(MessageWriter::new(std::io::sink()), token, version)
+    }
+
+    #[test]
+    fn test_no_token_no_notifications() {
+        let progress = reporter(None, ProtocolVersion::LATEST);
+        assert!(progress.notification(10.0, "Installing").is_none());
+    }
+
+    #[test]
+    fn test_notification_format() {
+        let progress = reporter(Some(serde_json::json!("abc")), ProtocolVersion::LATEST);
+        let notification = progress.notification(40.0, "Installing").unwrap();
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["method"], "notifications/progress");
+        assert_eq!(json["params"]["progressToken"], "abc");
+        assert_eq!(json["params"]["progress"], 40.0);
+        assert_eq!(json["params"]["total"], 100.0);
+        assert_eq!(json["params"]["message"], "Installing");
+    }
+
+    #[test]
+    fn test_message_omitted_for_old_protocol() {
+        let progress = reporter(Some(serde_json::json!(7)), ProtocolVersion::V2024_11_05);
+        let notification = progress.notification(40.0, "Installing").unwrap();
+        assert!(notification.params.unwrap().get("message").is_none());
+    }
+
+    #[test]
+    fn test_progress_only_increases() {
+        let progress = reporter(Some(serde_json::json!(1)), ProtocolVersion::LATEST);
+        assert!(progress.notification(0.0, "Dumping UI hierarchy").is_some());
+        assert!(progress.notification(0.0, "Dumping UI hierarchy").is_none());
+        assert!(progress.notification(20.3, "Pushing").is_some());
+        // Small increments within a step are throttled, a new step is not
+        assert!(progress.notification(20.8, "Pushing").is_none());
+        assert!(progress.notification(21.5, "Pushing").is_some());
+        assert!(progress.notification(21.6, "Installing").is_some());
+        assert!(progress.notification(10.0, "Verifying").is_none());
+    }
+}
@@ -14,6 +14,9 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+
+use super::logging::LogLevel;
 
 /// MCP JSON-RPC Request
 ///
@@ -42,7 +45,8 @@ pub struct McpRequest {
     /// Always "2.0" for JSON-RPC 2.0 protocol
     #[allow(dead_code)]
     pub jsonrpc: String,
-    /// Request identifier - used to match responses to requests
+    /// Request identifier - used to match responses to requests.
+    /// Absent for notifications, which must never be answered.
     pub id: Option<Value>,
     /// Method name to invoke (e.g., "initialize", "tools/list", "tools/call")
     pub method: String,
@@ -86,8 +90,8 @@ pub struct McpResponse {
 ///   "jsonrpc": "2.0",
 ///   "id": 1,
 ///   "error": {
-///     "code": -1,
-///     "message": "Device not found"
+///     "code": -32601,
+///     "message": "Method not found: sampling/createMessage"
 ///   }
 /// }
 /// ```
@@ -101,16 +105,179 @@ pub struct McpErrorResponse {
     pub error: McpError,
 }
 
+/// MCP JSON-RPC Notification
+///
+/// A message sent by the server without an id. The client never answers it.
+/// Used for resource change and (later) progress and log notifications.
+///
+/// # Example
+/// ```json
+/// {
+///   "jsonrpc": "2.0",
+///   "method": "notifications/resources/updated",
+///   "params": {"uri": "device://list"}
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct McpNotification {
+    /// Always "2.0" for JSON-RPC 2.0 protocol
+    pub jsonrpc: String,
+    /// Notification method (e.g., "notifications/resources/updated")
+    pub method: String,
+    /// Method-specific parameters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
 /// MCP Error
 ///
-/// Error information structure used in error responses.
-/// Code -1 is used for general application errors.
-#[derive(Debug, Serialize)]
+/// Error information structure used in error responses. Codes follow the
+/// JSON-RPC 2.0 specification so clients can tell a malformed request apart
+/// from an unknown method or a failure inside the server.
+#[derive(Debug, Clone, Serialize)]
 pub struct McpError {
-    /// Error code (-1 for general errors)
+    /// JSON-RPC error code (see the associated constants)
     pub code: i32,
     /// Human-readable error message
     pub message: String,
+    /// Additional error details (e.g., the uri of a missing resource)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// MCP Protocol Version
+///
+/// The protocol revisions this server can speak, oldest first. Revisions are
+/// dated strings, so their lexical order is also their chronological order.
+/// Features introduced by later revisions are only enabled when that revision
+/// (or a newer one) was negotiated during initialize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    /// Initial public MCP revision
+    V2024_11_05,
+    /// Adds tool annotations, completions and audio content
+    V2025_03_26,
+    /// Adds structured tool output, elicitation and `title` fields
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    /// All supported revisions, oldest first
+    pub const ALL: [ProtocolVersion; 3] = [
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+    ];
+
+    /// Newest supported revision
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V2025_06_18;
+
+    /// Wire representation used in "protocolVersion"
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// Pick the revision to use for a client's requested version
+    ///
+    /// Returns the requested revision if it is supported, otherwise the newest
+    /// supported revision that is not newer than the request. If the client
+    /// asks for something older than anything we support, the latest revision
+    /// is offered and the client decides whether to disconnect (as the MCP
+    /// specification prescribes).
+    pub fn negotiate(requested: &str) -> Self {
+        Self::ALL
+            .iter()
+            .rev()
+            .find(|version| version.as_str() <= requested)
+            .copied()
+            .unwrap_or(Self::LATEST)
+    }
+
+    /// Whether progress notifications may carry a `message` (2025-03-26+)
+    pub fn supports_progress_message(&self) -> bool {
+        *self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// Whether `Implementation.title` may be sent (2025-06-18+)
+    pub fn supports_implementation_title(&self) -> bool {
+        *self >= ProtocolVersion::V2025_06_18
+    }
+
+    /// Whether tools may carry `annotations` (2025-03-26+)
+    pub fn supports_tool_annotations(&self) -> bool {
+        *self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// Whether tools may declare `outputSchema` and return `structuredContent` (2025-06-18+)
+    pub fn supports_structured_output(&self) -> bool {
+        *self >= ProtocolVersion::V2025_06_18
+    }
+
+    /// Whether "completion/complete" is available (2025-03-26+)
+    pub fn supports_completions(&self) -> bool {
+        *self >= ProtocolVersion::V2025_03_26
+    }
+
+    /// Whether the server may send "elicitation/create" (2025-06-18+)
+    pub fn supports_elicitation(&self) -> bool {
+        *self >= ProtocolVersion::V2025_06_18
+    }
+}
+
+/// MCP Initialize Parameters
+///
+/// Parameters sent by the client with the "initialize" request. Only the
+/// fields the server acts on are parsed; everything else is ignored.
+///
+/// # Example
+/// ```json
+/// {
+///   "protocolVersion": "2025-06-18",
+///   "capabilities": {"roots": {}},
+///   "clientInfo": {"name": "Zed", "version": "0.200.0"}
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct InitializeParams {
+    /// Protocol revision the client would like to use
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: Option<String>,
+    /// Information about the client application
+    #[serde(rename = "clientInfo")]
+    pub client_info: Option<ClientInfo>,
+    /// Features the client supports
+    #[serde(default)]
+    pub capabilities: ClientCapabilities,
+}
+
+/// Client Capabilities
+///
+/// The client features the server makes use of. Each capability is an object
+/// whose presence means "supported"; its contents are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct ClientCapabilities {
+    /// The client answers "elicitation/create" requests
+    #[serde(default)]
+    pub elicitation: Option<Value>,
+    /// The client answers "roots/list" requests
+    #[serde(default)]
+    pub roots: Option<Value>,
+}
+
+/// Client Info
+///
+/// Name and version of the connecting MCP client.
+#[derive(Debug, Deserialize)]
+pub struct ClientInfo {
+    /// Client name (e.g., "Zed")
+    pub name: String,
+    /// Client version
+    #[serde(default)]
+    pub version: String,
 }
 
 /// MCP Initialize Result
@@ -122,14 +289,18 @@ pub struct McpError {
 /// # Example
 /// ```json
 /// {
-///   "protocolVersion": "2024-11-05",
+///   "protocolVersion": "2025-06-18",
 ///   "capabilities": {"tools": {}},
-///   "serverInfo": {"name": "mobile-device-mcp-server", "version": "1.0.0"}
+///   "serverInfo": {
+///     "name": "mobile-device-mcp-server",
+///     "title": "Mobile Device MCP",
+///     "version": "0.1.0"
+///   }
 /// }
 /// ```
 #[derive(Debug, Serialize)]
 pub struct InitializeResult {
-    /// MCP protocol version (currently "2024-11-05")
+    /// Negotiated MCP protocol version
     #[serde(rename = "protocolVersion")]
     pub protocol_version: String,
     /// Server capabilities (what features are supported)
@@ -141,12 +312,21 @@ pub struct InitializeResult {
 
 /// MCP Capabilities
 ///
-/// Describes what features the server supports. Currently only tools
-/// are supported (no resources, prompts, or sampling).
+/// Describes what features the server supports: tools, resources, prompts,
+/// logging and argument completion (no sampling).
 #[derive(Debug, Serialize)]
 pub struct Capabilities {
     /// Tools capability - indicates server can provide tools
     pub tools: ToolsCapability,
+    /// Resources capability - indicates server exposes device resources
+    pub resources: ResourcesCapability,
+    /// Prompts capability - indicates server provides prompt templates
+    pub prompts: PromptsCapability,
+    /// Logging capability - indicates server sends log messages
+    pub logging: LoggingCapability,
+    /// Completions capability - only sent for protocol 2025-03-26 and later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
 }
 
 /// Tools Capability
@@ -156,6 +336,40 @@ pub struct Capabilities {
 #[derive(Debug, Serialize)]
 pub struct ToolsCapability {}
 
+/// Resources Capability
+///
+/// Indicates that resources are supported. The resources are discovered via
+/// "resources/list" and "resources/templates/list".
+#[derive(Debug, Serialize)]
+pub struct ResourcesCapability {
+    /// Clients may subscribe to "notifications/resources/updated"
+    pub subscribe: bool,
+    /// Server sends "notifications/resources/list_changed" when devices come and go
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
+/// Prompts Capability
+///
+/// Empty struct indicating that prompts are supported. The prompts are
+/// discovered via "prompts/list" and rendered via "prompts/get".
+#[derive(Debug, Serialize)]
+pub struct PromptsCapability {}
+
+/// Logging Capability
+///
+/// Empty struct indicating that the server sends "notifications/message".
+/// The client sets the minimum level via "logging/setLevel".
+#[derive(Debug, Serialize)]
+pub struct LoggingCapability {}
+
+/// Completions Capability
+///
+/// Empty struct indicating that the server suggests argument values via
+/// "completion/complete".
+#[derive(Debug, Serialize)]
+pub struct CompletionsCapability {}
+
 /// Server Info
 ///
 /// Metadata about the MCP server including name and version.
@@ -164,7 +378,10 @@ pub struct ToolsCapability {}
 pub struct ServerInfo {
     /// Server name (e.g., "mobile-device-mcp-server")
     pub name: String,
-    /// Server version (e.g., "1.0.0")
+    /// Display name (only sent for protocol 2025-06-18 and later)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Server version, taken from the crate version (e.g., "0.1.0")
     pub version: String,
 }
 
@@ -187,28 +404,296 @@ pub struct ServerInfo {
 pub struct ToolCallParams {
     /// Name of the tool to invoke (must match a tool from tools/list)
     pub name: String,
-    /// Tool-specific arguments as a JSON object
+    /// Tool-specific arguments as a JSON object (may be omitted for tools without arguments)
+    #[serde(default)]
     pub arguments: Value,
+    /// Request metadata (e.g., the progress token)
+    #[serde(rename = "_meta", default)]
+    pub meta: Option<RequestMeta>,
+}
+
+/// Request Metadata
+///
+/// The `_meta` object a client may attach to request params.
+///
+/// # Example
+/// ```json
+/// {"progressToken": "install-1"}
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestMeta {
+    /// Token to use in "notifications/progress" for this request
+    #[serde(rename = "progressToken")]
+    pub progress_token: Option<Value>,
+}
+
+/// Resource URI Parameters
+///
+/// Parameters for the "resources/read", "resources/subscribe" and
+/// "resources/unsubscribe" methods.
+///
+/// # Example
+/// ```json
+/// {"uri": "device://emulator-5554/hierarchy.json"}
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct ResourceUriParams {
+    /// URI of the resource to read or (un)subscribe
+    pub uri: String,
+}
+
+/// Cancelled Notification Parameters
+///
+/// Parameters of the "notifications/cancelled" notification a client sends
+/// to abandon a request it is still waiting for.
+///
+/// # Example
+/// ```json
+/// {"requestId": 7, "reason": "User pressed stop"}
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledParams {
+    /// ID of the request to cancel
+    pub request_id: Value,
+    /// Optional reason, for logging only
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Set Level Parameters
+///
+/// Parameters of the "logging/setLevel" request.
+///
+/// # Example
+/// ```json
+/// {"level": "debug"}
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct SetLevelParams {
+    /// Minimum level of the log messages the client wants
+    pub level: LogLevel,
+}
+
+/// Get Prompt Parameters
+///
+/// Parameters for the "prompts/get" method.
+///
+/// # Example
+/// ```json
+/// {"name": "explore_app", "arguments": {"package": "com.example.app"}}
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct GetPromptParams {
+    /// Name of the prompt (must match a prompt from prompts/list)
+    pub name: String,
+    /// Prompt arguments (always strings)
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+/// Complete Parameters
+///
+/// Parameters of the "completion/complete" request: the prompt, resource
+/// template or tool the argument belongs to, the partially typed value and
+/// the arguments the user already filled in.
+///
+/// # Example
+/// ```json
+/// {
+///   "ref": {"type": "ref/tool", "name": "mobile_device_mcp_launch_app"},
+///   "argument": {"name": "app_id", "value": "com.ex"},
+///   "context": {"arguments": {"device_id": "emulator-5554"}}
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct CompleteParams {
+    /// What the argument belongs to
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    /// The argument being completed
+    pub argument: CompletionArgument,
+    /// Previously resolved arguments (sent by 2025-06-18 clients)
+    #[serde(default)]
+    pub context: CompletionContext,
+}
+
+/// Completion Reference
+///
+/// `ref/prompt` and `ref/resource` are defined by MCP. `ref/tool` is accepted
+/// as well so tool arguments can be completed by clients that offer it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    /// A prompt from prompts/list
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    /// A resource template from resources/templates/list
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+    /// A tool from tools/list
+    #[serde(rename = "ref/tool")]
+    Tool { name: String },
+}
+
+/// Completion Argument
+///
+/// Name of the argument and what has been typed so far.
+#[derive(Debug, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Completion Context
+#[derive(Debug, Default, Deserialize)]
+pub struct CompletionContext {
+    /// Arguments already filled in (e.g., the device_id when completing app_id)
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+impl McpError {
+    /// Invalid JSON was received by the server
+    pub const PARSE_ERROR: i32 = -32700;
+    /// The JSON sent is not a valid JSON-RPC request object
+    pub const INVALID_REQUEST: i32 = -32600;
+    /// The method does not exist or is not available
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    /// Invalid method parameters (including unknown tools and bad tool arguments)
+    pub const INVALID_PARAMS: i32 = -32602;
+    /// Internal error while handling the request (e.g. a device operation failed)
+    pub const INTERNAL_ERROR: i32 = -32603;
+    /// MCP-specific: the requested resource does not exist
+    pub const RESOURCE_NOT_FOUND: i32 = -32002;
+
+    /// Create an error with an explicit code
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Attach additional error details
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Create a -32700 parse error
+    pub fn parse_error(message: impl Into<String>) -> Self {
+        Self::new(Self::PARSE_ERROR, message)
+    }
+
+    /// Create a -32600 invalid request error
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_REQUEST, message)
+    }
+
+    /// Create a -32601 method not found error
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(
+            Self::METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )
+    }
+
+    /// Create a -32602 invalid params error
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    /// Create a -32603 internal error
+    pub fn internal_error(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+
+    /// Create a -32002 resource not found error carrying the uri
+    pub fn resource_not_found(uri: &str) -> Self {
+        Self::new(Self::RESOURCE_NOT_FOUND, "Resource not found")
+            .with_data(serde_json::json!({ "uri": uri }))
+    }
 }
 
 impl McpRequest {
     /// Parse a request from JSON string
     ///
     /// Deserializes a JSON-RPC request from a string. This is the entry point
-    /// for processing incoming MCP requests.
+    /// for processing incoming MCP requests. Failures are returned as ready-to-send
+    /// error responses:
+    /// - Malformed JSON yields a -32700 parse error with a null id
+    /// - Valid JSON that is not a JSON-RPC 2.0 request object yields a -32600
+    ///   invalid request error, echoing the id when one could be read
     ///
     /// # Arguments
     /// * `json_str` - JSON string containing the request
     ///
     /// # Returns
-    /// Parsed request or error message if JSON is invalid
+    /// Parsed request or the error response to send back to the client
     ///
     /// # Example
     /// ```rust
     /// let request = McpRequest::from_json(r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#)?;
     /// ```
-    pub fn from_json(json_str: &str) -> Result<Self, String> {
-        serde_json::from_str(json_str).map_err(|e| format!("Failed to parse request: {}", e))
+    pub fn from_json(json_str: &str) -> Result<Self, McpErrorResponse> {
+        let value: Value = serde_json::from_str(json_str).map_err(|e| {
+            McpErrorResponse::from_error(
+                Value::Null,
+                McpError::parse_error(format!("Parse error: {}", e)),
+            )
+        })?;
+
+        let object = match value.as_object() {
+            Some(object) => object,
+            None if value.is_array() => {
+                return Err(McpErrorResponse::from_error(
+                    Value::Null,
+                    McpError::invalid_request("Batch requests are not supported"),
+                ))
+            }
+            None => {
+                return Err(McpErrorResponse::from_error(
+                    Value::Null,
+                    McpError::invalid_request("Request must be a JSON object"),
+                ))
+            }
+        };
+
+        // MCP requires string or number ids; a missing id marks a notification
+        let id = match object.get("id") {
+            None => Value::Null,
+            Some(id @ (Value::String(_) | Value::Number(_))) => id.clone(),
+            Some(_) => {
+                return Err(McpErrorResponse::from_error(
+                    Value::Null,
+                    McpError::invalid_request("Request id must be a string or number"),
+                ))
+            }
+        };
+
+        if object.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+            return Err(McpErrorResponse::from_error(
+                id,
+                McpError::invalid_request("jsonrpc must be exactly \"2.0\""),
+            ));
+        }
+
+        serde_json::from_value(value).map_err(|e| {
+            McpErrorResponse::from_error(
+                id,
+                McpError::invalid_request(format!("Invalid request: {}", e)),
+            )
+        })
+    }
+}
+
+impl McpRequest {
+    /// Whether this message is a notification (no id, no response expected)
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
     }
 }
 
@@ -237,77 +722,96 @@ impl McpResponse {
             result,
         }
     }
-
-    /// Convert to JSON string
-    ///
-    /// Serializes the response to a JSON string suitable for sending to
-    /// the MCP client over stdout.
-    ///
-    /// # Returns
-    /// JSON string or error if serialization fails
-    pub fn to_json(&self) -> Result<String, String> {
-        serde_json::to_string(self).map_err(|e| format!("Failed to serialize response: {}", e))
-    }
 }
 
 impl McpErrorResponse {
     /// Create a new error response
     ///
-    /// Constructs an MCP error response for a failed operation. Use code -1
-    /// for general application errors.
+    /// Constructs an MCP error response for a failed operation. Build the
+    /// error with one of the [`McpError`] constructors so the JSON-RPC code
+    /// matches the kind of failure.
     ///
     /// # Arguments
-    /// * `id` - Request ID to match with the original request
-    /// * `code` - Error code (use -1 for general errors)
-    /// * `message` - Human-readable error description
+    /// * `id` - Request ID to match with the original request (null for parse errors)
+    /// * `error` - Error code and human-readable description
     ///
     /// # Example
     /// ```rust
-    /// let error = McpErrorResponse::error(
+    /// let error = McpErrorResponse::from_error(
     ///     json!(1),
-    ///     -1,
-    ///     "Device not found".to_string()
+    ///     McpError::method_not_found("sampling/createMessage"),
     /// );
     /// ```
-    pub fn error(id: Value, code: i32, message: String) -> Self {
+    pub fn from_error(id: Value, error: McpError) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             id,
-            error: McpError { code, message },
+            error,
         }
     }
+}
 
-    /// Convert to JSON string
+impl McpNotification {
+    /// Create a new notification
     ///
-    /// Serializes the error response to a JSON string suitable for sending
-    /// to the MCP client over stdout.
-    ///
-    /// # Returns
-    /// JSON string or error if serialization fails
-    pub fn to_json(&self) -> Result<String, String> {
-        serde_json::to_string(self).map_err(|e| format!("Failed to serialize error: {}", e))
+    /// # Arguments
+    /// * `method` - Notification method name
+    /// * `params` - Parameters, or None for notifications without any
+    pub fn new(method: &str, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    /// Create a "notifications/resources/updated" notification for a uri
+    pub fn resource_updated(uri: &str) -> Self {
+        Self::new(
+            "notifications/resources/updated",
+            Some(serde_json::json!({ "uri": uri })),
+        )
+    }
+
+    /// Create a "notifications/resources/list_changed" notification
+    pub fn resource_list_changed() -> Self {
+        Self::new("notifications/resources/list_changed", None)
     }
 }
 
 impl InitializeResult {
-    /// Create a new initialize result with default values
+    /// Create the initialize result for a negotiated protocol version
     ///
     /// Returns an initialize result with:
-    /// - Protocol version: "2024-11-05" (current MCP version)
-    /// - Capabilities: Tools only (no resources, prompts, sampling)
-    /// - Server info: "mobile-device-mcp-server" v1.0.0
+    /// - Protocol version: the negotiated revision
+    /// - Capabilities: Tools, resources, prompts, logging and (from 2025-03-26)
+    ///   completions (no sampling)
+    /// - Server info: "mobile-device-mcp-server" at the crate version, plus a
+    ///   display title when the revision supports it
     ///
     /// This is sent in response to the "initialize" method during the
     /// initial handshake between client and server.
-    pub fn new() -> Self {
+    pub fn new(version: ProtocolVersion) -> Self {
         Self {
-            protocol_version: "2024-11-05".to_string(),
+            protocol_version: version.as_str().to_string(),
             capabilities: Capabilities {
                 tools: ToolsCapability {},
+                resources: ResourcesCapability {
+                    subscribe: true,
+                    list_changed: true,
+                },
+                prompts: PromptsCapability {},
+                logging: LoggingCapability {},
+                completions: version
+                    .supports_completions()
+                    .then_some(CompletionsCapability {}),
             },
             server_info: ServerInfo {
                 name: "mobile-device-mcp-server".to_string(),
-                version: "1.0.0".to_string(),
+                title: version
+                    .supports_implementation_title()
+                    .then(|| "Mobile Device MCP".to_string()),
+                version: env!("CARGO_PKG_VERSION").to_string(),
             },
         }
     }
@@ -315,6 +819,209 @@ impl InitializeResult {
 
 impl Default for InitializeResult {
     fn default() -> Self {
-        Self::new()
+        Self::new(ProtocolVersion::LATEST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error_of(json_str: &str) -> McpErrorResponse {
+        McpRequest::from_json(json_str).expect_err("request should be rejected")
+    }
+
+    #[test]
+    fn test_parse_valid_request() {
+        let request =
+            McpRequest::from_json(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        assert_eq!(request.method, "tools/list");
+        assert_eq!(request.id, Some(serde_json::json!(1)));
+    }
+
+    #[test]
+    fn test_malformed_json_is_parse_error_with_null_id() {
+        let error = parse_error_of(r#"{"jsonrpc":"2.0","id":1,"method":"#);
+        assert_eq!(error.error.code, McpError::PARSE_ERROR);
+        assert!(error.id.is_null());
+    }
+
+    #[test]
+    fn test_missing_method_is_invalid_request_with_id() {
+        let error = parse_error_of(r#"{"jsonrpc":"2.0","id":7}"#);
+        assert_eq!(error.error.code, McpError::INVALID_REQUEST);
+        assert_eq!(error.id, serde_json::json!(7));
+    }
+
+    #[test]
+    fn test_wrong_jsonrpc_version_is_invalid_request() {
+        let error = parse_error_of(r#"{"jsonrpc":"1.0","id":"a","method":"ping"}"#);
+        assert_eq!(error.error.code, McpError::INVALID_REQUEST);
+        assert_eq!(error.id, serde_json::json!("a"));
+    }
+
+    #[test]
+    fn test_non_object_is_invalid_request() {
+        assert_eq!(parse_error_of("42").error.code, McpError::INVALID_REQUEST);
+        assert_eq!(parse_error_of("[]").error.code, McpError::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_invalid_id_type_is_invalid_request_with_null_id() {
+        let error = parse_error_of(r#"{"jsonrpc":"2.0","id":{"x":1},"method":"ping"}"#);
+        assert_eq!(error.error.code, McpError::INVALID_REQUEST);
+        assert!(error.id.is_null());
+
+        let error = parse_error_of(r#"{"jsonrpc":"2.0","id":null,"method":"ping"}"#);
+        assert_eq!(error.error.code, McpError::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_notification_has_no_id() {
+        let request =
+            McpRequest::from_json(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .unwrap();
+        assert!(request.is_notification());
+    }
+
+    #[test]
+    fn test_tool_call_params_without_arguments() {
+        let params: ToolCallParams =
+            serde_json::from_value(serde_json::json!({"name": "some_tool"})).unwrap();
+        assert!(params.arguments.is_null());
+    }
+
+    #[test]
+    fn test_negotiate_exact_match() {
+        for version in ProtocolVersion::ALL {
+            assert_eq!(ProtocolVersion::negotiate(version.as_str()), version);
+        }
+    }
+
+    #[test]
+    fn test_negotiate_unknown_newer_version_picks_latest() {
+        assert_eq!(
+            ProtocolVersion::negotiate("2099-01-01"),
+            ProtocolVersion::LATEST
+        );
+    }
+
+    #[test]
+    fn test_negotiate_between_revisions_picks_older() {
+        assert_eq!(
+            ProtocolVersion::negotiate("2025-05-01"),
+            ProtocolVersion::V2025_03_26
+        );
+    }
+
+    #[test]
+    fn test_negotiate_too_old_offers_latest() {
+        assert_eq!(
+            ProtocolVersion::negotiate("2024-01-01"),
+            ProtocolVersion::LATEST
+        );
+    }
+
+    #[test]
+    fn test_initialize_result_uses_crate_version() {
+        let result = InitializeResult::new(ProtocolVersion::V2024_11_05);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["protocolVersion"], "2024-11-05");
+        assert_eq!(json["serverInfo"]["version"], env!("CARGO_PKG_VERSION"));
+        assert!(json["serverInfo"].get("title").is_none());
+    }
+
+    #[test]
+    fn test_initialize_result_advertises_logging() {
+        let json = serde_json::to_value(InitializeResult::new(ProtocolVersion::LATEST)).unwrap();
+        assert!(json["capabilities"]["logging"].is_object());
+    }
+
+    #[test]
+    fn test_completions_capability_depends_on_revision() {
+        let json =
+            serde_json::to_value(InitializeResult::new(ProtocolVersion::V2024_11_05)).unwrap();
+        assert!(json["capabilities"].get("completions").is_none());
+        let json =
+            serde_json::to_value(InitializeResult::new(ProtocolVersion::V2025_03_26)).unwrap();
+        assert!(json["capabilities"]["completions"].is_object());
+    }
+
+    #[test]
+    fn test_parse_complete_params() {
+        let params: CompleteParams = serde_json::from_value(serde_json::json!({
+            "ref": {"type": "ref/prompt", "name": "explore_app"},
+            "argument": {"name": "package", "value": "com."}
+        }))
+        .unwrap();
+        assert_eq!(
+            params.reference,
+            CompletionReference::Prompt {
+                name: "explore_app".to_string()
+            }
+        );
+        assert_eq!(params.argument.value, "com.");
+        assert!(params.context.arguments.is_empty());
+    }
+
+    #[test]
+    fn test_initialize_params_elicitation_capability() {
+        let params: InitializeParams = serde_json::from_value(serde_json::json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {"elicitation": {}}
+        }))
+        .unwrap();
+        assert!(params.capabilities.elicitation.is_some());
+        assert!(params.capabilities.roots.is_none());
+
+        let params: InitializeParams = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(params.capabilities.elicitation.is_none());
+    }
+
+    #[test]
+    fn test_initialize_result_title_on_newer_revision() {
+        let json =
+            serde_json::to_value(InitializeResult::new(ProtocolVersion::V2025_06_18)).unwrap();
+        assert_eq!(json["serverInfo"]["title"], "Mobile Device MCP");
+    }
+
+    #[test]
+    fn test_resource_not_found_carries_uri() {
+        let error = McpError::resource_not_found("device://gone/info");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], -32002);
+        assert_eq!(json["data"]["uri"], "device://gone/info");
+    }
+
+    #[test]
+    fn test_error_without_data_omits_field() {
+        let json = serde_json::to_value(McpError::method_not_found("foo")).unwrap();
+        assert!(json.get("data").is_none());
+    }
+
+    #[test]
+    fn test_server_notification_serialization() {
+        let json =
+            serde_json::to_value(McpNotification::resource_updated("device://list")).unwrap();
+        assert_eq!(json["method"], "notifications/resources/updated");
+        assert_eq!(json["params"]["uri"], "device://list");
+        assert!(json.get("id").is_none());
+
+        let json = serde_json::to_value(McpNotification::resource_list_changed()).unwrap();
+        assert!(json.get("params").is_none());
+    }
+
+    #[test]
+    fn test_tool_call_progress_token() {
+        let params: ToolCallParams = serde_json::from_value(serde_json::json!({
+            "name": "mobile_device_mcp_install_app",
+            "arguments": {},
+            "_meta": {"progressToken": 42}
+        }))
+        .unwrap();
+        assert_eq!(
+            params.meta.unwrap().progress_token,
+            Some(serde_json::json!(42))
+        );
     }
 }
@@ -0,0 +1,296 @@
+// mobile-mcp-zed-extension/src/mcp/roots.rs
+// Client Roots
+//
+// The server is started from wherever the editor happens to run it, so its
+// working directory says nothing about the user's project. Clients that
+// declare the `roots` capability answer "roots/list" with the workspace
+// folders instead. Paths given to tools are resolved against them:
+//
+// - Relative paths resolve against the first root
+// - Files may only be written inside one of the roots
+//
+// The list is fetched the first time a path needs it and kept until the
+// client sends "notifications/roots/list_changed". Without the capability
+// (or with an empty list) paths are used as given, as before.
+
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::elicitation::ClientRequests;
+use super::writer::MessageWriter;
+
+/// Roots of the session, None until fetched
+#[derive(Clone, Default)]
+pub struct RootsCache(Arc<Mutex<Option<Vec<PathBuf>>>>);
+
+impl RootsCache {
+    /// Forget the roots so the next path fetches them again
+    pub fn invalidate(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Vec<PathBuf>>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// What a tool does with a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathAccess {
+    Read,
+    Write,
+}
+
+/// Result of "roots/list"
+#[derive(Debug, Deserialize)]
+struct ListRootsResult {
+    roots: Vec<Root>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Root {
+    /// Always a file:// URI
+    uri: String,
+}
+
+/// Resolves tool paths against the client's roots for one request
+pub struct Roots {
+    writer: MessageWriter,
+    requests: ClientRequests,
+    cache: RootsCache,
+    /// The client declared the roots capability
+    supported: bool,
+}
+
+impl Roots {
+    /// Create a roots handle for one request
+    ///
+    /// # Arguments
+    /// * `writer` - Shared writer for outgoing messages
+    /// * `requests` - The session's pending client requests
+    /// * `cache` - The session's roots, shared by all its requests
+    /// * `supported` - The client declared `capabilities.roots`
+    pub fn new(
+        writer: MessageWriter,
+        requests: ClientRequests,
+        cache: RootsCache,
+        supported: bool,
+    ) -> Self {
+        Self {
+            writer,
+            requests,
+            cache,
+            supported,
+        }
+    }
+
+    /// Resolve a path given to a tool
+    ///
+    /// # Arguments
+    /// * `path` - Path as the model passed it
+    /// * `access` - Writes must stay inside the roots
+    ///
+    /// # Returns
+    /// The path to use, or why it can't be used
+    pub fn resolve(&self, path: &str, access: PathAccess) -> Result<PathBuf, String> {
+        let roots = self.list()?;
+        resolve_path(&roots, Path::new(path), access)
+    }
+
+    /// The client's roots (empty without the capability)
+    fn list(&self) -> Result<Vec<PathBuf>, String> {
+        if !self.supported {
+            return Ok(Vec::new());
+        }
+        if let Some(roots) = self.cache.lock().clone() {
+            return Ok(roots);
+        }
+
+        let result = self
+            .requests
+            .send(&self.writer, "roots/list", serde_json::json!({}))
+            .map_err(|e| format!("Could not get the workspace roots: {}", e))?;
+        let result: ListRootsResult = serde_json::from_value(result)
+            .map_err(|e| format!("Invalid roots/list result: {}", e))?;
+        let roots: Vec<PathBuf> = result
+            .roots
+            .iter()
+            .filter_map(|root| file_uri_to_path(&root.uri))
+            .collect();
+
+        *self.cache.lock() = Some(roots.clone());
+        Ok(roots)
+    }
+}
+
+/// Resolve a path against a list of roots
+///
+/// Relative paths are joined to the first root. Writes must end up inside a
+/// root; `..` can't be used to escape one. With no roots the path is
+/// returned as given.
+pub fn resolve_path(roots: &[PathBuf], path: &Path, access: PathAccess) -> Result<PathBuf, String> {
+    let Some(first) = roots.first() else {
+        return Ok(path.to_path_buf());
+    };
+
+    let path = normalize(&if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        first.join(path)
+    });
+
+    if access == PathAccess::Write && !roots.iter().any(|root| path.starts_with(normalize(root))) {
+        return Err(format!(
+            "Refusing to write {}: it is outside the workspace roots ({})",
+            path.display(),
+            roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(path)
+}
+
+/// Remove `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Path of a file:// URI, or None for other schemes
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skip the authority (usually empty or "localhost")
+    let path = &rest[rest.find('/')?..];
+    let path = percent_decode(path)?;
+
+    // file:///C:/Users/... on Windows
+    #[cfg(windows)]
+    let path = match path.strip_prefix('/') {
+        Some(stripped) if stripped.get(1..2) == Some(":") => stripped.to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Decode %XX escapes, or None if the result isn't UTF-8
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn roots() -> Vec<PathBuf> {
+        vec![PathBuf::from("/work/app"), PathBuf::from("/work/lib")]
+    }
+
+    #[test]
+    fn test_relative_paths_use_first_root() {
+        let path = resolve_path(&roots(), Path::new("shots/home.png"), PathAccess::Write);
+        assert_eq!(path, Ok(PathBuf::from("/work/app/shots/home.png")));
+    }
+
+    #[test]
+    fn test_writes_outside_roots_refused() {
+        assert!(resolve_path(&roots(), Path::new("/tmp/home.png"), PathAccess::Write).is_err());
+        assert!(resolve_path(&roots(), Path::new("../../etc/x.png"), PathAccess::Write).is_err());
+        assert!(resolve_path(&roots(), Path::new("/work/lib/x.png"), PathAccess::Write).is_ok());
+        // A sibling directory sharing the prefix is not inside the root
+        assert!(resolve_path(
+            &roots(),
+            Path::new("/work/application/x"),
+            PathAccess::Write
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_reads_outside_roots_allowed() {
+        let path = resolve_path(&roots(), Path::new("/tmp/app.apk"), PathAccess::Read);
+        assert_eq!(path, Ok(PathBuf::from("/tmp/app.apk")));
+    }
+
+    #[test]
+    fn test_no_roots_keeps_path() {
+        let path = resolve_path(&[], Path::new("shot.png"), PathAccess::Write);
+        assert_eq!(path, Ok(PathBuf::from("shot.png")));
+    }
+
+    #[test]
+    fn test_file_uri_to_path() {
+        assert_eq!(
+            file_uri_to_path("file:///home/me/My%20Project"),
+            Some(PathBuf::from("/home/me/My Project"))
+        );
+        assert_eq!(
+            file_uri_to_path("file://localhost/srv/app"),
+            Some(PathBuf::from("/srv/app"))
+        );
+        assert_eq!(file_uri_to_path("https://example.com/x"), None);
+    }
+
+    #[test]
+    fn test_roots_fetched_once_until_invalidated() {
+        let (writer, mut messages) = MessageWriter::channel();
+        let requests = ClientRequests::default();
+        let cache = RootsCache::default();
+        let roots = Roots::new(writer, requests.clone(), cache.clone(), true);
+
+        let client = std::thread::spawn(move || {
+            let request: serde_json::Value =
+                serde_json::from_str(&messages.blocking_recv().unwrap()).unwrap();
+            assert_eq!(request["method"], "roots/list");
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": {"roots": [{"uri": "file:///work/app", "name": "app"}]}
+            });
+            requests.handle_response(&response.to_string());
+            messages
+        });
+
+        let path = roots.resolve("a.png", PathAccess::Write);
+        assert_eq!(path, Ok(PathBuf::from("/work/app/a.png")));
+        let mut messages = client.join().unwrap();
+
+        // Served from the cache: nothing is sent
+        assert!(roots.resolve("b.png", PathAccess::Write).is_ok());
+        assert!(messages.try_recv().is_err());
+
+        cache.invalidate();
+        assert!(cache.lock().is_none());
+    }
+}
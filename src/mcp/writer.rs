@@ -0,0 +1,133 @@
+// mobile-mcp-zed-extension/src/mcp/writer.rs
+// Outgoing Message Writer
+//
+// Responses are no longer the only thing written to the client: background
+// work (like the resource watcher) emits notifications at any time. Every
+// message goes through a shared MessageWriter so each JSON line is written
+// and flushed atomically, never interleaved with another message.
+
+use serde::Serialize;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Cloneable handle writing newline-delimited JSON messages
+#[derive(Clone)]
+pub struct MessageWriter {
+    out: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl MessageWriter {
+    /// Create a writer for any output sink
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(Box::new(out))),
+        }
+    }
+
+    /// Create a writer for the process's stdout (the stdio transport)
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Create a writer that sends each message to a channel
+    ///
+    /// Used by transports that don't write to a byte stream (HTTP): every
+    /// message arrives on the receiver as one JSON string, without the newline.
+    pub fn channel() -> (Self, UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let writer = Self::new(LineSender {
+            sender,
+            line: Vec::new(),
+        });
+        (writer, receiver)
+    }
+
+    /// Serialize a message and write it as one line
+    ///
+    /// # Arguments
+    /// * `message` - Response, error response or notification
+    pub fn write_message<T: Serialize>(&self, message: &T) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        crate::recording::sent(&json);
+        // A thread that panicked mid-write must not silence the whole session
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(out, "{}", json)?;
+        out.flush()
+    }
+
+    /// Flush any buffered output
+    pub fn flush(&self) -> io::Result<()> {
+        self.out.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
+}
+
+/// Sink that sends every complete line to a channel
+struct LineSender {
+    sender: UnboundedSender<String>,
+    line: Vec<u8>,
+}
+
+impl Write for LineSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = String::from_utf8_lossy(&self.line).into_owned();
+            self.line.clear();
+            self.sender
+                .send(line)
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "receiver closed"))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sink that keeps everything written to it
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_messages_written_as_lines() {
+        let buffer = SharedBuffer::default();
+        let writer = MessageWriter::new(buffer.clone());
+        let clone = writer.clone();
+
+        writer.write_message(&serde_json::json!({"id": 1})).unwrap();
+        clone.write_message(&serde_json::json!({"id": 2})).unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "{\"id\":1}\n{\"id\":2}\n");
+    }
+
+    #[test]
+    fn test_channel_receives_messages() {
+        let (writer, mut receiver) = MessageWriter::channel();
+        writer.write_message(&serde_json::json!({"id": 1})).unwrap();
+        writer.write_message(&serde_json::json!({"id": 2})).unwrap();
+
+        assert_eq!(receiver.try_recv().unwrap(), "{\"id\":1}");
+        assert_eq!(receiver.try_recv().unwrap(), "{\"id\":2}");
+        assert!(receiver.try_recv().is_err());
+    }
+}
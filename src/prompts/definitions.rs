@@ -0,0 +1,153 @@
+// mobile-mcp-zed-extension/src/prompts/definitions.rs
+// MCP Prompt Definitions for Mobile Testing Workflows
+//
+// Prompts are parameterised instructions the user picks in the client
+// (e.g., as a slash command in Zed's assistant) instead of retyping them.
+// Each prompt is rendered with the current device list and hints on which
+// tools to use, so the model can start working right away.
+//
+// Available prompts:
+// - explore_app: Explore an app and report crashes
+// - reproduce_bug: Reproduce a bug from a list of steps
+// - verify_deep_link: Check that a deep link opens the right screen
+// - accessibility_review: Review the current screen for accessibility issues
+
+use serde_json::{json, Value};
+
+/// Represents an argument of an MCP prompt
+#[derive(Debug, Clone)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+impl PromptArgument {
+    /// Create a new prompt argument
+    pub fn new(name: &str, description: &str, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            required,
+        }
+    }
+
+    /// Convert prompt argument to MCP-compliant JSON format
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "required": self.required
+        })
+    }
+}
+
+/// Represents an MCP prompt template
+///
+/// The arguments are substituted when the prompt is requested with
+/// "prompts/get".
+#[derive(Debug, Clone)]
+pub struct PromptDefinition {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+impl PromptDefinition {
+    /// Create a new prompt definition
+    ///
+    /// # Arguments
+    /// * `name` - Unique prompt identifier (e.g., "explore_app")
+    /// * `description` - Human-readable description shown by the client
+    /// * `arguments` - Arguments the prompt template accepts
+    pub fn new(name: &str, description: &str, arguments: Vec<PromptArgument>) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            arguments,
+        }
+    }
+
+    /// Convert prompt definition to MCP-compliant JSON format
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "arguments": self.arguments.iter().map(|a| a.to_json()).collect::<Vec<_>>()
+        })
+    }
+}
+
+/// Optional device argument shared by every prompt
+fn device_argument() -> PromptArgument {
+    PromptArgument::new(
+        "device_id",
+        "Device to use (otherwise one of the connected devices is picked)",
+        false,
+    )
+}
+
+/// Returns all available prompts
+pub fn get_all_prompts() -> Vec<PromptDefinition> {
+    vec![
+        PromptDefinition::new(
+            "explore_app",
+            "Explore an app screen by screen and report crashes or broken flows",
+            vec![
+                PromptArgument::new(
+                    "package",
+                    "Package name or bundle ID (e.g., com.example.app)",
+                    true,
+                ),
+                device_argument(),
+            ],
+        ),
+        PromptDefinition::new(
+            "reproduce_bug",
+            "Reproduce a bug from a list of steps and report what happens",
+            vec![
+                PromptArgument::new("steps", "Steps to reproduce, one per line", true),
+                device_argument(),
+            ],
+        ),
+        PromptDefinition::new(
+            "verify_deep_link",
+            "Open a deep link and verify it lands on the expected screen",
+            vec![
+                PromptArgument::new("url", "Deep link or URL to open", true),
+                PromptArgument::new("expected", "What the target screen should show", false),
+                device_argument(),
+            ],
+        ),
+        PromptDefinition::new(
+            "accessibility_review",
+            "Review the current screen for accessibility issues",
+            vec![device_argument()],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_names_unique() {
+        let prompts = get_all_prompts();
+        let mut names: Vec<_> = prompts.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), prompts.len());
+    }
+
+    #[test]
+    fn test_prompt_json_format() {
+        let prompts = get_all_prompts();
+        let explore = prompts.iter().find(|p| p.name == "explore_app").unwrap();
+        let json = explore.to_json();
+        assert_eq!(json["arguments"][0]["name"], "package");
+        assert_eq!(json["arguments"][0]["required"], true);
+        assert_eq!(json["arguments"][1]["name"], "device_id");
+        assert_eq!(json["arguments"][1]["required"], false);
+    }
+}
@@ -0,0 +1,277 @@
+// mobile-mcp-zed-extension/src/prompts/handlers.rs
+// Prompt Rendering
+//
+// Renders the templates from definitions.rs into the single user message
+// returned by "prompts/get". Every prompt starts with the same context block
+// (connected devices and the device to use), followed by the workflow
+// instructions, and ends with general tool usage hints.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::definitions::get_all_prompts;
+use crate::types::DeviceInfo;
+use crate::MobileDeviceManager;
+
+/// Result type for prompt handlers
+///
+/// Success returns the "prompts/get" result. Errors are messages for an
+/// invalid params response (unknown prompt or missing argument).
+pub type PromptResult = Result<Value, String>;
+
+/// List all prompts
+pub fn handle_list_prompts() -> Value {
+    let prompts: Vec<_> = get_all_prompts().iter().map(|p| p.to_json()).collect();
+    serde_json::json!({ "prompts": prompts })
+}
+
+/// Render a prompt with the devices currently connected
+///
+/// # Arguments
+/// * `manager` - Mobile device manager (used to list devices)
+/// * `name` - Prompt name from prompts/list
+/// * `arguments` - Prompt arguments supplied by the client
+pub fn handle_get_prompt(
+    manager: &mut MobileDeviceManager,
+    name: &str,
+    arguments: &HashMap<String, String>,
+) -> PromptResult {
+    let devices = manager.list_all_devices("auto");
+    render_prompt(name, arguments, &devices)
+}
+
+/// Render a prompt for a given device list
+///
+/// # Returns
+/// The "prompts/get" result with a description and one user message
+pub fn render_prompt(
+    name: &str,
+    arguments: &HashMap<String, String>,
+    devices: &[DeviceInfo],
+) -> PromptResult {
+    let definition = get_all_prompts()
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Unknown prompt: {}", name))?;
+
+    for argument in definition.arguments.iter().filter(|a| a.required) {
+        if arguments
+            .get(&argument.name)
+            .is_none_or(|v| v.trim().is_empty())
+        {
+            return Err(format!("Missing required argument: {}", argument.name));
+        }
+    }
+
+    let arg = |key: &str| arguments.get(key).map(|v| v.trim()).unwrap_or_default();
+
+    let instructions = match name {
+        "explore_app" => format!(
+            "Explore the app `{package}` and report any crashes or broken flows.\n\
+             \n\
+             1. Launch it with mobile_device_mcp_launch_app (app_id: \"{package}\").\n\
+             2. On every screen, call mobile_device_mcp_list_elements_on_screen and \
+             interact with each button, tab and menu you haven't visited yet.\n\
+             3. After each action, check that the app is still in the foreground. If it \
+             closed or shows an \"app has stopped\" dialog, that is a crash: take a \
+             screenshot and note the steps that led to it.\n\
+             4. Use mobile_device_mcp_press_button (\"back\") to return from dead ends.\n\
+             \n\
+             Finish with a report listing the screens visited, every crash or error with \
+             its reproduction steps, and anything that looked broken.",
+            package = arg("package")
+        ),
+        "reproduce_bug" => format!(
+            "Try to reproduce this bug by following the steps exactly:\n\
+             \n\
+             {steps}\n\
+             \n\
+             Before each step, inspect the screen with \
+             mobile_device_mcp_list_elements_on_screen and tap elements by the center \
+             of their bounds. Take a screenshot after the final step.\n\
+             \n\
+             Report whether the bug reproduced, what you observed at each step and \
+             where the behavior diverged from the steps, if it did.",
+            steps = arg("steps")
+        ),
+        "verify_deep_link" => {
+            let expected = match arg("expected") {
+                "" => "Describe which screen it opened and whether that looks like \
+                       the intended destination."
+                    .to_string(),
+                expected => format!("The target screen should show: {}", expected),
+            };
+            format!(
+                "Verify that the deep link `{url}` opens the right screen.\n\
+                 \n\
+                 1. Open it with mobile_device_mcp_open_url (url: \"{url}\").\n\
+                 2. Wait for the screen to settle, then inspect it with \
+                 mobile_device_mcp_list_elements_on_screen and take a screenshot.\n\
+                 3. {expected}\n\
+                 \n\
+                 Report PASS or FAIL with the evidence. If a chooser dialog or the \
+                 browser opened instead of the app, that is a FAIL.",
+                url = arg("url"),
+                expected = expected
+            )
+        }
+        "accessibility_review" => "Review the current screen for accessibility issues.\n\
+             \n\
+             1. Call mobile_device_mcp_list_elements_on_screen and \
+             mobile_device_mcp_take_screenshot.\n\
+             2. Check every interactive element for a meaningful label, touch targets \
+             smaller than 48x48 dp (44x44 pt on iOS), text that is too small or low \
+             contrast, and content that only makes sense visually.\n\
+             \n\
+             Do not change the app state. Report each issue with the element, its \
+             bounds, the problem and a suggested fix, ordered by severity."
+            .to_string(),
+        _ => return Err(format!("Unknown prompt: {}", name)),
+    };
+
+    let text = format!(
+        "{}\n\n{}\n\n{}",
+        device_context(arg("device_id"), devices),
+        instructions,
+        TOOL_HINTS
+    );
+
+    Ok(serde_json::json!({
+        "description": definition.description,
+        "messages": [{
+            "role": "user",
+            "content": {
+                "type": "text",
+                "text": text
+            }
+        }]
+    }))
+}
+
+/// Hints appended to every prompt
+const TOOL_HINTS: &str = "Tool usage hints:\n\
+- Pass device_id to device tools (or select one with mobile_device_mcp_select_device); \
+the platform is detected from the device.\n\
+- Coordinates are in device pixels; use mobile_device_mcp_get_screen_size when unsure.\n\
+- Prefer mobile_device_mcp_list_elements_on_screen over screenshots to find elements; \
+use screenshots to confirm what the user would see.\n\
+- If a tool result has isError set, read the message and adapt instead of repeating the call.";
+
+/// Describe the target device and everything connected
+fn device_context(device_id: &str, devices: &[DeviceInfo]) -> String {
+    let device_list = if devices.is_empty() {
+        "No devices are connected. Ask the user to start an emulator or simulator, \
+         or to connect a device, before continuing."
+            .to_string()
+    } else {
+        devices
+            .iter()
+            .map(|d| {
+                format!(
+                    "- {} ({}) - {} {} [{}]",
+                    d.name, d.id, d.platform, d.device_type, d.state
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let use_device = |device: &DeviceInfo| {
+        format!(
+            "Use the device {} (device_id: \"{}\", platform: \"{}\").",
+            device.name, device.id, device.platform
+        )
+    };
+    let target = match (device_id, devices) {
+        ("", []) => return format!("Connected devices:\n{}", device_list),
+        ("", [device]) => use_device(device),
+        ("", _) => "Pick one of the connected devices.".to_string(),
+        (id, _) => match devices.iter().find(|d| d.id == id) {
+            Some(device) => use_device(device),
+            None => format!("Use the device \"{}\" (it is not connected right now).", id),
+        },
+    };
+
+    format!("Connected devices:\n{}\n\n{}", device_list, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str, platform: &str) -> DeviceInfo {
+        DeviceInfo {
+            id: id.to_string(),
+            name: "Test Device".to_string(),
+            platform: platform.to_string(),
+            device_type: "emulator".to_string(),
+            state: "online".to_string(),
+        }
+    }
+
+    fn arguments(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn message_text(result: &Value) -> String {
+        result["messages"][0]["content"]["text"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_every_prompt_renders() {
+        let args = arguments(&[
+            ("package", "com.example.app"),
+            ("steps", "1. Open settings"),
+            ("url", "example://home"),
+        ]);
+        let devices = [device("emulator-5554", "android")];
+        for prompt in get_all_prompts() {
+            let result = render_prompt(&prompt.name, &args, &devices).unwrap();
+            assert_eq!(result["messages"][0]["role"], "user");
+            assert!(message_text(&result).contains("emulator-5554"));
+            assert!(message_text(&result).contains("Tool usage hints"));
+        }
+    }
+
+    #[test]
+    fn test_arguments_substituted() {
+        let result = render_prompt(
+            "explore_app",
+            &arguments(&[("package", "com.example.app")]),
+            &[],
+        )
+        .unwrap();
+        let text = message_text(&result);
+        assert!(text.contains("`com.example.app`"));
+        assert!(text.contains("No devices are connected"));
+    }
+
+    #[test]
+    fn test_single_device_is_targeted() {
+        let result = render_prompt(
+            "accessibility_review",
+            &HashMap::new(),
+            &[device("ABC-123", "ios")],
+        )
+        .unwrap();
+        assert!(message_text(&result).contains("device_id: \"ABC-123\", platform: \"ios\""));
+    }
+
+    #[test]
+    fn test_missing_required_argument() {
+        let error = render_prompt("verify_deep_link", &HashMap::new(), &[]).unwrap_err();
+        assert_eq!(error, "Missing required argument: url");
+    }
+
+    #[test]
+    fn test_unknown_prompt() {
+        assert!(render_prompt("nope", &HashMap::new(), &[]).is_err());
+    }
+}
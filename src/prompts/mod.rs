@@ -0,0 +1,8 @@
+// mobile-mcp-zed-extension/src/prompts/mod.rs
+// Prompts module with built-in mobile testing workflows
+
+pub mod definitions;
+pub mod handlers;
+
+pub use definitions::{get_all_prompts, PromptDefinition};
+pub use handlers::*;
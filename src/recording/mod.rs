@@ -0,0 +1,438 @@
+// mobile-mcp-zed-extension/src/recording/mod.rs
+// Session Recording and Replay
+//
+// With --record <file.jsonl> the server writes a transcript (see
+// transcript.rs) of every MCP message it receives and sends, and of every
+// raw device call behind them: adb shell output, adb's device list, file
+// pushes and xcrun/libimobiledevice processes. Attach it to a bug report to
+// show exactly what the devices answered.
+//
+// With --replay <file.jsonl> the device calls are served from a transcript
+// instead of touching devices, so a session can be reproduced offline: the
+// same requests produce the same responses. Calls are matched by device and
+// command; repeated calls get the recorded results in order, and once those
+// run out the last one again. A call that was never recorded fails.
+//
+// Background polling (the resource watcher) is left out of transcripts and
+// doesn't run while replaying, so it can't take the results recorded for
+// the session's requests.
+//
+// The mode is process-wide and chosen once at startup. Device code reaches it
+// through the wrapper functions below, which run the real call only when not
+// replaying.
+
+pub mod transcript;
+
+use serde_json::Value;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::process::Output;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use transcript::{CommandOutput, Data, Entry, Event, Outcome};
+
+/// What happens to device calls
+enum Mode {
+    Record(Recorder),
+    Replay(Replay),
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+thread_local! {
+    /// Whether this thread's device calls are left out of the transcript
+    static UNRECORDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Record this process's session to a transcript file
+pub fn record_to(path: &Path) -> io::Result<()> {
+    set_mode(Mode::Record(Recorder::create(path)?))
+}
+
+/// Serve device calls from a transcript file
+pub fn replay_from(path: &Path) -> io::Result<()> {
+    set_mode(Mode::Replay(Replay::load(path)?))
+}
+
+fn set_mode(mode: Mode) -> io::Result<()> {
+    MODE.set(mode)
+        .map_err(|_| io::Error::other("Recording or replay already started"))
+}
+
+/// Whether device calls are served from a transcript
+pub fn is_replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay(_)))
+}
+
+/// Leave the device calls of the current thread out of the transcript
+pub fn skip_thread() {
+    UNRECORDED.with(|unrecorded| unrecorded.set(true));
+}
+
+/// Record a line received from the client
+pub fn received(line: &str) {
+    if let Some(Mode::Record(recorder)) = MODE.get() {
+        recorder.record(Event::Received {
+            message: parse_message(line),
+        });
+    }
+}
+
+/// Record a message sent to the client
+pub fn sent(json: &str) {
+    if let Some(Mode::Record(recorder)) = MODE.get() {
+        recorder.record(Event::Sent {
+            message: parse_message(json),
+        });
+    }
+}
+
+/// List the connected Android devices
+///
+/// # Arguments
+/// * `list` - Asks adb for the ids of devices in the "device" state
+pub fn adb_devices(
+    list: impl FnOnce() -> Result<Vec<String>, String>,
+) -> Result<Vec<String>, String> {
+    device_call(
+        &transcript::adb_devices_key(),
+        list,
+        |result| Event::AdbDevices {
+            result: Outcome::from_result(result.clone()),
+        },
+        |event| match event? {
+            Event::AdbDevices { result } => result.into_result(),
+            _ => Err(mismatched_entry()),
+        },
+    )
+}
+
+/// Run an adb shell command
+///
+/// # Arguments
+/// * `device_id` - Device the command runs on
+/// * `args` - The command (without the PID wrapper)
+/// * `run` - Runs it on the device
+pub fn adb_shell(
+    device_id: &str,
+    args: &[&str],
+    run: impl FnOnce() -> Result<Vec<u8>, String>,
+) -> Result<Vec<u8>, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    device_call(
+        &transcript::adb_shell_key(device_id, &args),
+        run,
+        |result| Event::AdbShell {
+            device_id: device_id.to_string(),
+            args: args.clone(),
+            result: Outcome::from_result(result.as_deref().map(Data::new).map_err(Clone::clone)),
+        },
+        |event| match event? {
+            Event::AdbShell { result, .. } => result.into_result()?.into_bytes(),
+            _ => Err(mismatched_entry()),
+        },
+    )
+}
+
+/// Push a file to a device
+///
+/// # Arguments
+/// * `device_id` - Device to push to
+/// * `remote_path` - Destination on the device
+/// * `push` - Pushes the file
+pub fn adb_push(
+    device_id: &str,
+    remote_path: &str,
+    push: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    device_call(
+        &transcript::adb_push_key(device_id, remote_path),
+        push,
+        |result| Event::AdbPush {
+            device_id: device_id.to_string(),
+            remote_path: remote_path.to_string(),
+            result: Outcome::from_result(result.clone()),
+        },
+        |event| match event? {
+            Event::AdbPush { result, .. } => result.into_result(),
+            _ => Err(mismatched_entry()),
+        },
+    )
+}
+
+/// Run a child process to completion
+///
+/// # Arguments
+/// * `program` - Program name (e.g., "xcrun")
+/// * `args` - Its arguments
+/// * `run` - Runs the process
+pub fn command(
+    program: &str,
+    args: Vec<String>,
+    run: impl FnOnce() -> io::Result<Output>,
+) -> io::Result<Output> {
+    device_call(
+        &transcript::command_key(program, &args),
+        run,
+        |result| Event::Command {
+            program: program.to_string(),
+            args: args.clone(),
+            result: Outcome::from_result(
+                result
+                    .as_ref()
+                    .map(|output| CommandOutput {
+                        status: output.status.code(),
+                        stdout: Data::new(&output.stdout),
+                        stderr: Data::new(&output.stderr),
+                    })
+                    .map_err(|e| e.to_string()),
+            ),
+        },
+        |event| {
+            let event = event.map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
+            let Event::Command { result, .. } = event else {
+                return Err(io::Error::other(mismatched_entry()));
+            };
+            let output = result.into_result().map_err(io::Error::other)?;
+            Ok(Output {
+                status: exit_status(output.status),
+                stdout: output.stdout.into_bytes().map_err(io::Error::other)?,
+                stderr: output.stderr.into_bytes().map_err(io::Error::other)?,
+            })
+        },
+    )
+}
+
+/// Run, record or replay one device call
+///
+/// # Arguments
+/// * `key` - Identifies the call in the transcript
+/// * `run` - Makes the real call
+/// * `to_event` - Describes the real call's result for the transcript
+/// * `from_event` - Rebuilds the result from the recorded event (or the
+///   error that there is none)
+fn device_call<T>(
+    key: &str,
+    run: impl FnOnce() -> T,
+    to_event: impl FnOnce(&T) -> Event,
+    from_event: impl FnOnce(Result<Event, String>) -> T,
+) -> T {
+    match MODE.get() {
+        None => run(),
+        Some(Mode::Record(_)) if UNRECORDED.with(Cell::get) => run(),
+        Some(Mode::Record(recorder)) => {
+            let started = Instant::now();
+            let result = run();
+            recorder.record_call(to_event(&result), started);
+            result
+        }
+        Some(Mode::Replay(replay)) => from_event(
+            replay
+                .next(key)
+                .ok_or_else(|| format!("No recorded result for `{}`", key)),
+        ),
+    }
+}
+
+fn mismatched_entry() -> String {
+    "Transcript entry doesn't match the call".to_string()
+}
+
+#[cfg(unix)]
+fn exit_status(code: Option<i32>) -> std::process::ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    // Killed by a signal: report SIGKILL
+    std::process::ExitStatus::from_raw(code.map(|code| code << 8).unwrap_or(9))
+}
+
+#[cfg(windows)]
+fn exit_status(code: Option<i32>) -> std::process::ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(code.unwrap_or(1) as u32)
+}
+
+/// Messages are stored as JSON; anything unparsable is kept as a string
+fn parse_message(line: &str) -> Value {
+    serde_json::from_str(line).unwrap_or_else(|_| Value::String(line.to_string()))
+}
+
+/// Appends entries to a transcript file
+struct Recorder {
+    started: Instant,
+    file: Mutex<LineWriter<File>>,
+}
+
+impl Recorder {
+    fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            started: Instant::now(),
+            file: Mutex::new(LineWriter::new(File::create(path)?)),
+        })
+    }
+
+    fn record(&self, event: Event) {
+        self.write(Entry {
+            time_ms: self.started.elapsed().as_millis() as u64,
+            duration_ms: None,
+            event,
+        });
+    }
+
+    /// Record a device call that started at `started`
+    fn record_call(&self, event: Event, started: Instant) {
+        self.write(Entry {
+            time_ms: started.duration_since(self.started).as_millis() as u64,
+            duration_ms: Some(started.elapsed().as_millis() as u64),
+            event,
+        });
+    }
+
+    fn write(&self, entry: Entry) {
+        let Ok(line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        // A full disk must not take the session down with it
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Recorded device call results, by call
+struct Replay {
+    calls: Mutex<HashMap<String, VecDeque<Event>>>,
+}
+
+impl Replay {
+    fn load(path: &Path) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    fn read(input: impl BufRead) -> io::Result<Self> {
+        let mut calls: HashMap<String, VecDeque<Event>> = HashMap::new();
+        for (number, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Transcript line {}: {}", number + 1, e),
+                )
+            })?;
+            if let Some(key) = entry.event.replay_key() {
+                calls.entry(key).or_default().push_back(entry.event);
+            }
+        }
+        Ok(Self {
+            calls: Mutex::new(calls),
+        })
+    }
+
+    /// The next recorded result of a call (the last one repeats)
+    fn next(&self, key: &str) -> Option<Event> {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        let results = calls.get_mut(key)?;
+        if results.len() > 1 {
+            results.pop_front()
+        } else {
+            results.front().cloned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell_entry(args: &[&str], output: &str) -> String {
+        let entry = Entry {
+            time_ms: 0,
+            duration_ms: Some(5),
+            event: Event::AdbShell {
+                device_id: "emulator-5554".to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+                result: Outcome::Output(Data::new(output.as_bytes())),
+            },
+        };
+        serde_json::to_string(&entry).unwrap()
+    }
+
+    fn output_of(event: Option<Event>) -> Data {
+        match event {
+            Some(Event::AdbShell {
+                result: Outcome::Output(data),
+                ..
+            }) => data,
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_replay_in_order_then_repeat_last() {
+        let transcript = [
+            r#"{"time_ms":0,"kind":"received","message":{"jsonrpc":"2.0","id":1,"method":"ping"}}"#
+                .to_string(),
+            shell_entry(&["settings", "get", "system", "user_rotation"], "0"),
+            shell_entry(&["settings", "get", "system", "user_rotation"], "1"),
+        ]
+        .join("\n");
+        let replay = Replay::read(transcript.as_bytes()).unwrap();
+
+        let key = transcript::adb_shell_key(
+            "emulator-5554",
+            &["settings", "get", "system", "user_rotation"].map(String::from),
+        );
+        assert_eq!(output_of(replay.next(&key)), Data::new(b"0"));
+        assert_eq!(output_of(replay.next(&key)), Data::new(b"1"));
+        assert_eq!(output_of(replay.next(&key)), Data::new(b"1"));
+        assert!(replay.next("adb -s emulator-5554 shell wm size").is_none());
+    }
+
+    #[test]
+    fn test_invalid_transcript_line_is_reported() {
+        let error = Replay::read("{}\n".as_bytes()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn test_recorded_entries_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "mobile-device-mcp-recording-{}.jsonl",
+            std::process::id()
+        ));
+        let recorder = Recorder::create(&path).unwrap();
+        recorder.record(Event::Sent {
+            message: parse_message(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#),
+        });
+        recorder.record_call(
+            Event::AdbDevices {
+                result: Outcome::Output(vec!["emulator-5554".to_string()]),
+            },
+            Instant::now(),
+        );
+        drop(recorder);
+
+        let replay = Replay::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            replay.next(&transcript::adb_devices_key()),
+            Some(Event::AdbDevices {
+                result: Outcome::Output(vec!["emulator-5554".to_string()]),
+            })
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_status_round_trip() {
+        assert_eq!(exit_status(Some(0)).code(), Some(0));
+        assert_eq!(exit_status(Some(3)).code(), Some(3));
+        assert_eq!(exit_status(None).code(), None);
+    }
+}
@@ -0,0 +1,246 @@
+// mobile-mcp-zed-extension/src/recording/transcript.rs
+// Transcript Format
+//
+// A transcript is a JSONL file, one entry per line, in the order things
+// happened. Every entry has the milliseconds since recording started and a
+// `kind`:
+//
+//   received / sent - MCP messages from and to the client
+//   adb_devices     - Android devices adb reported as connected
+//   adb_shell       - An adb shell command on a device and its output
+//   adb_push        - A file pushed to a device
+//   command         - A child process (xcrun, idevicescreenshot) and its output
+//
+// Device entries also carry how long the call took. Outputs that aren't
+// UTF-8 (screenshots) are stored as base64.
+//
+// # Example
+// ```json
+// {"time_ms":0,"kind":"received","message":{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{...}}}
+// {"time_ms":3,"duration_ms":41,"kind":"adb_shell","device_id":"emulator-5554","args":["wm","size"],"output":{"text":"Physical size: 1080x2400\n"}}
+// {"time_ms":45,"kind":"sent","message":{"jsonrpc":"2.0","id":1,"result":{...}}}
+// ```
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One line of a transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Milliseconds since recording started
+    pub time_ms: u64,
+    /// How long a device call took
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// What happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Event {
+    /// Message from the client
+    Received { message: Value },
+    /// Message to the client
+    Sent { message: Value },
+    /// Ids of the Android devices in the "device" state
+    AdbDevices {
+        #[serde(flatten)]
+        result: Outcome<Vec<String>>,
+    },
+    /// `adb -s <device_id> shell <args>`
+    AdbShell {
+        device_id: String,
+        args: Vec<String>,
+        #[serde(flatten)]
+        result: Outcome<Data>,
+    },
+    /// `adb -s <device_id> push <local> <remote>`
+    AdbPush {
+        device_id: String,
+        remote_path: String,
+        #[serde(flatten)]
+        result: Outcome<()>,
+    },
+    /// A child process run to completion
+    Command {
+        program: String,
+        args: Vec<String>,
+        #[serde(flatten)]
+        result: Outcome<CommandOutput>,
+    },
+}
+
+impl Event {
+    /// Key matching a device call to its recorded result, None for messages
+    ///
+    /// Calls with the same key are replayed in the order they were recorded.
+    pub fn replay_key(&self) -> Option<String> {
+        match self {
+            Event::Received { .. } | Event::Sent { .. } => None,
+            Event::AdbDevices { .. } => Some(adb_devices_key()),
+            Event::AdbShell {
+                device_id, args, ..
+            } => Some(adb_shell_key(device_id, args)),
+            Event::AdbPush {
+                device_id,
+                remote_path,
+                ..
+            } => Some(adb_push_key(device_id, remote_path)),
+            Event::Command { program, args, .. } => Some(command_key(program, args)),
+        }
+    }
+}
+
+pub fn adb_devices_key() -> String {
+    "adb devices".to_string()
+}
+
+pub fn adb_shell_key(device_id: &str, args: &[String]) -> String {
+    format!("adb -s {} shell {}", device_id, args.join(" "))
+}
+
+pub fn adb_push_key(device_id: &str, remote_path: &str) -> String {
+    format!("adb -s {} push {}", device_id, remote_path)
+}
+
+pub fn command_key(program: &str, args: &[String]) -> String {
+    format!("{} {}", program, args.join(" "))
+}
+
+/// Result of a device call: `{"output": ...}` or `{"error": "..."}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome<T> {
+    Output(T),
+    Error(String),
+}
+
+impl<T> Outcome<T> {
+    pub fn from_result(result: Result<T, String>) -> Self {
+        match result {
+            Ok(output) => Outcome::Output(output),
+            Err(e) => Outcome::Error(e),
+        }
+    }
+
+    pub fn into_result(self) -> Result<T, String> {
+        match self {
+            Outcome::Output(output) => Ok(output),
+            Outcome::Error(e) => Err(e),
+        }
+    }
+}
+
+/// Raw output bytes: `{"text": "..."}` if UTF-8, `{"base64": "..."}` otherwise
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Data {
+    Text(String),
+    Base64(String),
+}
+
+impl Data {
+    pub fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Data::Text(text.to_string()),
+            Err(_) => Data::Base64(STANDARD.encode(bytes)),
+        }
+    }
+
+    pub fn into_bytes(self) -> Result<Vec<u8>, String> {
+        match self {
+            Data::Text(text) => Ok(text.into_bytes()),
+            Data::Base64(data) => STANDARD
+                .decode(data)
+                .map_err(|e| format!("Invalid base64 in transcript: {}", e)),
+        }
+    }
+}
+
+/// Exit status and output of a child process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandOutput {
+    /// Exit code, None if the process was killed by a signal
+    pub status: Option<i32>,
+    pub stdout: Data,
+    pub stderr: Data,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_data_keeps_text_readable() {
+        assert_eq!(Data::new(b"Success\n"), Data::Text("Success\n".to_string()));
+        let png = [0x89, b'P', b'N', b'G', 0xff];
+        let data = Data::new(&png);
+        assert!(matches!(data, Data::Base64(_)));
+        assert_eq!(data.into_bytes().unwrap(), png);
+    }
+
+    #[test]
+    fn test_entry_format() {
+        let entry = Entry {
+            time_ms: 3,
+            duration_ms: Some(41),
+            event: Event::AdbShell {
+                device_id: "emulator-5554".to_string(),
+                args: vec!["wm".to_string(), "size".to_string()],
+                result: Outcome::Output(Data::new(b"Physical size: 1080x2400\n")),
+            },
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "time_ms": 3,
+                "duration_ms": 41,
+                "kind": "adb_shell",
+                "device_id": "emulator-5554",
+                "args": ["wm", "size"],
+                "output": {"text": "Physical size: 1080x2400\n"}
+            })
+        );
+        assert_eq!(serde_json::from_value::<Entry>(json).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_error_and_message_entries_round_trip() {
+        for event in [
+            Event::AdbDevices {
+                result: Outcome::Error("adb server not running".to_string()),
+            },
+            Event::Received {
+                message: json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+            },
+        ] {
+            let entry = Entry {
+                time_ms: 0,
+                duration_ms: None,
+                event,
+            };
+            let line = serde_json::to_string(&entry).unwrap();
+            assert_eq!(serde_json::from_str::<Entry>(&line).unwrap(), entry);
+        }
+    }
+
+    #[test]
+    fn test_messages_have_no_replay_key() {
+        let event = Event::Sent { message: json!({}) };
+        assert!(event.replay_key().is_none());
+        let event = Event::AdbPush {
+            device_id: "emulator-5554".to_string(),
+            remote_path: "/data/local/tmp/app.apk".to_string(),
+            result: Outcome::Output(()),
+        };
+        assert_eq!(
+            event.replay_key().as_deref(),
+            Some("adb -s emulator-5554 push /data/local/tmp/app.apk")
+        );
+    }
+}
@@ -0,0 +1,303 @@
+// mobile-mcp-zed-extension/src/resources/definitions.rs
+// MCP Resource Definitions for Mobile Device State
+//
+// Resources expose live device state that a client can attach as context
+// without spending a tool call. Every resource is addressed by a URI:
+//
+// - device://list                      - All connected devices (JSON)
+// - device://{id}/info                 - Device details, screen size, orientation (JSON)
+// - device://{id}/screenshot.png       - Current screen (PNG blob)
+// - device://{id}/hierarchy.json       - On-screen UI elements (JSON)
+//
+// The per-device URIs are also advertised as resource templates so clients
+// can build them for devices that appear after resources/list was called.
+
+use serde_json::{json, Value};
+
+use crate::types::DeviceInfo;
+
+/// URI scheme shared by all device resources
+const SCHEME: &str = "device://";
+
+/// Represents a concrete MCP resource
+///
+/// Returned by "resources/list". The uri can be passed straight to
+/// "resources/read".
+#[derive(Debug, Clone)]
+pub struct ResourceDefinition {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: String,
+}
+
+impl ResourceDefinition {
+    /// Create a new resource definition
+    ///
+    /// # Arguments
+    /// * `uri` - Resource URI (e.g., "device://emulator-5554/info")
+    /// * `name` - Human-readable name shown by the client
+    /// * `description` - What the resource contains
+    /// * `mime_type` - Content type returned by "resources/read"
+    pub fn new(uri: &str, name: &str, description: &str, mime_type: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            mime_type: mime_type.to_string(),
+        }
+    }
+
+    /// Convert resource definition to MCP-compliant JSON format
+    pub fn to_json(&self) -> Value {
+        json!({
+            "uri": self.uri,
+            "name": self.name,
+            "description": self.description,
+            "mimeType": self.mime_type
+        })
+    }
+}
+
+/// Represents an MCP resource template
+///
+/// Returned by "resources/templates/list". The uri_template follows
+/// RFC 6570 and is expanded by the client with a device id.
+#[derive(Debug, Clone)]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    pub description: String,
+    pub mime_type: String,
+}
+
+impl ResourceTemplate {
+    /// Create a new resource template
+    pub fn new(uri_template: &str, name: &str, description: &str, mime_type: &str) -> Self {
+        Self {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            mime_type: mime_type.to_string(),
+        }
+    }
+
+    /// Convert resource template to MCP-compliant JSON format
+    pub fn to_json(&self) -> Value {
+        json!({
+            "uriTemplate": self.uri_template,
+            "name": self.name,
+            "description": self.description,
+            "mimeType": self.mime_type
+        })
+    }
+}
+
+/// A parsed device resource URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceResource {
+    /// device://list
+    List,
+    /// device://{id}/info
+    Info(String),
+    /// device://{id}/screenshot.png
+    Screenshot(String),
+    /// device://{id}/hierarchy.json
+    Hierarchy(String),
+}
+
+impl DeviceResource {
+    /// Parse a resource URI
+    ///
+    /// # Returns
+    /// The resource the URI points to, or None if it isn't a device resource
+    ///
+    /// # Example
+    /// ```rust
+    /// let resource = DeviceResource::parse("device://emulator-5554/info");
+    /// assert_eq!(resource, Some(DeviceResource::Info("emulator-5554".to_string())));
+    /// ```
+    pub fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix(SCHEME)?;
+        if path == "list" {
+            return Some(DeviceResource::List);
+        }
+
+        // Device ids never contain '/', so the last segment names the resource
+        let (device_id, resource) = path.rsplit_once('/')?;
+        if device_id.is_empty() || device_id.contains('/') {
+            return None;
+        }
+        let device_id = device_id.to_string();
+
+        match resource {
+            "info" => Some(DeviceResource::Info(device_id)),
+            "screenshot.png" => Some(DeviceResource::Screenshot(device_id)),
+            "hierarchy.json" => Some(DeviceResource::Hierarchy(device_id)),
+            _ => None,
+        }
+    }
+
+    /// Build the URI for this resource
+    pub fn uri(&self) -> String {
+        match self {
+            DeviceResource::List => format!("{}list", SCHEME),
+            DeviceResource::Info(id) => format!("{}{}/info", SCHEME, id),
+            DeviceResource::Screenshot(id) => format!("{}{}/screenshot.png", SCHEME, id),
+            DeviceResource::Hierarchy(id) => format!("{}{}/hierarchy.json", SCHEME, id),
+        }
+    }
+
+    /// Device the resource belongs to (None for the device list)
+    pub fn device_id(&self) -> Option<&str> {
+        match self {
+            DeviceResource::List => None,
+            DeviceResource::Info(id)
+            | DeviceResource::Screenshot(id)
+            | DeviceResource::Hierarchy(id) => Some(id),
+        }
+    }
+}
+
+/// Returns the device list resource
+///
+/// This resource is always present, even when no device is connected.
+pub fn device_list_resource() -> ResourceDefinition {
+    ResourceDefinition::new(
+        &DeviceResource::List.uri(),
+        "Connected devices",
+        "All connected Android devices and iOS simulators with their state",
+        "application/json",
+    )
+}
+
+/// Returns the concrete resources for one connected device
+///
+/// # Arguments
+/// * `device` - Device discovered by list_all_devices
+pub fn device_resources(device: &DeviceInfo) -> Vec<ResourceDefinition> {
+    vec![
+        ResourceDefinition::new(
+            &DeviceResource::Info(device.id.clone()).uri(),
+            &format!("{} info", device.name),
+            &format!(
+                "Details, screen size and orientation of {} ({})",
+                device.name, device.platform
+            ),
+            "application/json",
+        ),
+        ResourceDefinition::new(
+            &DeviceResource::Screenshot(device.id.clone()).uri(),
+            &format!("{} screenshot", device.name),
+            &format!("Current screen of {} as PNG", device.name),
+            "image/png",
+        ),
+        ResourceDefinition::new(
+            &DeviceResource::Hierarchy(device.id.clone()).uri(),
+            &format!("{} UI hierarchy", device.name),
+            &format!(
+                "Elements currently on screen of {} with labels and bounds",
+                device.name
+            ),
+            "application/json",
+        ),
+    ]
+}
+
+/// Returns the templates for per-device resources
+pub fn get_resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate::new(
+            "device://{device_id}/info",
+            "Device info",
+            "Details, screen size and orientation of a device",
+            "application/json",
+        ),
+        ResourceTemplate::new(
+            "device://{device_id}/screenshot.png",
+            "Device screenshot",
+            "Current screen of a device as PNG",
+            "image/png",
+        ),
+        ResourceTemplate::new(
+            "device://{device_id}/hierarchy.json",
+            "Device UI hierarchy",
+            "Elements currently on screen of a device with labels and bounds",
+            "application/json",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_device() -> DeviceInfo {
+        DeviceInfo {
+            id: "emulator-5554".to_string(),
+            name: "Pixel 6".to_string(),
+            platform: "android".to_string(),
+            device_type: "emulator".to_string(),
+            state: "online".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_device_uris() {
+        assert_eq!(
+            DeviceResource::parse("device://list"),
+            Some(DeviceResource::List)
+        );
+        assert_eq!(
+            DeviceResource::parse("device://emulator-5554/info"),
+            Some(DeviceResource::Info("emulator-5554".to_string()))
+        );
+        assert_eq!(
+            DeviceResource::parse("device://ABC-123/screenshot.png"),
+            Some(DeviceResource::Screenshot("ABC-123".to_string()))
+        );
+        assert_eq!(
+            DeviceResource::parse("device://emulator-5554/hierarchy.json"),
+            Some(DeviceResource::Hierarchy("emulator-5554".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_uris() {
+        assert_eq!(DeviceResource::parse("file:///tmp/x.png"), None);
+        assert_eq!(DeviceResource::parse("device://emulator-5554/logs"), None);
+        assert_eq!(DeviceResource::parse("device:///info"), None);
+        assert_eq!(DeviceResource::parse("device://a/b/info"), None);
+    }
+
+    #[test]
+    fn test_uri_round_trip() {
+        for resource in [
+            DeviceResource::List,
+            DeviceResource::Info("emulator-5554".to_string()),
+            DeviceResource::Screenshot("emulator-5554".to_string()),
+            DeviceResource::Hierarchy("emulator-5554".to_string()),
+        ] {
+            assert_eq!(DeviceResource::parse(&resource.uri()), Some(resource));
+        }
+    }
+
+    #[test]
+    fn test_device_resources_are_readable() {
+        let resources = device_resources(&sample_device());
+        assert_eq!(resources.len(), 3);
+        for resource in resources {
+            let parsed = DeviceResource::parse(&resource.uri).unwrap();
+            assert_eq!(parsed.device_id(), Some("emulator-5554"));
+        }
+    }
+
+    #[test]
+    fn test_templates_json_format() {
+        let templates = get_resource_templates();
+        assert_eq!(templates.len(), 3);
+        let json = templates[1].to_json();
+        assert_eq!(json["uriTemplate"], "device://{device_id}/screenshot.png");
+        assert_eq!(json["mimeType"], "image/png");
+    }
+}
@@ -0,0 +1,135 @@
+// mobile-mcp-zed-extension/src/resources/handlers.rs
+// Resource Handler Implementations
+//
+// Implements "resources/list", "resources/templates/list" and
+// "resources/read" on top of MobileDeviceManager. Device resources are
+// resolved against the live device list, so a resource for a device that was
+// unplugged is reported as not found rather than as a device failure.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+use super::definitions::{
+    device_list_resource, device_resources, get_resource_templates, DeviceResource,
+};
+use crate::devices::no_progress;
+use crate::MobileDeviceManager;
+
+/// Why a resource read failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceError {
+    /// The URI doesn't name a known resource (-32002)
+    NotFound(String),
+    /// The device could not produce the resource (-32603)
+    Read(String),
+}
+
+/// Result type for resource handlers
+///
+/// Success returns the "resources/read" result with a contents array.
+pub type ResourceResult = Result<Value, ResourceError>;
+
+/// List the device list plus every resource of each connected device
+///
+/// # Example Response
+/// ```json
+/// {"resources": [{"uri": "device://list", "name": "Connected devices", ...}]}
+/// ```
+pub fn handle_list_resources(manager: &mut MobileDeviceManager) -> Value {
+    let mut resources = vec![device_list_resource().to_json()];
+    for device in manager.list_all_devices("auto") {
+        resources.extend(device_resources(&device).iter().map(|r| r.to_json()));
+    }
+
+    serde_json::json!({ "resources": resources })
+}
+
+/// List the per-device resource templates
+pub fn handle_list_resource_templates() -> Value {
+    let templates: Vec<_> = get_resource_templates()
+        .iter()
+        .map(|t| t.to_json())
+        .collect();
+
+    serde_json::json!({ "resourceTemplates": templates })
+}
+
+/// Read a device resource
+///
+/// # Arguments
+/// * `manager` - Mobile device manager
+/// * `uri` - Resource URI from resources/list or an expanded template
+///
+/// # Returns
+/// Contents array with JSON text or a base64 PNG blob, or why it failed
+///
+/// # Example Response
+/// ```json
+/// {"contents": [{"uri": "device://emulator-5554/screenshot.png", "mimeType": "image/png", "blob": "iVBORw0KGgo..."}]}
+/// ```
+pub fn handle_read_resource(manager: &mut MobileDeviceManager, uri: &str) -> ResourceResult {
+    let resource =
+        DeviceResource::parse(uri).ok_or_else(|| ResourceError::NotFound(uri.to_string()))?;
+
+    let devices = manager.list_all_devices("auto");
+
+    let Some(device_id) = resource.device_id() else {
+        return json_contents(uri, &devices);
+    };
+
+    let device = devices
+        .iter()
+        .find(|d| d.id == device_id)
+        .ok_or_else(|| ResourceError::NotFound(uri.to_string()))?;
+    let platform = device.platform.as_str();
+
+    match &resource {
+        DeviceResource::Info(_) => {
+            // Screen size and orientation are best-effort extras
+            let screen_size = manager
+                .get_screen_size(device_id, platform)
+                .ok()
+                .map(|(width, height)| serde_json::json!({ "width": width, "height": height }));
+            let orientation = manager.get_orientation(device_id, platform).ok();
+
+            let mut info = serde_json::to_value(device)
+                .map_err(|e| ResourceError::Read(format!("Failed to serialize device: {}", e)))?;
+            info["screen_size"] = screen_size.unwrap_or(Value::Null);
+            info["orientation"] = orientation.map(Value::String).unwrap_or(Value::Null);
+            json_contents(uri, &info)
+        }
+        DeviceResource::Screenshot(_) => {
+            let screenshot = manager
+                .take_screenshot(device_id, platform, &no_progress)
+                .map_err(|e| ResourceError::Read(format!("Failed to take screenshot: {}", e)))?;
+            Ok(serde_json::json!({
+                "contents": [{
+                    "uri": uri,
+                    "mimeType": "image/png",
+                    "blob": STANDARD.encode(&screenshot)
+                }]
+            }))
+        }
+        DeviceResource::Hierarchy(_) => {
+            let elements = manager
+                .list_elements_on_screen(device_id, platform, None, &no_progress)
+                .map_err(|e| ResourceError::Read(format!("Failed to list elements: {}", e)))?;
+            json_contents(uri, &elements)
+        }
+        DeviceResource::List => unreachable!("device list has no device id"),
+    }
+}
+
+/// Wrap a serializable value as a single JSON text content
+fn json_contents<T: serde::Serialize>(uri: &str, value: &T) -> ResourceResult {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| ResourceError::Read(format!("Failed to serialize resource: {}", e)))?;
+
+    Ok(serde_json::json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": "application/json",
+            "text": text
+        }]
+    }))
+}
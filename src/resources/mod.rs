@@ -0,0 +1,9 @@
+// mobile-mcp-zed-extension/src/resources/mod.rs
+// Resources module exposing live device state
+
+pub mod definitions;
+pub mod handlers;
+pub mod watcher;
+
+pub use definitions::{get_resource_templates, DeviceResource, ResourceDefinition};
+pub use handlers::*;
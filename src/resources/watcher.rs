@@ -0,0 +1,476 @@
+// mobile-mcp-zed-extension/src/resources/watcher.rs
+// Background Watcher for Resource Change Notifications
+//
+// Devices change without the client asking: emulators boot, phones get
+// unplugged, the user taps into another screen. The watcher polls device
+// state on its own thread (with its own clone of the device manager, so it
+// never blocks tool calls) and writes notifications through each session's
+// writer:
+//
+// - notifications/resources/list_changed - a device appeared or disappeared
+// - notifications/resources/updated      - a subscribed resource changed
+//
+// There is one watcher per process. Its thread runs while at least one
+// session watches, and each session only gets the updates it subscribed to.
+// The foreground activity is only queried for devices whose hierarchy or
+// screenshot some session subscribed to, since that costs an adb round-trip
+// per device.
+//
+// Polls are left out of transcripts (--record), and nothing is polled while
+// replaying one (--replay): the polls would consume the device output
+// recorded for the session's own requests.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use super::definitions::DeviceResource;
+use crate::mcp::logging::{self, LogLevel, SessionLogger};
+use crate::mcp::{McpNotification, MessageWriter};
+use crate::MobileDeviceManager;
+
+/// URIs the client subscribed to, shared with the server
+pub type Subscriptions = Arc<Mutex<HashSet<String>>>;
+
+/// How often device state is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What the watcher last saw on one device
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DeviceSnapshot {
+    /// Connection state reported by adb/simctl
+    pub state: String,
+    /// Foreground activity, only tracked while a screen resource is subscribed
+    pub foreground: Option<String>,
+}
+
+/// Device id -> last seen state
+pub type Snapshot = BTreeMap<String, DeviceSnapshot>;
+
+/// Work out which notifications a change between two polls produces
+///
+/// # Arguments
+/// * `previous` - Snapshot from the last poll
+/// * `current` - Snapshot from this poll
+/// * `subscriptions` - Subscribed resource URIs
+///
+/// # Returns
+/// Notifications to send, list_changed first
+pub fn changes(
+    previous: &Snapshot,
+    current: &Snapshot,
+    subscriptions: &HashSet<String>,
+) -> Vec<McpNotification> {
+    let mut notifications = Vec::new();
+
+    if !previous.keys().eq(current.keys()) {
+        notifications.push(McpNotification::resource_list_changed());
+    }
+
+    let state = |snapshot: &Snapshot, id: &str| snapshot.get(id).map(|d| d.state.clone());
+    let states_changed = !previous
+        .iter()
+        .map(|(id, d)| (id, &d.state))
+        .eq(current.iter().map(|(id, d)| (id, &d.state)));
+
+    let mut uris: Vec<_> = subscriptions.iter().collect();
+    uris.sort();
+    for uri in uris {
+        let changed = match DeviceResource::parse(uri) {
+            Some(DeviceResource::List) => states_changed,
+            Some(DeviceResource::Info(id)) => state(previous, &id) != state(current, &id),
+            Some(DeviceResource::Hierarchy(id)) | Some(DeviceResource::Screenshot(id)) => {
+                match (previous.get(&id), current.get(&id)) {
+                    // A foreground that wasn't tracked yet is not a change
+                    (Some(before), Some(after)) => {
+                        before.state != after.state
+                            || (before.foreground.is_some()
+                                && after.foreground.is_some()
+                                && before.foreground != after.foreground)
+                    }
+                    (before, after) => before.is_some() != after.is_some(),
+                }
+            }
+            None => false,
+        };
+        if changed {
+            notifications.push(McpNotification::resource_updated(uri));
+        }
+    }
+
+    notifications
+}
+
+/// Poll the current device state
+fn take_snapshot(manager: &mut MobileDeviceManager, subscriptions: &HashSet<String>) -> Snapshot {
+    let screen_watched: HashSet<String> = subscriptions
+        .iter()
+        .filter_map(|uri| match DeviceResource::parse(uri) {
+            Some(DeviceResource::Hierarchy(id)) | Some(DeviceResource::Screenshot(id)) => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    manager
+        .list_all_devices("auto")
+        .into_iter()
+        .map(|device| {
+            let foreground = if screen_watched.contains(&device.id) {
+                manager
+                    .get_foreground_activity(&device.id, &device.platform)
+                    .ok()
+            } else {
+                None
+            };
+            (
+                device.id,
+                DeviceSnapshot {
+                    state: device.state,
+                    foreground,
+                },
+            )
+        })
+        .collect()
+}
+
+/// A watching session: its subscriptions and where its notifications go
+struct Watcher {
+    writer: MessageWriter,
+    subscriptions: Subscriptions,
+    logger: SessionLogger,
+}
+
+#[derive(Default)]
+struct Watchers {
+    sessions: HashMap<u64, Watcher>,
+    next_id: u64,
+    /// The polling thread is running
+    polling: bool,
+}
+
+/// Device watcher shared by every session of the process
+#[derive(Clone)]
+pub struct ResourceWatcher {
+    manager: MobileDeviceManager,
+    watchers: Arc<Mutex<Watchers>>,
+}
+
+impl ResourceWatcher {
+    /// Watcher polling through (a clone of) the given manager
+    pub fn new(manager: MobileDeviceManager) -> Self {
+        Self {
+            manager,
+            watchers: Arc::default(),
+        }
+    }
+
+    /// Start sending a session notifications
+    ///
+    /// Starts the polling thread if no other session is watching.
+    ///
+    /// # Arguments
+    /// * `writer` - The session's writer for outgoing messages
+    /// * `subscriptions` - Subscribed resource URIs (updated by the server)
+    /// * `logger` - Session logger for the notifications sent
+    ///
+    /// # Returns
+    /// A handle that stops the notifications when dropped
+    pub fn watch(
+        &self,
+        writer: MessageWriter,
+        subscriptions: Subscriptions,
+        logger: SessionLogger,
+    ) -> WatchHandle {
+        let mut watchers = lock(&self.watchers);
+        let id = watchers.next_id;
+        watchers.next_id += 1;
+        watchers.sessions.insert(
+            id,
+            Watcher {
+                writer,
+                subscriptions,
+                logger,
+            },
+        );
+        if !watchers.polling && !crate::recording::is_replaying() {
+            watchers.polling = self.spawn_poller();
+        }
+
+        WatchHandle {
+            watchers: Arc::clone(&self.watchers),
+            id,
+        }
+    }
+
+    /// Poll devices until no session is watching
+    ///
+    /// The thread isn't joined: an adb call in flight may take seconds and
+    /// shouldn't delay shutdown.
+    fn spawn_poller(&self) -> bool {
+        let mut manager = self.manager.clone();
+        let watchers = Arc::clone(&self.watchers);
+
+        thread::Builder::new()
+            .name("resource-watcher".to_string())
+            .spawn(move || {
+                let _polling = Polling(Arc::clone(&watchers));
+                crate::recording::skip_thread();
+                // Polling itself isn't logged to the client: at debug level
+                // it would bury everything else under device listings
+                let mut previous = take_snapshot(&mut manager, &subscribed(&watchers));
+
+                loop {
+                    thread::sleep(POLL_INTERVAL);
+                    {
+                        let mut watchers = lock(&watchers);
+                        if watchers.sessions.is_empty() {
+                            watchers.polling = false;
+                            return;
+                        }
+                    }
+
+                    let current = take_snapshot(&mut manager, &subscribed(&watchers));
+                    notify(&watchers, &previous, &current);
+                    previous = current;
+                }
+            })
+            .is_ok()
+    }
+}
+
+/// Held by the poller thread so a panic doesn't leave `polling` set, which
+/// would keep the next session from starting a new thread
+struct Polling(Arc<Mutex<Watchers>>);
+
+impl Drop for Polling {
+    fn drop(&mut self) {
+        // A normal exit clears the flag itself, under the same lock that
+        // found no session left
+        if thread::panicking() {
+            lock(&self.0).polling = false;
+        }
+    }
+}
+
+/// A session's registration with the watcher
+///
+/// Dropping it stops the session's notifications; the thread exits once no
+/// session is left.
+pub struct WatchHandle {
+    watchers: Arc<Mutex<Watchers>>,
+    id: u64,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        lock(&self.watchers).sessions.remove(&self.id);
+    }
+}
+
+/// URIs subscribed by any session
+fn subscribed(watchers: &Mutex<Watchers>) -> HashSet<String> {
+    let subscriptions: Vec<Subscriptions> = lock(watchers)
+        .sessions
+        .values()
+        .map(|watcher| Arc::clone(&watcher.subscriptions))
+        .collect();
+    subscriptions
+        .iter()
+        .flat_map(|subscriptions| lock(subscriptions).clone())
+        .collect()
+}
+
+/// Send every session the notifications for its subscriptions
+///
+/// Sessions whose client is gone are dropped.
+fn notify(watchers: &Mutex<Watchers>, previous: &Snapshot, current: &Snapshot) {
+    let mut watchers = lock(watchers);
+    watchers.sessions.retain(|_, watcher| {
+        let subscriptions = lock(&watcher.subscriptions).clone();
+        for notification in changes(previous, current, &subscriptions) {
+            watcher.logger.log(
+                LogLevel::Debug,
+                logging::PROTOCOL,
+                &format!("Resource watcher: {:?}", notification.params),
+            );
+            if watcher.writer.write_message(&notification).is_err() {
+                return false;
+            }
+        }
+        true
+    });
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(devices: &[(&str, &str, Option<&str>)]) -> Snapshot {
+        devices
+            .iter()
+            .map(|(id, state, foreground)| {
+                (
+                    id.to_string(),
+                    DeviceSnapshot {
+                        state: state.to_string(),
+                        foreground: foreground.map(|f| f.to_string()),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn subscribed(uris: &[&str]) -> HashSet<String> {
+        uris.iter().map(|uri| uri.to_string()).collect()
+    }
+
+    fn methods_and_uris(notifications: &[McpNotification]) -> Vec<(String, Option<String>)> {
+        notifications
+            .iter()
+            .map(|n| {
+                (
+                    n.method.clone(),
+                    n.params
+                        .as_ref()
+                        .and_then(|p| p["uri"].as_str())
+                        .map(|s| s.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_change_no_notifications() {
+        let before = snapshot(&[("emulator-5554", "device", Some("a/.Main"))]);
+        let subs = subscribed(&["device://list", "device://emulator-5554/hierarchy.json"]);
+        assert!(changes(&before, &before.clone(), &subs).is_empty());
+    }
+
+    #[test]
+    fn test_device_plugged_in() {
+        let before = snapshot(&[]);
+        let after = snapshot(&[("emulator-5554", "device", None)]);
+        let notifications = changes(&before, &after, &subscribed(&["device://list"]));
+        assert_eq!(
+            methods_and_uris(&notifications),
+            vec![
+                ("notifications/resources/list_changed".to_string(), None),
+                (
+                    "notifications/resources/updated".to_string(),
+                    Some("device://list".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_changed_sent_without_subscriptions() {
+        let before = snapshot(&[("emulator-5554", "device", None)]);
+        let after = snapshot(&[]);
+        let notifications = changes(&before, &after, &HashSet::new());
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(
+            notifications[0].method,
+            "notifications/resources/list_changed"
+        );
+    }
+
+    #[test]
+    fn test_foreground_change_updates_hierarchy_only() {
+        let before = snapshot(&[("emulator-5554", "device", Some("a/.Main"))]);
+        let after = snapshot(&[("emulator-5554", "device", Some("a/.Settings"))]);
+        let subs = subscribed(&[
+            "device://list",
+            "device://emulator-5554/info",
+            "device://emulator-5554/hierarchy.json",
+        ]);
+        assert_eq!(
+            methods_and_uris(&changes(&before, &after, &subs)),
+            vec![(
+                "notifications/resources/updated".to_string(),
+                Some("device://emulator-5554/hierarchy.json".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_newly_tracked_foreground_is_not_a_change() {
+        let before = snapshot(&[("emulator-5554", "device", None)]);
+        let after = snapshot(&[("emulator-5554", "device", Some("a/.Main"))]);
+        let subs = subscribed(&["device://emulator-5554/hierarchy.json"]);
+        assert!(changes(&before, &after, &subs).is_empty());
+    }
+
+    #[test]
+    fn test_each_session_gets_its_own_updates() {
+        let watchers = Mutex::new(Watchers::default());
+        let mut receivers = Vec::new();
+        for (id, uri) in ["device://list", "device://emulator-5554/info"]
+            .iter()
+            .enumerate()
+        {
+            let (writer, receiver) = MessageWriter::channel();
+            let subscriptions: Subscriptions = Arc::new(Mutex::new(subscribed(&[uri])));
+            lock(&watchers).sessions.insert(
+                id as u64,
+                Watcher {
+                    logger: SessionLogger::new(writer.clone(), false),
+                    writer,
+                    subscriptions,
+                },
+            );
+            receivers.push(receiver);
+        }
+        assert_eq!(super::subscribed(&watchers).len(), 2);
+
+        let before = snapshot(&[("emulator-5554", "device", None)]);
+        let after = snapshot(&[("emulator-5554", "offline", None)]);
+        notify(&watchers, &before, &after);
+        assert!(receivers[0].try_recv().unwrap().contains("device://list"));
+        assert!(receivers[0].try_recv().is_err());
+        assert!(receivers[1]
+            .try_recv()
+            .unwrap()
+            .contains("emulator-5554/info"));
+
+        // A session whose client is gone stops watching
+        drop(receivers.remove(0));
+        notify(&watchers, &after, &before);
+        assert_eq!(lock(&watchers).sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_panicking_poller_can_be_restarted() {
+        let watchers = Arc::new(Mutex::new(Watchers {
+            polling: true,
+            ..Watchers::default()
+        }));
+        let poller = Arc::clone(&watchers);
+        let result = thread::spawn(move || {
+            let _polling = Polling(poller);
+            panic!("device listing failed");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(!lock(&watchers).polling);
+    }
+
+    #[test]
+    fn test_state_change_updates_info() {
+        let before = snapshot(&[("emulator-5554", "device", None)]);
+        let after = snapshot(&[("emulator-5554", "offline", None)]);
+        let subs = subscribed(&["device://emulator-5554/info"]);
+        assert_eq!(
+            methods_and_uris(&changes(&before, &after, &subs)),
+            vec![(
+                "notifications/resources/updated".to_string(),
+                Some("device://emulator-5554/info".to_string())
+            )]
+        );
+    }
+}
@@ -0,0 +1,430 @@
+// mobile-mcp-zed-extension/src/tools/args.rs
+// Typed Tool Arguments
+//
+// Every tool's arguments are described by one struct. The same type is used
+// twice: its JSON Schema (generated by schemars from the fields and their doc
+// comments) becomes the tool's `inputSchema`, and tools/call arguments are
+// decoded into it before dispatch. The schema a client sees can't drift from
+// what the server actually accepts.
+//
+// Unknown arguments are rejected (`deny_unknown_fields`, which the schema
+// reports as `additionalProperties: false`) so a typo never falls back to a
+// default.
+//
+// Adding a tool means adding its argument struct here, a definition naming
+// it in definitions.rs and a handler in handlers.rs.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::devices::{BackendRegistry, Button, Orientation};
+
+/// Generate the `inputSchema` of a tool from its argument type
+///
+/// Subschemas are inlined and the type's own title and description dropped:
+/// clients show the tool's name and description instead.
+pub fn input_schema<T: JsonSchema>() -> Value {
+    schema_for::<T>(false)
+}
+
+/// Generate a tool's `outputSchema` (or part of it) from a result type
+///
+/// Like input_schema, except that `Option` fields may be null: results
+/// serialize a missing value as null instead of leaving it out.
+pub fn output_schema<T: JsonSchema>() -> Value {
+    schema_for::<T>(true)
+}
+
+fn schema_for<T: JsonSchema>(nullable_options: bool) -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|settings| {
+            settings.inline_subschemas = true;
+            settings.option_add_null_type = nullable_options;
+            settings.meta_schema = None;
+        })
+        .into_generator();
+    let schema = generator.into_root_schema_for::<T>();
+
+    let mut value = serde_json::to_value(schema).unwrap_or_else(|_| json!({}));
+    if let Some(object) = value.as_object_mut() {
+        object.remove("title");
+        object.remove("description");
+    }
+    // Optional arguments are `#[serde(default)]`, which schemars reports as
+    // `"default": null` - not a valid value for a string enum
+    if let Some(properties) = value["properties"].as_object_mut() {
+        for property in properties.values_mut().filter_map(Value::as_object_mut) {
+            if property.get("default") == Some(&Value::Null) {
+                property.remove("default");
+            }
+        }
+    }
+    value
+}
+
+/// Decode tools/call arguments into a tool's argument type
+///
+/// Omitted arguments are treated as an empty object, so tools without
+/// required arguments can be called without any.
+pub fn decode<T: DeserializeOwned>(args: Value) -> Result<T, String> {
+    let args = if args.is_null() { json!({}) } else { args };
+    serde_json::from_value(args).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+// ============================================================================
+// Enumerated Values
+// ============================================================================
+//
+// Platforms, buttons and orientations stay strings so handlers can check them
+// per platform, but the schema lists the accepted values.
+
+fn string_enum(values: &[&str]) -> Schema {
+    serde_json::from_value(json!({
+        "type": "string",
+        "enum": values
+    }))
+    .unwrap_or(Schema::Bool(true))
+}
+
+fn platform_schema(_: &mut SchemaGenerator) -> Schema {
+    string_enum(BackendRegistry::default_platforms())
+}
+
+fn platform_filter_schema(_: &mut SchemaGenerator) -> Schema {
+    let mut platforms = BackendRegistry::default_platforms().to_vec();
+    platforms.push("auto");
+    string_enum(&platforms)
+}
+
+fn button_schema(_: &mut SchemaGenerator) -> Schema {
+    let names: Vec<&str> = Button::ALL
+        .iter()
+        .map(Button::name)
+        .chain(Button::ALIASES.iter().map(|(alias, _)| *alias))
+        .collect();
+    string_enum(&names)
+}
+
+fn orientation_schema(_: &mut SchemaGenerator) -> Schema {
+    string_enum(&Orientation::ALL.map(|orientation| orientation.name()))
+}
+
+// ============================================================================
+// Device Information Tools
+// ============================================================================
+
+/// Arguments of list_available_devices
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListDevicesArgs {
+    /// Only list devices of this platform (defaults to the configured platform)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_filter_schema")]
+    pub platform: Option<String>,
+}
+
+/// Arguments of select_device
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SelectDeviceArgs {
+    /// Device identifier or alias to use when device_id is omitted
+    pub device_id: String,
+}
+
+/// Arguments of tools that only need the target device (get_screen_size,
+/// get_orientation, list_apps, take_screenshot)
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+}
+
+/// Arguments of list_elements_on_screen
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListElementsArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Optional filter to search for specific elements (e.g., text content, resource ID)
+    pub filter: Option<String>,
+}
+
+// ============================================================================
+// Screen Interaction Tools
+// ============================================================================
+
+/// Arguments of save_screenshot
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SaveScreenshotArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Where to save the screenshot, relative to the workspace (e.g., 'screenshots/home.png')
+    pub output_path: String,
+}
+
+/// Arguments of click_on_screen_at_coordinates and double_tap_on_screen
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TapArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// X coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub x: f64,
+    /// Y coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub y: f64,
+}
+
+/// Arguments of long_press_on_screen_at_coordinates
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LongPressArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// X coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub x: f64,
+    /// Y coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub y: f64,
+    /// Duration of long press in milliseconds (default: 1000)
+    pub duration: Option<u32>,
+}
+
+/// Arguments of swipe_on_screen
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SwipeArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Starting X coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub start_x: f64,
+    /// Starting Y coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub start_y: f64,
+    /// Ending X coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub end_x: f64,
+    /// Ending Y coordinate in pixels
+    #[schemars(range(min = 0))]
+    pub end_y: f64,
+    /// Duration of swipe in milliseconds (default: 300)
+    pub duration: Option<u32>,
+}
+
+// ============================================================================
+// Input Tools
+// ============================================================================
+
+/// Arguments of type_keys
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TypeKeysArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Text to type
+    pub text: String,
+}
+
+/// Arguments of press_button
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PressButtonArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Button name: home, back, menu, power, volume_up, volume_down, camera, enter, etc.
+    #[schemars(schema_with = "button_schema")]
+    pub button: String,
+}
+
+// ============================================================================
+// App Management Tools
+// ============================================================================
+
+/// Arguments of launch_app
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LaunchAppArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// App package name (Android: com.example.app) or bundle ID (iOS: com.example.app), or common name (chrome, youtube, settings, etc.)
+    pub app_id: String,
+}
+
+/// Arguments of terminate_app and uninstall_app
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AppArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// App package name (Android) or bundle ID (iOS)
+    pub app_id: String,
+}
+
+/// Arguments of install_app
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InstallAppArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Path to APK file (Android) or IPA file (iOS), relative to the workspace or absolute
+    pub app_path: String,
+}
+
+// ============================================================================
+// Navigation Tools
+// ============================================================================
+
+/// Arguments of open_url
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OpenUrlArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// URL to open (must include http:// or https://)
+    pub url: String,
+}
+
+/// Arguments of set_orientation
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SetOrientationArgs {
+    /// Device identifier or alias (e.g., 'emulator-5554' for Android or device UDID
+    /// for iOS); defaults to the selected device, or the only one connected
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Platform override (detected from device_id by default)
+    #[serde(default)]
+    #[schemars(schema_with = "platform_schema")]
+    pub platform: Option<String>,
+    /// Target orientation
+    #[schemars(schema_with = "orientation_schema")]
+    pub orientation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_from_struct() {
+        let schema = input_schema::<LongPressArgs>();
+        assert_eq!(schema["type"], "object");
+        assert!(schema.get("title").is_none());
+        assert_eq!(schema["properties"]["x"]["type"], "number");
+        assert_eq!(
+            schema["properties"]["x"]["description"],
+            "X coordinate in pixels"
+        );
+        assert_eq!(
+            schema["properties"]["platform"]["enum"],
+            json!(["android", "ios"])
+        );
+        assert_eq!(schema["required"], json!(["x", "y"]));
+        assert!(schema["properties"]["platform"].get("default").is_none());
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["properties"]["y"]["minimum"], 0.0);
+    }
+
+    #[test]
+    fn test_decode_arguments() {
+        let args: TapArgs =
+            decode(json!({"device_id": "emulator-5554", "x": 10, "y": 20.5})).unwrap();
+        assert_eq!(args.device_id.as_deref(), Some("emulator-5554"));
+        assert_eq!(args.platform, None);
+        assert_eq!(args.y, 20.5);
+
+        let error = decode::<TapArgs>(json!({"device_id": "emulator-5554", "x": 10})).unwrap_err();
+        assert!(error.contains("missing field `y`"));
+
+        // The device can be left to the session default
+        let args: TapArgs = decode(json!({"x": 10, "y": 20})).unwrap();
+        assert!(args.device_id.is_none());
+    }
+
+    #[test]
+    fn test_omitted_arguments_are_empty() {
+        let args: ListDevicesArgs = decode(Value::Null).unwrap();
+        assert!(args.platform.is_none());
+    }
+}
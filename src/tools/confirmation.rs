@@ -0,0 +1,119 @@
+// mobile-mcp-zed-extension/src/tools/confirmation.rs
+// Confirming Destructive Tool Calls
+//
+// Tools annotated as destructive (uninstall, terminate...) can lose data the
+// user cares about. When the client supports elicitation the server asks the
+// user before running them, naming the device and the app. Emulators and
+// simulators are usually disposable, so the auto_approve_virtual_devices
+// setting lets those calls through without asking.
+
+use serde_json::Value;
+
+use super::definitions::ToolDefinition;
+use crate::types::DeviceInfo;
+
+/// The question to ask before running a tool call, or None to run it as is
+///
+/// # Arguments
+/// * `tool` - The tool being called
+/// * `args` - Its (validated) arguments
+/// * `device` - The device the call targets, if it is connected
+/// * `auto_approve_virtual` - Skip the question for emulators and simulators
+pub fn confirmation_message(
+    tool: &ToolDefinition,
+    args: &Value,
+    device: Option<&DeviceInfo>,
+    auto_approve_virtual: bool,
+) -> Option<String> {
+    if !tool.is_destructive() {
+        return None;
+    }
+    if auto_approve_virtual && device.is_some_and(DeviceInfo::is_virtual) {
+        return None;
+    }
+
+    let action = tool
+        .annotations
+        .as_ref()
+        .map(|annotations| annotations.title.clone())
+        .unwrap_or_else(|| tool.name.clone());
+    let device_id = args.get("device_id").and_then(Value::as_str).unwrap_or("");
+    let target = match device {
+        Some(device) => format!("{} ({}, {})", device.name, device_id, device.device_type),
+        None => device_id.to_string(),
+    };
+
+    Some(match args.get("app_id").and_then(Value::as_str) {
+        Some(app_id) => format!("{} {} on {}?", action, app_id, target),
+        None => format!("{} on {}?", action, target),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::find_tool;
+    use serde_json::json;
+
+    fn tool(name: &str) -> &'static ToolDefinition {
+        find_tool(name).unwrap()
+    }
+
+    fn device(id: &str, device_type: &str) -> DeviceInfo {
+        DeviceInfo {
+            id: id.to_string(),
+            name: "Pixel 6".to_string(),
+            platform: "android".to_string(),
+            device_type: device_type.to_string(),
+            state: "connected".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_destructive_tool_names_device_and_app() {
+        let args = json!({"device_id": "R58M123ABC", "app_id": "com.example.app"});
+        let message = confirmation_message(
+            tool("mobile_device_mcp_uninstall_app"),
+            &args,
+            Some(&device("R58M123ABC", "mobile")),
+            true,
+        );
+        assert_eq!(
+            message.as_deref(),
+            Some("Uninstall App com.example.app on Pixel 6 (R58M123ABC, mobile)?")
+        );
+    }
+
+    #[test]
+    fn test_non_destructive_tool_runs_unasked() {
+        let args = json!({"device_id": "R58M123ABC", "app_id": "com.example.app"});
+        let message = confirmation_message(
+            tool("mobile_device_mcp_launch_app"),
+            &args,
+            Some(&device("R58M123ABC", "mobile")),
+            false,
+        );
+        assert!(message.is_none());
+    }
+
+    #[test]
+    fn test_virtual_devices_auto_approved_only_when_enabled() {
+        let terminate = tool("mobile_device_mcp_terminate_app");
+        let emulator = device("emulator-5554", "mobile");
+        let args = json!({"device_id": "emulator-5554", "app_id": "com.example.app"});
+
+        assert!(confirmation_message(terminate, &args, Some(&emulator), true).is_none());
+        assert!(confirmation_message(terminate, &args, Some(&emulator), false).is_some());
+    }
+
+    #[test]
+    fn test_unknown_device_is_asked_about() {
+        let args = json!({"device_id": "gone", "app_id": "com.example.app"});
+        let message =
+            confirmation_message(tool("mobile_device_mcp_terminate_app"), &args, None, true);
+        assert_eq!(
+            message.as_deref(),
+            Some("Terminate App com.example.app on gone?")
+        );
+    }
+}
@@ -5,7 +5,11 @@
 // the Model Context Protocol (MCP) specification. Each tool has:
 // - A unique name (snake_case)
 // - A human-readable description
-// - A JSON schema defining its input parameters
+// - A JSON schema defining its input parameters, generated from the tool's
+//   argument type in args.rs
+// - A JSON schema describing its structured result (outputSchema)
+// - Annotations telling the client whether it only observes the device,
+//   can destroy data, or reaches outside the device
 //
 // Tools are organized into categories:
 // - Device Information: Query device state and capabilities
@@ -14,18 +18,96 @@
 // - App Management: Install, launch, terminate apps
 // - Navigation: URL opening, orientation control
 
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+use crate::devices::{InstalledApp, ScreenElement};
+use crate::types::DeviceInfo;
+
+use super::args::{
+    input_schema, output_schema, AppArgs, DeviceArgs, InstallAppArgs, LaunchAppArgs,
+    ListDevicesArgs, ListElementsArgs, LongPressArgs, OpenUrlArgs, PressButtonArgs,
+    SaveScreenshotArgs, SelectDeviceArgs, SetOrientationArgs, SwipeArgs, TapArgs, TypeKeysArgs,
+};
 
 /// Represents an MCP tool with its schema
 ///
 /// This structure encapsulates everything needed to define a tool in the
 /// Model Context Protocol. The input_schema follows JSON Schema specification
-/// and defines what parameters the tool accepts.
+/// and defines what parameters the tool accepts; the output_schema describes
+/// the `structuredContent` of a successful result.
 #[derive(Debug, Clone)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: String,
     pub input_schema: Value,
+    pub output_schema: Option<Value>,
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Hints about a tool's behavior
+///
+/// Clients use them to decide what needs the user's approval: observation
+/// tools can run unattended, destructive ones should be confirmed. All hints
+/// are always sent because the spec's defaults assume the worst (destructive,
+/// open world).
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolAnnotations {
+    /// Human-readable title
+    pub title: String,
+    /// The tool doesn't change the device
+    #[serde(rename = "readOnlyHint")]
+    pub read_only: bool,
+    /// The tool may delete apps or data
+    #[serde(rename = "destructiveHint")]
+    pub destructive: bool,
+    /// Calling the tool again with the same arguments has no further effect
+    #[serde(rename = "idempotentHint")]
+    pub idempotent: bool,
+    /// The tool reaches beyond the device (e.g., the web)
+    #[serde(rename = "openWorldHint")]
+    pub open_world: bool,
+}
+
+impl ToolAnnotations {
+    /// Annotations of a tool that only observes the device
+    pub fn read_only(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            read_only: true,
+            destructive: false,
+            idempotent: true,
+            open_world: false,
+        }
+    }
+
+    /// Annotations of a tool that changes the device (taps, app launches...)
+    pub fn action(title: &str) -> Self {
+        Self {
+            read_only: false,
+            idempotent: false,
+            ..Self::read_only(title)
+        }
+    }
+
+    /// Mark the tool as able to delete apps or data
+    pub fn destructive(mut self) -> Self {
+        self.destructive = true;
+        self
+    }
+
+    /// Mark repeated calls with the same arguments as harmless
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    /// Mark the tool as reaching beyond the device
+    pub fn open_world(mut self) -> Self {
+        self.open_world = true;
+        self
+    }
 }
 
 impl ToolDefinition {
@@ -40,16 +122,50 @@ impl ToolDefinition {
             name: name.to_string(),
             description: description.to_string(),
             input_schema,
+            output_schema: None,
+            annotations: None,
         }
     }
 
+    /// Declare the structure of the tool's `structuredContent`
+    ///
+    /// # Arguments
+    /// * `output_schema` - JSON Schema object describing a successful result
+    pub fn with_output_schema(mut self, output_schema: Value) -> Self {
+        self.output_schema = Some(output_schema);
+        self
+    }
+
+    /// Describe the tool's behavior to the client
+    ///
+    /// # Arguments
+    /// * `annotations` - Title and behavior hints
+    pub fn with_annotations(mut self, annotations: ToolAnnotations) -> Self {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    /// Whether the tool may delete apps or data (see ToolAnnotations)
+    pub fn is_destructive(&self) -> bool {
+        self.annotations
+            .as_ref()
+            .is_some_and(|annotations| annotations.destructive)
+    }
+
     /// Convert tool definition to MCP-compliant JSON format
     pub fn to_json(&self) -> Value {
-        json!({
+        let mut json = json!({
             "name": self.name,
             "description": self.description,
             "inputSchema": self.input_schema
-        })
+        });
+        if let Some(output_schema) = &self.output_schema {
+            json["outputSchema"] = output_schema.clone();
+        }
+        if let Some(annotations) = &self.annotations {
+            json["annotations"] = serde_json::to_value(annotations).unwrap_or(Value::Null);
+        }
+        json
     }
 }
 
@@ -59,35 +175,47 @@ impl ToolDefinition {
 /// of all 20+ tools available for mobile device automation. Tools are returned
 /// in a logical order by category for better organization.
 ///
+/// The schemas are generated once and shared; they only depend on the
+/// platforms, which don't change after startup.
+///
 /// # Returns
-/// A vector of all tool definitions ready to be sent to MCP clients
-pub fn get_all_tools() -> Vec<ToolDefinition> {
-    vec![
-        // Device Information Tools
-        tool_list_available_devices(),
-        tool_get_screen_size(),
-        tool_get_orientation(),
-        tool_list_apps(),
-        tool_list_elements_on_screen(),
-        // Screen Interaction Tools
-        tool_take_screenshot(),
-        tool_save_screenshot(),
-        tool_click_on_screen(),
-        tool_double_tap_on_screen(),
-        tool_long_press_on_screen(),
-        tool_swipe_on_screen(),
-        // Input Tools
-        tool_type_keys(),
-        tool_press_button(),
-        // App Management Tools
-        tool_launch_app(),
-        tool_terminate_app(),
-        tool_install_app(),
-        tool_uninstall_app(),
-        // Navigation Tools
-        tool_open_url(),
-        tool_set_orientation(),
-    ]
+/// All tool definitions ready to be sent to MCP clients
+pub fn get_all_tools() -> &'static [ToolDefinition] {
+    static TOOLS: OnceLock<Vec<ToolDefinition>> = OnceLock::new();
+    TOOLS.get_or_init(|| {
+        vec![
+            // Device Information Tools
+            tool_list_available_devices(),
+            tool_select_device(),
+            tool_get_screen_size(),
+            tool_get_orientation(),
+            tool_list_apps(),
+            tool_list_elements_on_screen(),
+            // Screen Interaction Tools
+            tool_take_screenshot(),
+            tool_save_screenshot(),
+            tool_click_on_screen(),
+            tool_double_tap_on_screen(),
+            tool_long_press_on_screen(),
+            tool_swipe_on_screen(),
+            // Input Tools
+            tool_type_keys(),
+            tool_press_button(),
+            // App Management Tools
+            tool_launch_app(),
+            tool_terminate_app(),
+            tool_install_app(),
+            tool_uninstall_app(),
+            // Navigation Tools
+            tool_open_url(),
+            tool_set_orientation(),
+        ]
+    })
+}
+
+/// Look up a tool definition by name
+pub fn find_tool(name: &str) -> Option<&'static ToolDefinition> {
+    get_all_tools().iter().find(|tool| tool.name == name)
 }
 
 // ============================================================================
@@ -107,12 +235,24 @@ fn tool_list_available_devices() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_list_available_devices",
         "List all available mobile devices and simulators. This includes both physical devices and emulators for Android and iOS platforms.",
-        json!({
-            "type": "object",
-            "properties": {},
-            "required": []
-        })
+        input_schema::<ListDevicesArgs>(),
+    )
+    .with_output_schema(devices_output_schema())
+    .with_annotations(ToolAnnotations::read_only("List Devices"))
+}
+
+/// Choose the default device of the session
+///
+/// With several devices connected, calls without device_id would otherwise
+/// fail. Selecting one lets a conversation stick to it.
+fn tool_select_device() -> ToolDefinition {
+    ToolDefinition::new(
+        "mobile_device_mcp_select_device",
+        "Select the device that tools use when device_id is omitted, for the rest of the session. Accepts a device id or an alias from the settings.",
+        input_schema::<SelectDeviceArgs>(),
     )
+    .with_output_schema(selected_device_output_schema())
+    .with_annotations(ToolAnnotations::action("Select Device").idempotent())
 }
 
 /// Get device screen dimensions
@@ -123,22 +263,10 @@ fn tool_get_screen_size() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_get_screen_size",
         "Get the screen size of the mobile device in pixels. Returns width and height.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier (e.g., 'emulator-5554' for Android or device UDID for iOS)"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                }
-            },
-            "required": ["device_id", "platform"]
-        }),
+        input_schema::<DeviceArgs>(),
     )
+    .with_output_schema(screen_size_output_schema())
+    .with_annotations(ToolAnnotations::read_only("Get Screen Size"))
 }
 
 /// Query current device orientation
@@ -149,22 +277,10 @@ fn tool_get_orientation() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_get_orientation",
         "Get the current screen orientation of the device. Returns 'portrait' or 'landscape'.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                }
-            },
-            "required": ["device_id", "platform"]
-        }),
+        input_schema::<DeviceArgs>(),
     )
+    .with_output_schema(orientation_output_schema())
+    .with_annotations(ToolAnnotations::read_only("Get Orientation"))
 }
 
 /// List all installed applications
@@ -175,22 +291,10 @@ fn tool_list_apps() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_list_apps",
         "List all the installed apps on the device. Returns app package names and labels.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                }
-            },
-            "required": ["device_id", "platform"]
-        }),
+        input_schema::<DeviceArgs>(),
     )
+    .with_output_schema(apps_output_schema())
+    .with_annotations(ToolAnnotations::read_only("List Apps"))
 }
 
 /// List UI elements currently visible on screen
@@ -202,26 +306,10 @@ fn tool_list_elements_on_screen() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_list_elements_on_screen",
         "List elements on screen and their coordinates, with optional filtering. Returns UI elements with their bounds and properties.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "filter": {
-                    "type": "string",
-                    "description": "Optional filter to search for specific elements (e.g., text content, resource ID)"
-                }
-            },
-            "required": ["device_id", "platform"]
-        })
+        input_schema::<ListElementsArgs>(),
     )
+    .with_output_schema(elements_output_schema())
+    .with_annotations(ToolAnnotations::read_only("List Screen Elements"))
 }
 
 // ============================================================================
@@ -241,22 +329,10 @@ fn tool_take_screenshot() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_take_screenshot",
         "Take a screenshot of the mobile device. Use this to understand the current state of the screen. Returns the screenshot as base64-encoded PNG image data.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                }
-            },
-            "required": ["device_id", "platform"]
-        })
+        input_schema::<DeviceArgs>(),
     )
+    .with_output_schema(screenshot_output_schema())
+    .with_annotations(ToolAnnotations::read_only("Take Screenshot"))
 }
 
 /// Save screenshot to file system
@@ -268,26 +344,10 @@ fn tool_save_screenshot() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_save_screenshot",
         "Save a screenshot of the mobile device to a file. Useful for creating test artifacts or documentation.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "output_path": {
-                    "type": "string",
-                    "description": "Path where the screenshot should be saved (e.g., '/tmp/screenshot.png')"
-                }
-            },
-            "required": ["device_id", "platform", "output_path"]
-        })
+        input_schema::<SaveScreenshotArgs>(),
     )
+    .with_output_schema(saved_screenshot_output_schema())
+    .with_annotations(ToolAnnotations::action("Save Screenshot").idempotent())
 }
 
 /// Perform a single tap at coordinates
@@ -299,30 +359,10 @@ fn tool_click_on_screen() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_click_on_screen_at_coordinates",
         "Click on the screen at given x,y coordinates. Use this to tap buttons, links, or any interactive elements.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "x": {
-                    "type": "number",
-                    "description": "X coordinate in pixels"
-                },
-                "y": {
-                    "type": "number",
-                    "description": "Y coordinate in pixels"
-                }
-            },
-            "required": ["device_id", "platform", "x", "y"]
-        })
+        input_schema::<TapArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Tap"))
 }
 
 /// Perform a double-tap gesture
@@ -334,30 +374,10 @@ fn tool_double_tap_on_screen() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_double_tap_on_screen",
         "Double-tap on the screen at given x,y coordinates. Useful for zoom or activation gestures.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "x": {
-                    "type": "number",
-                    "description": "X coordinate in pixels"
-                },
-                "y": {
-                    "type": "number",
-                    "description": "Y coordinate in pixels"
-                }
-            },
-            "required": ["device_id", "platform", "x", "y"]
-        })
+        input_schema::<TapArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Double Tap"))
 }
 
 /// Perform a long press (press and hold)
@@ -369,35 +389,10 @@ fn tool_long_press_on_screen() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_long_press_on_screen_at_coordinates",
         "Long press on the screen at given x,y coordinates. Useful for context menus or drag operations.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "x": {
-                    "type": "number",
-                    "description": "X coordinate in pixels"
-                },
-                "y": {
-                    "type": "number",
-                    "description": "Y coordinate in pixels"
-                },
-                "duration": {
-                    "type": "number",
-                    "description": "Duration of long press in milliseconds (default: 1000)",
-                    "default": 1000
-                }
-            },
-            "required": ["device_id", "platform", "x", "y"]
-        })
+        input_schema::<LongPressArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Long Press"))
 }
 
 /// Perform a swipe gesture
@@ -409,43 +404,10 @@ fn tool_swipe_on_screen() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_swipe_on_screen",
         "Swipe on the screen from start coordinates to end coordinates. Useful for scrolling or gesture navigation.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "start_x": {
-                    "type": "number",
-                    "description": "Starting X coordinate in pixels"
-                },
-                "start_y": {
-                    "type": "number",
-                    "description": "Starting Y coordinate in pixels"
-                },
-                "end_x": {
-                    "type": "number",
-                    "description": "Ending X coordinate in pixels"
-                },
-                "end_y": {
-                    "type": "number",
-                    "description": "Ending Y coordinate in pixels"
-                },
-                "duration": {
-                    "type": "number",
-                    "description": "Duration of swipe in milliseconds (default: 300)",
-                    "default": 300
-                }
-            },
-            "required": ["device_id", "platform", "start_x", "start_y", "end_x", "end_y"]
-        })
+        input_schema::<SwipeArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Swipe"))
 }
 
 // ============================================================================
@@ -464,26 +426,10 @@ fn tool_type_keys() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_type_keys",
         "Type text into the focused element. Use this to enter text in input fields, search boxes, etc.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "text": {
-                    "type": "string",
-                    "description": "Text to type"
-                }
-            },
-            "required": ["device_id", "platform", "text"]
-        })
+        input_schema::<TypeKeysArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Type Text"))
 }
 
 /// Press hardware or navigation button
@@ -495,27 +441,10 @@ fn tool_press_button() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_press_button",
         "Press a hardware or software button on device. Common buttons: home, back, menu, power, volume_up, volume_down, camera, enter.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "button": {
-                    "type": "string",
-                    "description": "Button name: home, back, menu, power, volume_up, volume_down, camera, enter, etc.",
-                    "enum": ["home", "back", "menu", "power", "volume_up", "volume_down", "camera", "enter", "search", "app_switch"]
-                }
-            },
-            "required": ["device_id", "platform", "button"]
-        })
+        input_schema::<PressButtonArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Press Button"))
 }
 
 // ============================================================================
@@ -535,26 +464,10 @@ fn tool_launch_app() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_launch_app",
         "Launch an app on mobile device. Use this to open a specific app. You can provide either the package name (Android) or bundle ID (iOS), or a common app name like 'chrome', 'youtube', etc.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "app_id": {
-                    "type": "string",
-                    "description": "App package name (Android: com.example.app) or bundle ID (iOS: com.example.app), or common name (chrome, youtube, settings, etc.)"
-                }
-            },
-            "required": ["device_id", "platform", "app_id"]
-        })
+        input_schema::<LaunchAppArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Launch App").idempotent())
 }
 
 /// Force-stop a running application
@@ -566,25 +479,13 @@ fn tool_terminate_app() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_terminate_app",
         "Stop and terminate an app on mobile device. Forces the app to close.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "app_id": {
-                    "type": "string",
-                    "description": "App package name (Android) or bundle ID (iOS)"
-                }
-            },
-            "required": ["device_id", "platform", "app_id"]
-        }),
+        input_schema::<AppArgs>(),
+    )
+    .with_output_schema(action_output_schema())
+    .with_annotations(
+        ToolAnnotations::action("Terminate App")
+            .destructive()
+            .idempotent(),
     )
 }
 
@@ -597,26 +498,10 @@ fn tool_install_app() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_install_app",
         "Install an app on mobile device from a local APK file (Android) or IPA file (iOS).",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "app_path": {
-                    "type": "string",
-                    "description": "Path to APK file (Android) or IPA file (iOS)"
-                }
-            },
-            "required": ["device_id", "platform", "app_path"]
-        }),
+        input_schema::<InstallAppArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Install App").idempotent())
 }
 
 /// Remove an installed application
@@ -628,25 +513,13 @@ fn tool_uninstall_app() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_uninstall_app",
         "Uninstall an app from mobile device. Removes the app completely from the device.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "app_id": {
-                    "type": "string",
-                    "description": "App package name (Android) or bundle ID (iOS)"
-                }
-            },
-            "required": ["device_id", "platform", "app_id"]
-        }),
+        input_schema::<AppArgs>(),
+    )
+    .with_output_schema(action_output_schema())
+    .with_annotations(
+        ToolAnnotations::action("Uninstall App")
+            .destructive()
+            .idempotent(),
     )
 }
 
@@ -666,26 +539,10 @@ fn tool_open_url() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_open_url",
         "Open a URL in browser on device. This will launch the default browser and navigate to the specified URL.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "url": {
-                    "type": "string",
-                    "description": "URL to open (must include http:// or https://)"
-                }
-            },
-            "required": ["device_id", "platform", "url"]
-        })
+        input_schema::<OpenUrlArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Open URL").open_world())
 }
 
 /// Change device screen orientation
@@ -697,27 +554,130 @@ fn tool_set_orientation() -> ToolDefinition {
     ToolDefinition::new(
         "mobile_device_mcp_set_orientation",
         "Change the screen orientation of the device. Sets the device to portrait or landscape mode.",
-        json!({
-            "type": "object",
-            "properties": {
-                "device_id": {
-                    "type": "string",
-                    "description": "Device identifier"
-                },
-                "platform": {
-                    "type": "string",
-                    "description": "Platform: 'android' or 'ios'",
-                    "enum": ["android", "ios"]
-                },
-                "orientation": {
-                    "type": "string",
-                    "description": "Target orientation",
-                    "enum": ["portrait", "landscape"]
-                }
-            },
-            "required": ["device_id", "platform", "orientation"]
-        })
+        input_schema::<SetOrientationArgs>(),
     )
+    .with_output_schema(action_output_schema())
+    .with_annotations(ToolAnnotations::action("Set Orientation").idempotent())
+}
+
+// ============================================================================
+// Output Schemas
+// ============================================================================
+//
+// Successful results carry the data as `structuredContent` next to the text
+// shown to the model. Lists are wrapped in an object because structured
+// content must be a JSON object. The DeviceInfo, InstalledApp and
+// ScreenElement schemas are generated from the types themselves.
+
+/// Result of list_available_devices: every DeviceInfo found
+fn devices_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "devices": {
+                "type": "array",
+                "items": output_schema::<DeviceInfo>()
+            }
+        },
+        "required": ["devices"]
+    })
+}
+
+/// Result of select_device: the DeviceInfo of the selected device
+fn selected_device_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "device": output_schema::<DeviceInfo>()
+        },
+        "required": ["device"]
+    })
+}
+
+/// Result of get_screen_size, in pixels
+fn screen_size_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "width": {"type": "integer"},
+            "height": {"type": "integer"}
+        },
+        "required": ["width", "height"]
+    })
+}
+
+/// Result of get_orientation
+fn orientation_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "orientation": {"type": "string", "enum": ["portrait", "landscape"]}
+        },
+        "required": ["orientation"]
+    })
+}
+
+/// Result of list_apps: every InstalledApp found
+fn apps_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "apps": {
+                "type": "array",
+                "items": output_schema::<InstalledApp>()
+            }
+        },
+        "required": ["apps"]
+    })
+}
+
+/// Result of list_elements_on_screen: every ScreenElement matching the filter
+fn elements_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "elements": {
+                "type": "array",
+                "items": output_schema::<ScreenElement>()
+            }
+        },
+        "required": ["elements"]
+    })
+}
+
+/// Result of take_screenshot (the image itself is in the content)
+fn screenshot_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "mime_type": {"type": "string"},
+            "size_bytes": {"type": "integer"}
+        },
+        "required": ["mime_type", "size_bytes"]
+    })
+}
+
+/// Result of save_screenshot
+fn saved_screenshot_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "size_bytes": {"type": "integer"}
+        },
+        "required": ["path", "size_bytes"]
+    })
+}
+
+/// Result of tools that perform an action: the device's reply
+fn action_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "message": {"type": "string"}
+        },
+        "required": ["message"]
+    })
 }
 
 #[cfg(test)]
@@ -727,7 +687,7 @@ mod tests {
     #[test]
     fn test_get_all_tools_count() {
         let tools = get_all_tools();
-        assert_eq!(tools.len(), 19, "Should have exactly 19 tools");
+        assert_eq!(tools.len(), 20, "Should have exactly 20 tools");
     }
 
     #[test]
@@ -766,6 +726,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_all_tools_have_output_schemas() {
+        for tool in get_all_tools() {
+            let json = tool.to_json();
+            assert_eq!(
+                json["outputSchema"]["type"], "object",
+                "Tool {} should have an object output schema",
+                tool.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_tool_annotations() {
+        let tools = get_all_tools();
+        let annotations = |name: &str| {
+            tools
+                .iter()
+                .find(|t| t.name == name)
+                .map(|t| t.to_json()["annotations"].clone())
+                .unwrap()
+        };
+
+        let screenshot = annotations("mobile_device_mcp_take_screenshot");
+        assert_eq!(screenshot["title"], "Take Screenshot");
+        assert_eq!(screenshot["readOnlyHint"], true);
+
+        // Selecting changes the session, but selecting twice is harmless
+        let select = annotations("mobile_device_mcp_select_device");
+        assert_eq!(select["readOnlyHint"], false);
+        assert_eq!(select["idempotentHint"], true);
+
+        let uninstall = annotations("mobile_device_mcp_uninstall_app");
+        assert_eq!(uninstall["readOnlyHint"], false);
+        assert_eq!(uninstall["destructiveHint"], true);
+        assert_eq!(uninstall["idempotentHint"], true);
+        assert_eq!(
+            annotations("mobile_device_mcp_terminate_app")["destructiveHint"],
+            true
+        );
+
+        let tap = annotations("mobile_device_mcp_click_on_screen_at_coordinates");
+        assert_eq!(tap["destructiveHint"], false);
+        assert_eq!(tap["openWorldHint"], false);
+        assert_eq!(
+            annotations("mobile_device_mcp_open_url")["openWorldHint"],
+            true
+        );
+
+        for tool in tools {
+            assert!(
+                tool.annotations.is_some(),
+                "Tool {} should be annotated",
+                tool.name
+            );
+        }
+    }
+
     #[test]
     fn test_tool_names_unique() {
         let tools = get_all_tools();
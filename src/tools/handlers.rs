@@ -4,7 +4,7 @@
 // This module implements the actual functionality for each MCP tool defined
 // in definitions.rs. Each handler:
 // - Takes a MobileDeviceManager reference and tool-specific parameters
-// - Performs the device operation (via AndroidRobot or IOSDevice)
+// - Performs the device operation (via the platform's DeviceBackend)
 // - Returns an MCP-formatted JSON response or error
 //
 // Handlers are organized by category matching the tool definitions:
@@ -15,9 +15,11 @@
 // - Navigation Handlers
 //
 // All handlers return HandlerResult which wraps either:
-// - Ok(Value) - MCP response with content array
-// - Err(String) - Error message to be sent to client
+// - Ok(Value) - MCP response with content array and structuredContent
+// - Err(ToolError::InvalidArguments) - Bad call, sent as a -32602 protocol error
+// - Err(ToolError::Execution) - Device operation failed, sent as an isError result
 
+use crate::devices::{Button, DeviceSelection, Orientation, Progress};
 #[cfg(feature = "native-binary")]
 use crate::MobileDeviceManager;
 use serde_json::Value;
@@ -25,11 +27,152 @@ use serde_json::Value;
 #[cfg(feature = "native-binary")]
 use base64::{engine::general_purpose::STANDARD, Engine};
 
+/// Why a tool call failed
+///
+/// The MCP spec separates problems with the call itself, which the client has
+/// to fix, from failures while running the tool, which the model should see
+/// so it can recover (retry, pick another device, install the app first...).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolError {
+    /// The arguments don't describe a valid call (unknown platform, button...)
+    InvalidArguments(String),
+    /// The device operation ran and failed (adb error, app not installed...)
+    Execution(String),
+}
+
 /// Result type for tool handlers
 ///
 /// Success returns a JSON Value formatted for MCP protocol with a content array.
-/// Error returns a String that will be wrapped in an MCP error response.
-pub type HandlerResult = Result<Value, String>;
+/// Errors are classified by `ToolError` so the server can report them properly.
+pub type HandlerResult = Result<Value, ToolError>;
+
+/// Build the tool result for a successful call
+///
+/// The text is what the model reads; the structured content matches the
+/// tool's outputSchema so scripts don't have to parse the text.
+///
+/// # Example Response
+/// ```json
+/// {"content": [{"type": "text", "text": "Screen size: 1080x2400 pixels"}],
+///  "structuredContent": {"width": 1080, "height": 2400}}
+/// ```
+pub fn success_result(text: String, structured: Value) -> Value {
+    serde_json::json!({
+        "content": [{
+            "type": "text",
+            "text": text
+        }],
+        "structuredContent": structured
+    })
+}
+
+/// Build the result of a tool that performs an action
+///
+/// # Arguments
+/// * `text` - Summary of the action for the model
+/// * `message` - The device's reply, also returned as structured content
+pub fn action_result(text: String, message: &str) -> Value {
+    success_result(text, serde_json::json!({ "message": message }))
+}
+
+/// Build the tool result for a failed execution
+///
+/// # Example Response
+/// ```json
+/// {"content": [{"type": "text", "text": "Failed to launch app: ..."}], "isError": true}
+/// ```
+pub fn error_result(message: &str) -> Value {
+    serde_json::json!({
+        "content": [{
+            "type": "text",
+            "text": message
+        }],
+        "isError": true
+    })
+}
+
+// ============================================================================
+// Argument Checks
+// ============================================================================
+//
+// Enumerated arguments are checked before touching a device so a typo is
+// reported as invalid params instead of a device failure.
+
+/// Ensure the call targets a platform that device tools can run on
+fn check_platform(manager: &MobileDeviceManager, platform: &str) -> Result<(), ToolError> {
+    let platforms = manager.platforms();
+    if platforms.contains(&platform) {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidArguments(format!(
+            "Unknown platform: {} (expected {})",
+            platform,
+            one_of(&platforms)
+        )))
+    }
+}
+
+/// Ensure the platform filter for device listing is valid
+fn check_platform_filter(manager: &MobileDeviceManager, platform: &str) -> Result<(), ToolError> {
+    let mut platforms = manager.platforms();
+    platforms.push("auto");
+    if platforms.contains(&platform) {
+        Ok(())
+    } else {
+        Err(ToolError::InvalidArguments(format!(
+            "Unknown platform: {} (expected {})",
+            platform,
+            one_of(&platforms)
+        )))
+    }
+}
+
+/// Ensure the button exists on the target platform
+fn check_button(
+    manager: &mut MobileDeviceManager,
+    platform: &str,
+    button: &str,
+) -> Result<(), ToolError> {
+    let available = manager.buttons(platform);
+    match Button::from_name(button) {
+        Some(button) if available.contains(&button) => Ok(()),
+        _ => Err(ToolError::InvalidArguments(format!(
+            "Unknown button for {}: {}. Available: {}",
+            platform,
+            button,
+            available
+                .iter()
+                .map(Button::name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Ensure the orientation is one of the supported values
+fn check_orientation(orientation: &str) -> Result<(), ToolError> {
+    match Orientation::from_name(orientation) {
+        Some(_) => Ok(()),
+        None => Err(ToolError::InvalidArguments(format!(
+            "Invalid orientation: {} (expected {})",
+            orientation,
+            one_of(&Orientation::ALL.map(|orientation| orientation.name()))
+        ))),
+    }
+}
+
+/// Quoted values for an error message: "a", "b" or "c"
+fn one_of(values: &[&str]) -> String {
+    let quoted: Vec<String> = values
+        .iter()
+        .map(|value| format!("\"{}\"", value))
+        .collect();
+    match quoted.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
 
 // ============================================================================
 // Device Information Handlers
@@ -56,6 +199,8 @@ pub type HandlerResult = Result<Value, String>;
 /// - iPhone 15 (UDID-123) - ios simulator [booted]
 /// ```
 pub fn handle_list_devices(manager: &mut MobileDeviceManager, platform: &str) -> HandlerResult {
+    check_platform_filter(manager, platform)?;
+
     let devices = manager.list_all_devices(platform);
 
     let device_list = devices
@@ -64,16 +209,45 @@ pub fn handle_list_devices(manager: &mut MobileDeviceManager, platform: &str) ->
         .collect::<Vec<_>>()
         .join("\n");
 
-    Ok(serde_json::json!({
-        "content": [{
-            "type": "text",
-            "text": if device_list.is_empty() {
-                "No devices found. Please ensure Android platform tools (adb) or iOS tools (xcrun) are installed.".to_string()
-            } else {
-                device_list
-            }
-        }]
-    }))
+    Ok(success_result(
+        if device_list.is_empty() {
+            "No devices found. Please ensure Android platform tools (adb) or iOS tools (xcrun) are installed.".to_string()
+        } else {
+            device_list
+        },
+        serde_json::json!({ "devices": devices }),
+    ))
+}
+
+/// Choose the device of calls that omit device_id
+///
+/// The choice lasts for the rest of the session. Aliases from the settings
+/// are resolved first.
+///
+/// # Arguments
+/// * `manager` - Mobile device manager
+/// * `selection` - The session's device selection
+/// * `device_id` - Device identifier or alias
+///
+/// # Returns
+/// MCP response naming the selected device, or error if it isn't connected
+pub fn handle_select_device(
+    manager: &mut MobileDeviceManager,
+    selection: &DeviceSelection,
+    device_id: &str,
+) -> HandlerResult {
+    let device = manager
+        .find_device(selection.resolve_alias(device_id))
+        .map_err(ToolError::Execution)?;
+    selection.select(&device.id);
+
+    Ok(success_result(
+        format!(
+            "Selected {} ({}). Device tools use it when device_id is omitted",
+            device.name, device.id
+        ),
+        serde_json::json!({ "device": device }),
+    ))
 }
 
 /// Get device screen dimensions
@@ -93,22 +267,27 @@ pub fn handle_get_screen_size(
     device_id: &str,
     platform: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.get_screen_size(device_id, platform) {
-            Ok((width, height)) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Screen size: {}x{} pixels", width, height)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to get screen size: {}", e)),
+            Ok((width, height)) => Ok(success_result(
+                format!("Screen size: {}x{} pixels", width, height),
+                serde_json::json!({ "width": width, "height": height }),
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to get screen size: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -128,22 +307,27 @@ pub fn handle_get_orientation(
     device_id: &str,
     platform: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.get_orientation(device_id, platform) {
-            Ok(orientation) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Current orientation: {}", orientation)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to get orientation: {}", e)),
+            Ok(orientation) => Ok(success_result(
+                format!("Current orientation: {}", orientation),
+                serde_json::json!({ "orientation": orientation }),
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to get orientation: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -165,6 +349,8 @@ pub fn handle_list_apps(
     device_id: &str,
     platform: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.list_apps(device_id, platform) {
@@ -175,24 +361,24 @@ pub fn handle_list_apps(
                     .collect::<Vec<_>>()
                     .join("\n");
 
-                Ok(serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": if app_list.is_empty() {
-                            "No apps found".to_string()
-                        } else {
-                            format!("Installed apps:\n{}", app_list)
-                        }
-                    }]
-                }))
+                Ok(success_result(
+                    if app_list.is_empty() {
+                        "No apps found".to_string()
+                    } else {
+                        format!("Installed apps:\n{}", app_list)
+                    },
+                    serde_json::json!({ "apps": apps }),
+                ))
             }
-            Err(e) => Err(format!("Failed to list apps: {}", e)),
+            Err(e) => Err(ToolError::Execution(format!("Failed to list apps: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -208,6 +394,7 @@ pub fn handle_list_apps(
 /// * `device_id` - Device identifier
 /// * `platform` - Platform: "android" or "ios"
 /// * `filter` - Optional text filter to search for specific elements
+/// * `progress` - Reports the steps of the operation to the client
 ///
 /// # Returns
 /// MCP response with element list (position, size, text, resource ID), or error
@@ -216,10 +403,13 @@ pub fn handle_list_elements(
     device_id: &str,
     platform: &str,
     filter: Option<&str>,
+    progress: Progress,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
-        match manager.list_elements_on_screen(device_id, platform, filter) {
+        match manager.list_elements_on_screen(device_id, platform, filter, progress) {
             Ok(elements) => {
                 let element_list = elements
                     .iter()
@@ -242,24 +432,30 @@ pub fn handle_list_elements(
                     .collect::<Vec<_>>()
                     .join("\n");
 
-                Ok(serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": if element_list.is_empty() {
-                            format!("No elements found (parsed {} elements, all filtered out)", elements.len())
-                        } else {
-                            format!("Screen elements:\n{}", element_list)
-                        }
-                    }]
-                }))
+                Ok(success_result(
+                    if element_list.is_empty() {
+                        format!(
+                            "No elements found (parsed {} elements, all filtered out)",
+                            elements.len()
+                        )
+                    } else {
+                        format!("Screen elements:\n{}", element_list)
+                    },
+                    serde_json::json!({ "elements": elements }),
+                ))
             }
-            Err(e) => Err(format!("Failed to list elements: {}", e)),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to list elements: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
-        let _ = (manager, device_id, platform, filter);
-        Err("Not available in extension mode".to_string())
+        let _ = (manager, device_id, platform, filter, progress);
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -281,6 +477,7 @@ pub fn handle_list_elements(
 /// * `manager` - Mobile device manager
 /// * `device_id` - Device identifier
 /// * `platform` - Platform: "android" or "ios"
+/// * `progress` - Reports the steps of the operation to the client
 ///
 /// # Returns
 /// MCP response with base64-encoded PNG image data, or error if capture fails
@@ -288,27 +485,40 @@ pub fn handle_take_screenshot(
     manager: &mut MobileDeviceManager,
     device_id: &str,
     platform: &str,
+    progress: Progress,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
-        match manager.take_screenshot(device_id, platform) {
+        match manager.take_screenshot(device_id, platform, progress) {
             Ok(screenshot_data) => {
+                progress(90.0, "Encoding screenshot");
                 let base64_data = STANDARD.encode(&screenshot_data);
                 Ok(serde_json::json!({
                     "content": [{
                         "type": "image",
                         "data": base64_data,
                         "mimeType": "image/png"
-                    }]
+                    }],
+                    "structuredContent": {
+                        "mime_type": "image/png",
+                        "size_bytes": screenshot_data.len()
+                    }
                 }))
             }
-            Err(e) => Err(format!("Failed to take screenshot: {}", e)),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to take screenshot: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
-        let _ = (manager, device_id, platform);
-        Err("Not available in extension mode".to_string())
+        let _ = (manager, device_id, platform, progress);
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -322,6 +532,7 @@ pub fn handle_take_screenshot(
 /// * `device_id` - Device identifier
 /// * `platform` - Platform: "android" or "ios"
 /// * `output_path` - File path where screenshot should be saved (e.g., "/tmp/screen.png")
+/// * `progress` - Reports the steps of the operation to the client
 ///
 /// # Returns
 /// MCP response confirming save location, or error if capture/save fails
@@ -330,26 +541,39 @@ pub fn handle_save_screenshot(
     device_id: &str,
     platform: &str,
     output_path: &str,
+    progress: Progress,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
-        match manager.take_screenshot(device_id, platform) {
-            Ok(screenshot_data) => match std::fs::write(output_path, screenshot_data) {
-                Ok(_) => Ok(serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": format!("Screenshot saved to: {}", output_path)
-                    }]
-                })),
-                Err(e) => Err(format!("Failed to save screenshot: {}", e)),
-            },
-            Err(e) => Err(format!("Failed to take screenshot: {}", e)),
+        match manager.take_screenshot(device_id, platform, progress) {
+            Ok(screenshot_data) => {
+                progress(90.0, "Writing file");
+                let size_bytes = screenshot_data.len();
+                match std::fs::write(output_path, screenshot_data) {
+                    Ok(_) => Ok(success_result(
+                        format!("Screenshot saved to: {}", output_path),
+                        serde_json::json!({ "path": output_path, "size_bytes": size_bytes }),
+                    )),
+                    Err(e) => Err(ToolError::Execution(format!(
+                        "Failed to save screenshot: {}",
+                        e
+                    ))),
+                }
+            }
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to take screenshot: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
-        let _ = (manager, device_id, platform, output_path);
-        Err("Not available in extension mode".to_string())
+        let _ = (manager, device_id, platform, output_path, progress);
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -375,22 +599,24 @@ pub fn handle_click_screen(
     x: f64,
     y: f64,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.tap_screen(device_id, platform, x, y) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Clicked at ({}, {}): {}", x, y, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to click: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Clicked at ({}, {}): {}", x, y, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to click: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, x, y);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -405,6 +631,7 @@ pub fn handle_click_screen(
 /// * `platform` - Platform: "android" or "ios"
 /// * `x` - X coordinate in pixels
 /// * `y` - Y coordinate in pixels
+/// * `progress` - Reports the steps of the operation to the client
 ///
 /// # Returns
 /// MCP response confirming double tap, or error
@@ -414,23 +641,26 @@ pub fn handle_double_tap(
     platform: &str,
     x: f64,
     y: f64,
+    progress: Progress,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
-        match manager.double_tap_screen(device_id, platform, x, y) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Double tapped at ({}, {}): {}", x, y, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to double tap: {}", e)),
+        match manager.double_tap_screen(device_id, platform, x, y, progress) {
+            Ok(msg) => Ok(action_result(
+                format!("Double tapped at ({}, {}): {}", x, y, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to double tap: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
-        let _ = (manager, device_id, platform, x, y);
-        Err("Not available in extension mode".to_string())
+        let _ = (manager, device_id, platform, x, y, progress);
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -457,23 +687,28 @@ pub fn handle_long_press(
     y: f64,
     duration: Option<u32>,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         let duration_ms = duration.unwrap_or(1000);
         match manager.long_press_screen(device_id, platform, x, y, duration_ms) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Long pressed at ({}, {}) for {}ms: {}", x, y, duration_ms, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to long press: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!(
+                    "Long pressed at ({}, {}) for {}ms: {}",
+                    x, y, duration_ms, msg
+                ),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to long press: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, x, y, duration);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -506,6 +741,8 @@ pub fn handle_swipe(
     end_y: f64,
     duration: Option<u32>,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         let duration_ms = duration.unwrap_or(300);
@@ -518,13 +755,14 @@ pub fn handle_swipe(
             end_y,
             duration_ms,
         ) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Swiped from ({}, {}) to ({}, {}): {}", start_x, start_y, end_x, end_y, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to swipe: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!(
+                    "Swiped from ({}, {}) to ({}, {}): {}",
+                    start_x, start_y, end_x, end_y, msg
+                ),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to swipe: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
@@ -532,7 +770,9 @@ pub fn handle_swipe(
         let _ = (
             manager, device_id, platform, start_x, start_y, end_x, end_y, duration,
         );
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -563,22 +803,21 @@ pub fn handle_type_keys(
     platform: &str,
     text: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.type_text(device_id, platform, text) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Typed text: {}", msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to type text: {}", e)),
+            Ok(msg) => Ok(action_result(format!("Typed text: {}", msg), &msg)),
+            Err(e) => Err(ToolError::Execution(format!("Failed to type text: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, text);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -602,22 +841,28 @@ pub fn handle_press_button(
     platform: &str,
     button: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+    check_button(manager, platform, button)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.press_button(device_id, platform, button) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Pressed button '{}': {}", button, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to press button: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Pressed button '{}': {}", button, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to press button: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, button);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -650,22 +895,24 @@ pub fn handle_launch_app(
     platform: &str,
     app_id: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.launch_app(device_id, platform, app_id) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Launched app '{}': {}", app_id, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to launch app: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Launched app '{}': {}", app_id, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to launch app: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, app_id);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -689,22 +936,27 @@ pub fn handle_terminate_app(
     platform: &str,
     app_id: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.terminate_app(device_id, platform, app_id) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Terminated app '{}': {}", app_id, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to terminate app: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Terminated app '{}': {}", app_id, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to terminate app: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, app_id);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -719,6 +971,7 @@ pub fn handle_terminate_app(
 /// * `device_id` - Device identifier
 /// * `platform` - Platform: "android" or "ios"
 /// * `app_path` - Local file path to APK or IPA file
+/// * `progress` - Reports the steps of the operation to the client
 ///
 /// # Returns
 /// MCP response confirming installation, or error if file not found/invalid
@@ -727,23 +980,29 @@ pub fn handle_install_app(
     device_id: &str,
     platform: &str,
     app_path: &str,
+    progress: Progress,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
-        match manager.install_app(device_id, platform, app_path) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Installed app from '{}': {}", app_path, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to install app: {}", e)),
+        match manager.install_app(device_id, platform, app_path, progress) {
+            Ok(msg) => Ok(action_result(
+                format!("Installed app from '{}': {}", app_path, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to install app: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
-        let _ = (manager, device_id, platform, app_path);
-        Err("Not available in extension mode".to_string())
+        let _ = (manager, device_id, platform, app_path, progress);
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -766,22 +1025,27 @@ pub fn handle_uninstall_app(
     platform: &str,
     app_id: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.uninstall_app(device_id, platform, app_id) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Uninstalled app '{}': {}", app_id, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to uninstall app: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Uninstalled app '{}': {}", app_id, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to uninstall app: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, app_id);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -811,22 +1075,24 @@ pub fn handle_open_url(
     platform: &str,
     url: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.open_url(device_id, platform, url) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Opened URL '{}': {}", url, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to open URL: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Opened URL '{}': {}", url, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!("Failed to open URL: {}", e))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, url);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
     }
 }
 
@@ -850,21 +1116,216 @@ pub fn handle_set_orientation(
     platform: &str,
     orientation: &str,
 ) -> HandlerResult {
+    check_platform(manager, platform)?;
+    check_orientation(orientation)?;
+
     #[cfg(feature = "native-binary")]
     {
         match manager.set_orientation(device_id, platform, orientation) {
-            Ok(msg) => Ok(serde_json::json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Set orientation to '{}': {}", orientation, msg)
-                }]
-            })),
-            Err(e) => Err(format!("Failed to set orientation: {}", e)),
+            Ok(msg) => Ok(action_result(
+                format!("Set orientation to '{}': {}", orientation, msg),
+                &msg,
+            )),
+            Err(e) => Err(ToolError::Execution(format!(
+                "Failed to set orientation: {}",
+                e
+            ))),
         }
     }
     #[cfg(not(feature = "native-binary"))]
     {
         let _ = (manager, device_id, platform, orientation);
-        Err("Not available in extension mode".to_string())
+        Err(ToolError::Execution(
+            "Not available in extension mode".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_result_sets_is_error() {
+        let result = error_result("Failed to launch app: not installed");
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["content"][0]["type"], "text");
+        assert_eq!(
+            result["content"][0]["text"],
+            "Failed to launch app: not installed"
+        );
+    }
+
+    #[test]
+    fn test_action_result_has_text_and_structured_content() {
+        let result = action_result("Clicked at (10, 20): ok".to_string(), "ok");
+        assert_eq!(result["content"][0]["text"], "Clicked at (10, 20): ok");
+        assert_eq!(result["structuredContent"]["message"], "ok");
+        assert!(result.get("isError").is_none());
+    }
+
+    #[test]
+    fn test_unknown_platform_is_invalid_arguments() {
+        let manager = &MobileDeviceManager::new();
+        assert!(check_platform(manager, "android").is_ok());
+        assert!(check_platform(manager, "ios").is_ok());
+        assert!(matches!(
+            check_platform(manager, "auto"),
+            Err(ToolError::InvalidArguments(message))
+                if message == "Unknown platform: auto (expected \"android\" or \"ios\")"
+        ));
+        assert!(check_platform_filter(manager, "auto").is_ok());
+        assert!(check_platform_filter(manager, "windows").is_err());
+    }
+
+    #[test]
+    fn test_button_checked_per_platform() {
+        let manager = &mut MobileDeviceManager::new();
+        assert!(check_button(manager, "android", "BACK").is_ok());
+        assert!(check_button(manager, "ios", "home").is_ok());
+        assert!(check_button(manager, "android", "search").is_ok());
+        assert!(matches!(
+            check_button(manager, "ios", "back"),
+            Err(ToolError::InvalidArguments(_))
+        ));
+    }
+
+    #[test]
+    fn test_orientation_checked() {
+        assert!(check_orientation("landscape").is_ok());
+        assert!(check_orientation("upside_down").is_err());
+    }
+
+    /// Manager with only the mock backend and its demo device
+    #[cfg(feature = "native-binary")]
+    fn mock_manager() -> MobileDeviceManager {
+        use crate::devices::mock::{MockBackend, MockDevice};
+        use crate::devices::BackendRegistry;
+        use std::sync::Arc;
+
+        let mut backends = BackendRegistry::empty();
+        backends.register(Box::new(MockBackend::new(Arc::new(MockDevice::demo()))));
+        MobileDeviceManager::with_backends(backends)
+    }
+
+    #[cfg(feature = "native-binary")]
+    #[test]
+    fn test_mock_platform_only_when_enabled() {
+        assert!(check_platform(&MobileDeviceManager::new(), "mock").is_err());
+        assert!(check_platform(&mock_manager(), "mock").is_ok());
+    }
+
+    #[cfg(feature = "native-binary")]
+    #[test]
+    fn test_every_handler_against_mock_device() {
+        use crate::devices::no_progress;
+
+        const DEVICE: &str = "mock-device";
+        let manager = &mut mock_manager();
+        let text = |result: HandlerResult| -> String {
+            result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        assert!(text(handle_list_devices(manager, "auto")).contains(DEVICE));
+        assert_eq!(
+            text(handle_get_screen_size(manager, DEVICE, "mock")),
+            "Screen size: 1080x2400 pixels"
+        );
+        assert!(text(handle_list_apps(manager, DEVICE, "mock")).contains("YouTube"));
+        let result = handle_take_screenshot(manager, DEVICE, "mock", &no_progress).unwrap();
+        assert_eq!(result["content"][0]["type"], "image");
+
+        let path = std::env::temp_dir().join(format!("mock-screen-{}.png", std::process::id()));
+        let path = path.to_str().unwrap();
+        handle_save_screenshot(manager, DEVICE, "mock", path, &no_progress).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        // Search on the launcher, then open Settings and go to About phone
+        handle_click_screen(manager, DEVICE, "mock", 100.0, 200.0).unwrap();
+        handle_type_keys(manager, DEVICE, "mock", "weather").unwrap();
+        let elements = text(handle_list_elements(
+            manager,
+            DEVICE,
+            "mock",
+            Some("weather"),
+            &no_progress,
+        ));
+        assert!(elements.contains("search_box"), "{}", elements);
+        handle_double_tap(manager, DEVICE, "mock", 150.0, 450.0, &no_progress).unwrap();
+        handle_long_press(manager, DEVICE, "mock", 500.0, 650.0, None).unwrap();
+        assert!(text(handle_list_elements(
+            manager,
+            DEVICE,
+            "mock",
+            None,
+            &no_progress
+        ))
+        .contains("Android version"));
+        handle_swipe(manager, DEVICE, "mock", 500.0, 1500.0, 500.0, 500.0, None).unwrap();
+        handle_press_button(manager, DEVICE, "mock", "back").unwrap();
+        handle_press_button(manager, DEVICE, "mock", "home").unwrap();
+
+        handle_launch_app(manager, DEVICE, "mock", "com.google.android.youtube").unwrap();
+        handle_terminate_app(manager, DEVICE, "mock", "com.google.android.youtube").unwrap();
+        handle_install_app(
+            manager,
+            DEVICE,
+            "mock",
+            "/builds/com.example.shop.apk",
+            &no_progress,
+        )
+        .unwrap();
+        handle_uninstall_app(manager, DEVICE, "mock", "com.example.shop").unwrap();
+        handle_open_url(manager, DEVICE, "mock", "https://example.com").unwrap();
+
+        handle_set_orientation(manager, DEVICE, "mock", "landscape").unwrap();
+        assert!(text(handle_get_orientation(manager, DEVICE, "mock")).contains("landscape"));
+
+        assert!(matches!(
+            handle_launch_app(manager, DEVICE, "mock", "com.example.missing"),
+            Err(ToolError::Execution(_))
+        ));
+    }
+
+    #[cfg(feature = "native-binary")]
+    #[test]
+    fn test_auto_platform_resolved_from_device_id() {
+        let mut manager = mock_manager();
+        assert_eq!(manager.resolve_platform("mock-device").unwrap(), "mock");
+        assert_eq!(
+            manager.get_screen_size("mock-device", "auto").unwrap(),
+            (1080, 2400)
+        );
+
+        let error = manager.resolve_platform("emulator-5554").unwrap_err();
+        assert_eq!(
+            error,
+            "Device not found: emulator-5554. Available devices: mock-device"
+        );
+    }
+
+    #[cfg(feature = "native-binary")]
+    #[test]
+    fn test_select_device_by_alias() {
+        use std::collections::HashMap;
+
+        let mut manager = mock_manager();
+        assert_eq!(manager.only_device("auto").unwrap(), "mock-device");
+
+        let selection = DeviceSelection::new(HashMap::from([(
+            "phone".to_string(),
+            "mock-device".to_string(),
+        )]));
+        let result = handle_select_device(&mut manager, &selection, "phone").unwrap();
+        assert_eq!(result["structuredContent"]["device"]["id"], "mock-device");
+        assert_eq!(selection.target(None).as_deref(), Some("mock-device"));
+
+        assert!(matches!(
+            handle_select_device(&mut manager, &selection, "pixel"),
+            Err(ToolError::Execution(_))
+        ));
     }
 }
@@ -1,8 +1,11 @@
 // mobile-mcp-zed-extension/src/tools/mod.rs
 // Tools module for mobile device automation
 
+pub mod args;
+pub mod confirmation;
 pub mod definitions;
 pub mod handlers;
+pub mod validation;
 
-pub use definitions::{get_all_tools, ToolDefinition};
+pub use definitions::{find_tool, get_all_tools, ToolDefinition};
 pub use handlers::*;
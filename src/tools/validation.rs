@@ -0,0 +1,261 @@
+// mobile-mcp-zed-extension/src/tools/validation.rs
+// Tool Argument Validation
+//
+// Every tools/call is checked against the tool's `inputSchema` before it is
+// dispatched, so a misspelled argument (`deviceId`), a platform outside the
+// enum or a negative coordinate is rejected with an error naming the JSON
+// path and the expected value, instead of silently falling back to a default
+// and acting on the wrong device.
+//
+// Only the JSON Schema keywords that args.rs generates are checked: type,
+// enum, minimum/maximum, required, properties, additionalProperties and
+// items.
+
+use serde_json::Value;
+use std::fmt;
+
+/// One argument that doesn't match the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// JSON path of the offending value (e.g., "$.platform")
+    pub path: String,
+    /// What was expected there
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate tool arguments against an input schema
+///
+/// Omitted arguments are validated as an empty object, like they are decoded.
+///
+/// # Arguments
+/// * `schema` - The tool's input schema
+/// * `args` - Arguments from tools/call
+///
+/// # Returns
+/// Ok, or every mismatch found
+pub fn validate(schema: &Value, args: &Value) -> Result<(), Vec<ValidationError>> {
+    let empty = Value::Object(Default::default());
+    let args = if args.is_null() { &empty } else { args };
+
+    let mut errors = Vec::new();
+    check(schema, args, "$", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+    let mut error = |message: String| {
+        errors.push(ValidationError {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(single) => vec![single.as_str()],
+            Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            error(format!(
+                "expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+            // Nothing else can be checked meaningfully
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            error(format!(
+                "expected one of {}, got {}",
+                allowed.join(", "),
+                value
+            ));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                error(format!("expected a value >= {}, got {}", minimum, value));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                error(format!("expected a value <= {}, got {}", maximum, value));
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        check_object(schema, object, path, errors);
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check(items, item, &format!("{}[{}]", path, index), errors);
+        }
+    }
+}
+
+fn check_object(
+    schema: &Value,
+    object: &serde_json::Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ValidationError>,
+) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                errors.push(ValidationError {
+                    path: format!("{}.{}", path, name),
+                    message: "missing required argument".to_string(),
+                });
+            }
+        }
+    }
+
+    for (name, value) in object {
+        let property_path = format!("{}.{}", path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property) => check(property, value, &property_path, errors),
+            None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                let known: Vec<&str> = properties
+                    .map(|properties| properties.keys().map(String::as_str).collect())
+                    .unwrap_or_default();
+                errors.push(ValidationError {
+                    path: property_path,
+                    message: unknown_argument_message(name, &known),
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+/// Explain an unknown argument, pointing out the likely intended one
+fn unknown_argument_message(name: &str, known: &[&str]) -> String {
+    // deviceId, DeviceID and device-id all mean device_id
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+    match known.iter().find(|k| normalize(k) == normalize(name)) {
+        Some(intended) => format!("unknown argument, did you mean \"{}\"?", intended),
+        None if known.is_empty() => "unknown argument, this tool takes none".to_string(),
+        None => format!("unknown argument, expected one of: {}", known.join(", ")),
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::args::{input_schema, ListDevicesArgs, LongPressArgs};
+    use serde_json::json;
+
+    fn errors(args: Value) -> Vec<String> {
+        validate(&input_schema::<LongPressArgs>(), &args)
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        assert!(errors(json!({"device_id": "emulator-5554", "x": 10, "y": 20.5})).is_empty());
+        assert!(errors(json!({
+            "device_id": "emulator-5554",
+            "platform": "android",
+            "x": 0,
+            "y": 0,
+            "duration": 500
+        }))
+        .is_empty());
+    }
+
+    #[test]
+    fn test_misspelled_argument_is_rejected() {
+        assert_eq!(
+            errors(json!({"deviceId": "emulator-5554", "x": 1, "y": 1})),
+            vec!["$.deviceId: unknown argument, did you mean \"device_id\"?"]
+        );
+    }
+
+    #[test]
+    fn test_enum_and_range() {
+        assert_eq!(
+            errors(json!({"device_id": "a", "platform": "windows", "x": -1, "y": 2})),
+            vec![
+                "$.platform: expected one of \"android\", \"ios\", got \"windows\"",
+                "$.x: expected a value >= 0, got -1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        assert_eq!(
+            errors(json!({"device_id": "a", "x": "10", "y": 2, "duration": 1.5})),
+            vec![
+                "$.duration: expected integer, got number",
+                "$.x: expected number, got string"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tool_without_arguments() {
+        let schema = input_schema::<ListDevicesArgs>();
+        assert!(validate(&schema, &Value::Null).is_ok());
+        let errors = validate(&schema, &json!({"device": "x"})).unwrap_err();
+        assert_eq!(
+            errors[0].message,
+            "unknown argument, expected one of: platform"
+        );
+    }
+}
@@ -0,0 +1,427 @@
+// mobile-mcp-zed-extension/src/transport/daemon.rs
+// Shared Daemon on a Unix Domain Socket
+//
+// Every Zed window starts its own server process. With `--proxy`, that
+// process is only a thin pipe between its stdio and a long-lived daemon
+// (`--daemon`) listening on a Unix socket. The proxy starts the daemon if it
+// isn't running yet. Each connection is a separate MCP session, but all of
+// them share the daemon's devices (see SharedDevices): one device registry,
+// one cache per Android device, and locks so two windows never drive the
+// same emulator at once.
+//
+// The settings (platform, aliases, auto-approve, debug) are those of the
+// proxy that started the daemon. Windows connecting later get them too, even
+// if their own settings differ; stop the daemon to pick up new settings.
+//
+// The daemon exits once it has had no clients for DAEMON_IDLE_TIMEOUT.
+
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::mcp::logging::{self, LogLevel};
+use crate::mcp::MessageWriter;
+use crate::types::MobileDeviceMcpSettings;
+use crate::{McpServer, SharedDevices};
+
+/// How long the daemon keeps running without clients
+const DAEMON_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How long a proxy waits for a daemon it started to accept connections
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default socket path, private to the current user
+///
+/// Uses $XDG_RUNTIME_DIR when set, otherwise a directory in the temp
+/// directory that only the current user can enter (see private_dir).
+pub fn default_socket_path() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return Ok(Path::new(&dir).join("mobile-device-mcp.sock"));
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+    let dir = std::env::temp_dir().join(format!("mobile-device-mcp-{}", user));
+    private_dir(&dir)?;
+    Ok(dir.join("daemon.sock"))
+}
+
+/// Create a directory only its owner can use, or check an existing one
+///
+/// Another user could create the directory first and put their own socket
+/// in it. Changing its permissions only works for the owner, so that fails
+/// for a directory somebody else created.
+fn private_dir(dir: &Path) -> io::Result<()> {
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        Err(_) => {}
+    }
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a directory", dir.display()),
+        ));
+    }
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("{} is not owned by the current user: {}", dir.display(), e),
+        )
+    })
+}
+
+/// Run the daemon until it has been idle for DAEMON_IDLE_TIMEOUT
+///
+/// # Arguments
+/// * `socket` - Path of the Unix socket to listen on
+/// * `settings` - Settings for every session
+pub async fn serve(socket: &Path, settings: MobileDeviceMcpSettings) -> io::Result<()> {
+    let listener = bind(socket).await?;
+    logging::log(
+        LogLevel::Info,
+        logging::PROTOCOL,
+        &format!("Daemon listening on {}", socket.display()),
+    );
+
+    let devices = SharedDevices::default();
+    let clients = Arc::new(AtomicUsize::new(0));
+
+    loop {
+        let accepted = tokio::time::timeout(DAEMON_IDLE_TIMEOUT, listener.accept()).await;
+        let stream = match accepted {
+            Ok(Ok((stream, _))) => stream,
+            Ok(Err(e)) => {
+                logging::log(
+                    LogLevel::Error,
+                    logging::PROTOCOL,
+                    &format!("Failed to accept connection: {}", e),
+                );
+                continue;
+            }
+            Err(_) if clients.load(Ordering::SeqCst) == 0 => break,
+            Err(_) => continue,
+        };
+
+        clients.fetch_add(1, Ordering::SeqCst);
+        let clients = Arc::clone(&clients);
+        let settings = settings.clone();
+        let devices = devices.clone();
+        tokio::spawn(async move {
+            serve_client(stream, settings, &devices).await;
+            clients.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    let _ = std::fs::remove_file(socket);
+    Ok(())
+}
+
+/// Run one MCP session over a client connection
+async fn serve_client(
+    stream: UnixStream,
+    settings: MobileDeviceMcpSettings,
+    devices: &SharedDevices,
+) {
+    let (input, mut output) = stream.into_split();
+    let (writer, mut messages) = MessageWriter::channel();
+
+    let forward = tokio::spawn(async move {
+        while let Some(mut line) = messages.recv().await {
+            line.push('\n');
+            if output.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut server = McpServer::new(settings, writer, devices);
+    server.run(BufReader::new(input)).await;
+    // Dropping the session drops the last writer, which ends the forwarder
+    drop(server);
+    let _ = forward.await;
+}
+
+/// Bind the socket, replacing a stale one left by a daemon that crashed
+async fn bind(socket: &Path) -> io::Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).await.is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("A daemon is already listening on {}", socket.display()),
+            ));
+        }
+        std::fs::remove_file(socket)?;
+    }
+
+    let listener = UnixListener::bind(socket)?;
+    // Other users must not control this user's devices
+    std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Pipe stdio to the daemon, starting it if needed
+///
+/// Returns once the daemon closes the connection (after stdin closed or
+/// the client shut the session down).
+pub async fn proxy(socket: &Path) -> io::Result<()> {
+    let stream = match UnixStream::connect(socket).await {
+        Ok(stream) => stream,
+        Err(_) => {
+            start_daemon(socket)?;
+            connect_when_ready(socket).await?
+        }
+    };
+    let (mut from_daemon, mut to_daemon) = stream.into_split();
+
+    tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut tokio::io::stdin(), &mut to_daemon).await;
+        // Tell the daemon the client is gone so it ends the session
+        let _ = to_daemon.shutdown().await;
+    });
+
+    let mut stdout = tokio::io::stdout();
+    tokio::io::copy(&mut from_daemon, &mut stdout).await?;
+    stdout.flush().await
+}
+
+/// Start a detached daemon on the socket
+///
+/// The daemon gets its own process group so it survives the window (and
+/// the proxy) that started it.
+fn start_daemon(socket: &Path) -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    Command::new(exe)
+        .arg("--daemon")
+        .arg("--socket")
+        .arg(socket)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    Ok(())
+}
+
+/// Connect to a daemon that is starting up
+async fn connect_when_ready(socket: &Path) -> io::Result<UnixStream> {
+    let deadline = tokio::time::Instant::now() + DAEMON_START_TIMEOUT;
+    loop {
+        match UnixStream::connect(socket).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if tokio::time::Instant::now() >= deadline => {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("Daemon did not start on {}: {}", socket.display(), e),
+                ))
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    fn temp_socket(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "mobile-mcp-test-{}-{}.sock",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_session_over_socket() {
+        let socket = temp_socket("session");
+        let listener = bind(&socket).await.unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_client(
+                stream,
+                MobileDeviceMcpSettings::default(),
+                &SharedDevices::default(),
+            )
+            .await;
+        });
+
+        let mut client = UnixStream::connect(&socket).await.unwrap();
+        client
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n")
+            .await
+            .unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        server.await.unwrap();
+        let _ = std::fs::remove_file(&socket);
+
+        assert_eq!(response, "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n");
+    }
+
+    #[tokio::test]
+    async fn test_stale_socket_is_replaced() {
+        let socket = temp_socket("stale");
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+
+        let listener = bind(&socket).await.unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // A live daemon is not replaced
+        assert_eq!(
+            bind(&socket).await.unwrap_err().kind(),
+            io::ErrorKind::AddrInUse
+        );
+        drop(listener);
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    /// Send requests over a new connection and return the responses
+    async fn session(socket: &Path, requests: &[&str]) -> Vec<String> {
+        let stream = UnixStream::connect(socket).await.unwrap();
+        let (output, mut input) = stream.into_split();
+        let mut output = BufReader::new(output);
+        let mut responses = Vec::new();
+        for request in requests {
+            input.write_all(request.as_bytes()).await.unwrap();
+            input.write_all(b"\n").await.unwrap();
+            let mut line = String::new();
+            output.read_line(&mut line).await.unwrap();
+            responses.push(line);
+        }
+        responses
+    }
+
+    #[tokio::test]
+    async fn test_two_clients_share_the_daemon() {
+        use crate::devices::mock::{MockBackend, MockDevice};
+        use crate::devices::BackendRegistry;
+        use crate::MobileDeviceManager;
+
+        let mut backends = BackendRegistry::empty();
+        backends.register(Box::new(MockBackend::new(Arc::new(MockDevice::demo()))));
+        let devices = SharedDevices::new(MobileDeviceManager::with_backends(backends));
+
+        let socket = temp_socket("shared");
+        let listener = bind(&socket).await.unwrap();
+        let daemon_devices = devices.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let devices = daemon_devices.clone();
+                tokio::spawn(async move {
+                    serve_client(stream, MobileDeviceMcpSettings::default(), &devices).await
+                });
+            }
+        });
+
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"mobile_device_mcp_list_available_devices","arguments":{}}}"#;
+        let responses = session(&socket, &[initialize, list]).await;
+        assert!(responses[1].contains("mock-device"));
+
+        // The first window's listing is known to the daemon, and so to the
+        // second window
+        assert_eq!(
+            devices
+                .manager
+                .devices
+                .platform_of("mock-device")
+                .as_deref(),
+            Some("mock")
+        );
+        let size = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"mobile_device_mcp_get_screen_size","arguments":{"device_id":"mock-device"}}}"#;
+        let responses = session(&socket, &[initialize, size]).await;
+        assert!(responses[1].contains("Screen size"));
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[tokio::test]
+    async fn test_selected_device_used_without_arguments() {
+        use crate::devices::mock::{MockBackend, MockDevice};
+        use crate::devices::BackendRegistry;
+        use crate::MobileDeviceManager;
+
+        let mut backends = BackendRegistry::empty();
+        backends.register(Box::new(MockBackend::new(Arc::new(MockDevice::demo()))));
+        backends.register(Box::new(
+            MockBackend::new(Arc::new(MockDevice::demo_with_id("mock-tablet")))
+                .on_platform("tablet"),
+        ));
+        let devices = SharedDevices::new(MobileDeviceManager::with_backends(backends));
+
+        let socket = temp_socket("selected");
+        let listener = bind(&socket).await.unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_client(stream, MobileDeviceMcpSettings::default(), &devices).await
+        });
+
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let select = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"mobile_device_mcp_select_device","arguments":{"device_id":"mock-tablet"}}}"#;
+        // No "arguments" at all
+        let size = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"mobile_device_mcp_get_screen_size"}}"#;
+        let responses = session(&socket, &[initialize, select, size]).await;
+        assert!(responses[1].contains("Selected"), "{}", responses[1]);
+        assert!(!responses[2].contains("isError"), "{}", responses[2]);
+        assert!(responses[2].contains("Screen size"), "{}", responses[2]);
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[tokio::test]
+    async fn test_completions_need_2025_03_26() {
+        let socket = temp_socket("completions");
+        let listener = bind(&socket).await.unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    serve_client(
+                        stream,
+                        MobileDeviceMcpSettings::default(),
+                        &SharedDevices::default(),
+                    )
+                    .await
+                });
+            }
+        });
+
+        let complete = r#"{"jsonrpc":"2.0","id":2,"method":"completion/complete","params":{"ref":{"type":"ref/tool","name":"mobile_device_mcp_set_orientation"},"argument":{"name":"orientation","value":"p"}}}"#;
+        let old = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05"}}"#;
+        let responses = session(&socket, &[old, complete]).await;
+        assert!(responses[1].contains("-32601"), "{}", responses[1]);
+
+        let new = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#;
+        let responses = session(&socket, &[new, complete]).await;
+        assert!(responses[1].contains("portrait"), "{}", responses[1]);
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    #[test]
+    fn test_private_dir() {
+        let dir = std::env::temp_dir().join(format!("mobile-mcp-test-dir-{}", std::process::id()));
+        private_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // An existing directory is made private again
+        private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        std::fs::remove_dir(&dir).unwrap();
+
+        let file = dir.with_extension("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(private_dir(&file).is_err());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,747 @@
+// mobile-mcp-zed-extension/src/transport/http.rs
+// Streamable HTTP Transport
+//
+// Serves MCP on a single endpoint (/mcp) as described by the Streamable HTTP
+// transport of the MCP spec:
+// - POST sends one JSON-RPC message. Requests are answered with an SSE stream
+//   (progress notifications and requests to the client such as elicitation,
+//   then the response) or plain JSON if the client doesn't accept event
+//   streams; notifications get 202 Accepted.
+// - GET opens an SSE stream for server-initiated messages (resource updates).
+// - DELETE ends the session.
+//
+// "initialize" creates a session and returns its id in the Mcp-Session-Id
+// header, which the client sends with every later request. Each session is a
+// regular McpServer writing to a channel; a router task takes that channel
+// apart and sends every message to the stream waiting for it. All sessions
+// share the devices (see SharedDevices), so two clients don't drive one
+// device at once. A session that sees no requests for SESSION_IDLE_TIMEOUT
+// (and has no open stream) is closed, since clients may leave without DELETE.
+//
+// Only requests addressed to a loopback name (or an allowed host) are
+// served, so a page on another site can't reach the server through DNS
+// rebinding.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use serde_json::Value;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+
+use crate::mcp::{
+    ClientRequests, McpError, McpErrorResponse, McpRequest, MessageWriter, ProtocolVersion,
+};
+use crate::types::MobileDeviceMcpSettings;
+use crate::{McpServer, SharedDevices};
+
+/// Status and message of a rejected HTTP request
+type Rejection = (StatusCode, String);
+
+/// Header carrying the session id
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Header carrying the negotiated protocol version (2025-06-18 and later)
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// How long a session is kept without requests or open streams
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often idle sessions are looked for
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// State shared by all HTTP requests
+#[derive(Clone)]
+struct AppState {
+    settings: MobileDeviceMcpSettings,
+    devices: SharedDevices,
+    sessions: Arc<Mutex<HashMap<String, Arc<Session>>>>,
+    /// Host header values requests may carry (host:port)
+    allowed_hosts: Arc<Vec<String>>,
+}
+
+/// One MCP session
+struct Session {
+    server: tokio::sync::Mutex<McpServer>,
+    routes: Arc<Mutex<Routes>>,
+    /// When the client last sent something
+    last_seen: Mutex<Instant>,
+}
+
+impl Session {
+    /// Whether the client is neither waiting for anything nor sending
+    fn is_idle(&self, now: Instant) -> bool {
+        let routes = lock(&self.routes);
+        let streaming = !routes.requests.is_empty()
+            || routes
+                .standalone
+                .as_ref()
+                .is_some_and(|stream| !stream.is_closed());
+        !streaming && now.duration_since(*lock(&self.last_seen)) >= SESSION_IDLE_TIMEOUT
+    }
+}
+
+/// Where the messages a session writes should go
+#[derive(Default)]
+struct Routes {
+    /// Streams of POST requests waiting for their response, by request id
+    requests: HashMap<String, RequestStream>,
+    /// Request id for each progress token
+    progress: HashMap<String, String>,
+    /// The GET stream for messages unrelated to a request
+    standalone: Option<UnboundedSender<String>>,
+    /// The session's requests to the client, to find the call each one was
+    /// made for
+    client_requests: ClientRequests,
+}
+
+/// Stream of a POST request waiting for its response
+struct RequestStream {
+    sender: UnboundedSender<String>,
+    /// An SSE stream; a plain JSON reply only carries the response
+    events: bool,
+}
+
+impl Routes {
+    /// Send a message to the stream waiting for it
+    ///
+    /// Responses end their request's stream, progress notifications go to
+    /// the request that carries the token and requests to the client
+    /// (elicitation, roots) to the request they were made for. Anything else,
+    /// or anything whose request can't take it, goes to the GET stream. If
+    /// the client hasn't opened one, notifications are dropped and requests
+    /// fail at once, since no answer could ever come.
+    fn route(&mut self, line: String) {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            return;
+        };
+
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            let id = message.get("id").unwrap_or(&Value::Null).to_string();
+            if let Some(stream) = self.requests.remove(&id) {
+                let _ = stream.sender.send(line);
+            }
+            self.progress.retain(|_, request| *request != id);
+            return;
+        };
+
+        let client_request = message.get("id").map(Value::to_string);
+        let origin = match &client_request {
+            Some(id) => self.client_requests.origin_of(id),
+            None if method == "notifications/progress" => {
+                let token = message["params"]["progressToken"].to_string();
+                self.progress.get(&token).cloned()
+            }
+            None => None,
+        };
+        let stream = origin
+            .and_then(|id| self.requests.get(&id))
+            .filter(|stream| stream.events);
+        let line = match stream {
+            Some(stream) => match stream.sender.send(line) {
+                Ok(()) => return,
+                Err(unsent) => unsent.0,
+            },
+            None => line,
+        };
+
+        if let Some(stream) = &self.standalone {
+            if stream.send(line).is_ok() {
+                return;
+            }
+            self.standalone = None;
+        }
+        if let Some(id) = client_request {
+            self.client_requests.fail(
+                &id,
+                &format!("The client has no open stream to receive {}", method),
+            );
+        }
+    }
+
+    /// Stop waiting for a request (it was cancelled)
+    fn forget(&mut self, id: &Value) {
+        let id = id.to_string();
+        self.requests.remove(&id);
+        self.progress.retain(|_, request| *request != id);
+    }
+}
+
+/// Serve MCP over HTTP until the process is stopped
+///
+/// # Arguments
+/// * `addr` - Address to listen on (e.g., 127.0.0.1:8080)
+/// * `settings` - Settings for every session
+/// * `allowed_hosts` - Host names (or host:port) clients may use besides
+///   the loopback names and the bound address
+pub async fn serve(
+    addr: SocketAddr,
+    settings: MobileDeviceMcpSettings,
+    allowed_hosts: &[String],
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+
+    let state = AppState {
+        settings,
+        devices: SharedDevices::default(),
+        sessions: Arc::default(),
+        allowed_hosts: Arc::new(host_allowlist(local_addr, allowed_hosts)),
+    };
+    tokio::spawn(expire_idle_sessions(state.clone()));
+    let app = Router::new()
+        .route(
+            "/mcp",
+            post(handle_post).get(handle_get).delete(handle_delete),
+        )
+        .with_state(state);
+
+    eprintln!("MCP server listening on http://{}/mcp", local_addr);
+    axum::serve(listener, app).await
+}
+
+/// Host header values that address this server
+///
+/// The loopback names and the bound address (unless it is 0.0.0.0 or ::)
+/// with the bound port, plus the extra hosts; those get the port too unless
+/// they name one.
+fn host_allowlist(local_addr: SocketAddr, extra: &[String]) -> Vec<String> {
+    let port = local_addr.port();
+    let mut hosts: Vec<String> = ["localhost", "127.0.0.1", "[::1]"]
+        .iter()
+        .map(|host| format!("{}:{}", host, port))
+        .collect();
+    if !local_addr.ip().is_unspecified() {
+        hosts.push(local_addr.to_string());
+    }
+    for host in extra {
+        let has_port = host
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| !port.is_empty() && !port.contains(']'));
+        if has_port {
+            hosts.push(host.clone());
+        } else {
+            hosts.push(format!("{}:{}", host, port));
+        }
+    }
+    hosts
+}
+
+/// Close sessions whose client left without DELETE
+async fn expire_idle_sessions(state: AppState) {
+    let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+        let idle: Vec<String> = lock(&state.sessions)
+            .iter()
+            .filter(|(_, session)| session.is_idle(now))
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in idle {
+            remove_session(&state, &session_id).await;
+        }
+    }
+}
+
+/// POST: one JSON-RPC message from the client
+async fn handle_post(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    if let Err(rejection) = check_origin(&headers, &state.allowed_hosts) {
+        return rejection.into_response();
+    }
+
+    let Ok(body) = std::str::from_utf8(&body) else {
+        return json_rpc_error(
+            StatusCode::BAD_REQUEST,
+            McpError::parse_error("Parse error: body is not valid UTF-8"),
+        );
+    };
+    // Responses answer the session's own requests (elicitation)
+    if is_response(body) {
+        return match find_session(&state, &headers) {
+            Ok((_, session)) => {
+                session.server.lock().await.handle_message(body).await;
+                StatusCode::ACCEPTED.into_response()
+            }
+            Err(rejection) => rejection.into_response(),
+        };
+    }
+
+    let request = match McpRequest::from_json(body) {
+        Ok(request) => request,
+        Err(response) => return (StatusCode::BAD_REQUEST, axum::Json(response)).into_response(),
+    };
+
+    let (session_id, session) = if request.method == "initialize" {
+        new_session(&state)
+    } else {
+        match find_session(&state, &headers) {
+            Ok(found) => found,
+            Err(rejection) => return rejection.into_response(),
+        }
+    };
+
+    if let Some(id) = request.id.clone() {
+        return handle_request(&state, session_id, session, request, id, body, &headers).await;
+    }
+
+    if request.method == "notifications/cancelled" {
+        // The client no longer reads the cancelled request's stream
+        let id = request.params.as_ref().and_then(|p| p.get("requestId"));
+        if let Some(id) = id {
+            lock(&session.routes).forget(id);
+        }
+    }
+    session.server.lock().await.handle_message(body).await;
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// Run a request and stream its messages back
+async fn handle_request(
+    state: &AppState,
+    session_id: String,
+    session: Arc<Session>,
+    request: McpRequest,
+    id: Value,
+    body: &str,
+    headers: &HeaderMap,
+) -> Response {
+    let events = accepts_event_stream(headers);
+    let (sender, receiver) = mpsc::unbounded_channel();
+    {
+        let mut routes = lock(&session.routes);
+        let token = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("progressToken"));
+        if let Some(token) = token {
+            routes.progress.insert(token.to_string(), id.to_string());
+        }
+        routes
+            .requests
+            .insert(id.to_string(), RequestStream { sender, events });
+    }
+
+    let is_initialize = request.method == "initialize";
+    let keep_session = {
+        let mut server = session.server.lock().await;
+        let keep_session = server.handle_message(body).await;
+        // A failed initialize leaves nothing behind
+        if is_initialize && !server.is_initialized() {
+            server.close().await;
+            false
+        } else {
+            keep_session
+        }
+    };
+    if is_initialize && keep_session {
+        lock(&state.sessions).insert(session_id.clone(), Arc::clone(&session));
+    } else if !keep_session {
+        remove_session(state, &session_id).await;
+    }
+
+    let mut response = if events {
+        let events = UnboundedReceiverStream::new(receiver)
+            .map(|line| Ok::<_, Infallible>(Event::default().event("message").data(line)));
+        Sse::new(events).into_response()
+    } else {
+        json_response(receiver).await
+    };
+
+    if is_initialize && keep_session {
+        if let Ok(value) = HeaderValue::from_str(&session_id) {
+            response.headers_mut().insert(SESSION_HEADER, value);
+        }
+    }
+    response
+}
+
+/// Wait for a request's response and return it as plain JSON
+async fn json_response(mut receiver: UnboundedReceiver<String>) -> Response {
+    while let Some(line) = receiver.recv().await {
+        let is_response = serde_json::from_str::<Value>(&line)
+            .map(|message| message.get("method").is_none())
+            .unwrap_or(false);
+        if is_response {
+            return ([(header::CONTENT_TYPE, "application/json")], line).into_response();
+        }
+    }
+    // Cancelled: there is no response to send
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// GET: stream of server-initiated messages
+async fn handle_get(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(rejection) = check_origin(&headers, &state.allowed_hosts) {
+        return rejection.into_response();
+    }
+    if !accepts_event_stream(&headers) {
+        return StatusCode::METHOD_NOT_ALLOWED.into_response();
+    }
+    let (_, session) = match find_session(&state, &headers) {
+        Ok(found) => found,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    lock(&session.routes).standalone = Some(sender);
+
+    let events = UnboundedReceiverStream::new(receiver)
+        .map(|line| Ok::<_, Infallible>(Event::default().event("message").data(line)));
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// DELETE: the client is done with the session
+async fn handle_delete(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Err(rejection) = check_origin(&headers, &state.allowed_hosts) {
+        return rejection.into_response();
+    }
+    match find_session(&state, &headers) {
+        Ok((session_id, _)) => {
+            remove_session(&state, &session_id).await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+/// Create a session and its router task
+///
+/// The session is only stored (and reachable by its id) once initialize
+/// succeeded.
+fn new_session(state: &AppState) -> (String, Arc<Session>) {
+    let (writer, mut messages) = MessageWriter::channel();
+    let server = McpServer::new(state.settings.clone(), writer, &state.devices);
+    let routes = Routes {
+        client_requests: server.client_requests.clone(),
+        ..Routes::default()
+    };
+    let session = Arc::new(Session {
+        server: tokio::sync::Mutex::new(server),
+        routes: Arc::new(Mutex::new(routes)),
+        last_seen: Mutex::new(Instant::now()),
+    });
+
+    let routes = Arc::clone(&session.routes);
+    tokio::spawn(async move {
+        while let Some(line) = messages.recv().await {
+            lock(&routes).route(line);
+        }
+    });
+
+    (uuid::Uuid::new_v4().to_string(), session)
+}
+
+/// Look up the session named by the request headers
+///
+/// A missing session id is a bad request; an unknown one (expired or
+/// deleted) is 404 so the client knows to initialize again.
+fn find_session(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(String, Arc<Session>), Rejection> {
+    if let Some(version) = header_str(headers, PROTOCOL_VERSION_HEADER) {
+        if !ProtocolVersion::ALL.iter().any(|v| v.as_str() == version) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported protocol version: {}", version),
+            ));
+        }
+    }
+
+    let Some(session_id) = header_str(headers, SESSION_HEADER) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Missing Mcp-Session-Id header".to_string(),
+        ));
+    };
+    let session = lock(&state.sessions).get(session_id).cloned();
+    match session {
+        Some(session) => {
+            *lock(&session.last_seen) = Instant::now();
+            Ok((session_id.to_string(), session))
+        }
+        None => Err((StatusCode::NOT_FOUND, "Unknown session".to_string())),
+    }
+}
+
+/// Forget a session and cancel whatever it still runs
+async fn remove_session(state: &AppState, session_id: &str) {
+    let session = lock(&state.sessions).remove(session_id);
+    if let Some(session) = session {
+        session.server.lock().await.close().await;
+    }
+}
+
+/// Reject requests from other sites (DNS rebinding protection)
+///
+/// A page on another site whose name resolves to this machine sends its own
+/// name as Host (and Origin), so both must be one of the allowed hosts.
+/// Non-browser clients send no Origin header.
+fn check_origin(headers: &HeaderMap, allowed_hosts: &[String]) -> Result<(), Rejection> {
+    let allowed =
+        |host: Option<&str>| host.is_some_and(|host| allowed_hosts.iter().any(|h| h == host));
+
+    if !allowed(header_str(headers, header::HOST.as_str())) {
+        return Err((StatusCode::FORBIDDEN, "Host not allowed".to_string()));
+    }
+    let Some(origin) = header_str(headers, header::ORIGIN.as_str()) else {
+        return Ok(());
+    };
+    let origin_host = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"));
+    if allowed(origin_host) {
+        return Ok(());
+    }
+    Err((StatusCode::FORBIDDEN, "Origin not allowed".to_string()))
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    header_str(headers, header::ACCEPT.as_str())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// Whether a body is a JSON-RPC response (result or error, no method)
+fn is_response(body: &str) -> bool {
+    serde_json::from_str::<Value>(body)
+        .map(|message| {
+            message.get("method").is_none()
+                && (message.get("result").is_some() || message.get("error").is_some())
+        })
+        .unwrap_or(false)
+}
+
+fn json_rpc_error(status: StatusCode, error: McpError) -> Response {
+    (
+        status,
+        axum::Json(McpErrorResponse::from_error(Value::Null, error)),
+    )
+        .into_response()
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes_with_request(id: &str, token: Option<&str>) -> (Routes, UnboundedReceiver<String>) {
+        let mut routes = Routes::default();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        routes.requests.insert(
+            id.to_string(),
+            RequestStream {
+                sender,
+                events: true,
+            },
+        );
+        if let Some(token) = token {
+            routes.progress.insert(token.to_string(), id.to_string());
+        }
+        (routes, receiver)
+    }
+
+    #[test]
+    fn test_response_ends_request_stream() {
+        let (mut routes, mut stream) = routes_with_request("7", Some("\"tok\""));
+        routes.route(r#"{"jsonrpc":"2.0","id":7,"result":{}}"#.to_string());
+
+        assert!(stream.try_recv().unwrap().contains("\"result\""));
+        assert!(routes.requests.is_empty() && routes.progress.is_empty());
+    }
+
+    #[test]
+    fn test_progress_goes_to_its_request() {
+        let (mut routes, mut stream) = routes_with_request("7", Some("\"tok\""));
+        let (standalone, mut standalone_stream) = mpsc::unbounded_channel();
+        routes.standalone = Some(standalone);
+
+        routes.route(
+            r#"{"jsonrpc":"2.0","method":"notifications/progress","params":{"progressToken":"tok","progress":10}}"#
+                .to_string(),
+        );
+        routes.route(
+            r#"{"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri":"device://list"}}"#
+                .to_string(),
+        );
+
+        assert!(stream
+            .try_recv()
+            .unwrap()
+            .contains("notifications/progress"));
+        assert!(stream.try_recv().is_err());
+        assert!(standalone_stream
+            .try_recv()
+            .unwrap()
+            .contains("resources/updated"));
+    }
+
+    #[test]
+    fn test_forget_cancelled_request() {
+        let (mut routes, mut stream) = routes_with_request("\"a\"", None);
+        routes.forget(&Value::String("a".to_string()));
+        // The sender is gone, so the stream ends
+        assert!(stream.try_recv().is_err());
+        assert!(routes.requests.is_empty());
+    }
+
+    /// Make a request to the client for call 7 and route it
+    fn route_client_request(routes: &mut Routes) -> std::thread::JoinHandle<Result<Value, String>> {
+        let (writer, mut messages) = MessageWriter::channel();
+        let requests = routes.client_requests.for_request(&serde_json::json!(7));
+        let call =
+            std::thread::spawn(move || requests.send(&writer, "elicitation/create", Value::Null));
+        routes.route(messages.blocking_recv().unwrap());
+        call
+    }
+
+    #[test]
+    fn test_client_request_goes_to_its_call() {
+        let (mut routes, mut stream) = routes_with_request("7", None);
+        let (standalone, mut standalone_stream) = mpsc::unbounded_channel();
+        routes.standalone = Some(standalone);
+
+        let call = route_client_request(&mut routes);
+        let request: Value = serde_json::from_str(&stream.try_recv().unwrap()).unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+        assert!(standalone_stream.try_recv().is_err());
+
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {}});
+        assert!(routes
+            .client_requests
+            .handle_response(&response.to_string()));
+        assert_eq!(call.join().unwrap(), Ok(serde_json::json!({})));
+    }
+
+    #[test]
+    fn test_client_request_without_stream_fails() {
+        // A plain JSON reply can't carry the request, and there is no GET stream
+        let (mut routes, _stream) = routes_with_request("7", None);
+        routes.requests.get_mut("7").unwrap().events = false;
+
+        let started = Instant::now();
+        let result = route_client_request(&mut routes).join().unwrap();
+        assert!(result.unwrap_err().contains("no open stream"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_origin_check() {
+        let allowed = host_allowlist("127.0.0.1:8080".parse().unwrap(), &[]);
+        let mut headers = HeaderMap::new();
+        assert!(check_origin(&headers, &allowed).is_err());
+
+        headers.insert(header::HOST, HeaderValue::from_static("localhost:8080"));
+        assert!(check_origin(&headers, &allowed).is_ok());
+        headers.insert(
+            header::ORIGIN,
+            HeaderValue::from_static("http://127.0.0.1:8080"),
+        );
+        assert!(check_origin(&headers, &allowed).is_ok());
+
+        headers.insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://evil.example"),
+        );
+        assert!(check_origin(&headers, &allowed).is_err());
+
+        // DNS rebinding: the other site's name is both Origin and Host
+        headers.insert(header::HOST, HeaderValue::from_static("evil.example:8080"));
+        headers.insert(
+            header::ORIGIN,
+            HeaderValue::from_static("http://evil.example:8080"),
+        );
+        assert!(check_origin(&headers, &allowed).is_err());
+        headers.remove(header::ORIGIN);
+        assert!(check_origin(&headers, &allowed).is_err());
+    }
+
+    #[test]
+    fn test_host_allowlist() {
+        let any = host_allowlist("0.0.0.0:9000".parse().unwrap(), &["lab-mac".to_string()]);
+        assert_eq!(
+            any,
+            vec![
+                "localhost:9000",
+                "127.0.0.1:9000",
+                "[::1]:9000",
+                "lab-mac:9000"
+            ]
+        );
+
+        let bound = host_allowlist(
+            "192.168.1.5:9000".parse().unwrap(),
+            &["lab-mac:80".to_string(), "[fe80::1]".to_string()],
+        );
+        assert!(bound.contains(&"192.168.1.5:9000".to_string()));
+        assert!(bound.contains(&"lab-mac:80".to_string()));
+        assert!(bound.contains(&"[fe80::1]:9000".to_string()));
+    }
+
+    fn test_state() -> AppState {
+        AppState {
+            settings: MobileDeviceMcpSettings::default(),
+            devices: SharedDevices::default(),
+            sessions: Arc::default(),
+            allowed_hosts: Arc::new(host_allowlist("127.0.0.1:8080".parse().unwrap(), &[])),
+        }
+    }
+
+    fn post_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("127.0.0.1:8080"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_session_stored_after_successful_initialize() {
+        let state = test_state();
+        let invalid =
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"capabilities":7}}"#;
+        let response =
+            handle_post(State(state.clone()), post_headers(), Bytes::from(invalid)).await;
+        assert!(response.headers().get(SESSION_HEADER).is_none());
+        assert!(lock(&state.sessions).is_empty());
+
+        let valid = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
+        let response = handle_post(State(state.clone()), post_headers(), Bytes::from(valid)).await;
+        assert!(response.headers().get(SESSION_HEADER).is_some());
+        assert_eq!(lock(&state.sessions).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_idle_session_expires() {
+        let state = test_state();
+        let (_, session) = new_session(&state);
+        let now = Instant::now();
+        assert!(!session.is_idle(now));
+        assert!(session.is_idle(now + SESSION_IDLE_TIMEOUT));
+
+        // An open stream keeps the session alive
+        let (sender, _receiver) = mpsc::unbounded_channel();
+        lock(&session.routes).standalone = Some(sender);
+        assert!(!session.is_idle(now + SESSION_IDLE_TIMEOUT));
+    }
+}
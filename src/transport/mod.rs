@@ -0,0 +1,9 @@
+// mobile-mcp-zed-extension/src/transport/mod.rs
+// Transports other than stdio
+//
+// The stdio transport is McpServer::run itself; these serve the same
+// McpServer sessions over other channels.
+
+#[cfg(unix)]
+pub mod daemon;
+pub mod http;
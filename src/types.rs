@@ -1,9 +1,9 @@
 // mobile-mcp-zed-extension/src/types.rs
 // Shared types for the Mobile Device MCP Server
 
-#[cfg(feature = "zed-extension")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ============================================================================
 // Settings and Configuration
@@ -16,9 +16,18 @@ pub struct MobileDeviceMcpSettings {
     #[serde(default)]
     pub debug: bool,
 
-    /// Platform to target: "android", "ios", or "auto"
+    /// Platform to target: "android", "ios", "mock", or "auto"
     #[serde(default = "default_platform")]
     pub platform: String,
+
+    /// Run destructive tools on emulators and simulators without asking
+    #[serde(default)]
+    pub auto_approve_virtual_devices: bool,
+
+    /// Short names usable wherever a device id is accepted, e.g.
+    /// {"pixel": "emulator-5554"}
+    #[serde(default)]
+    pub device_aliases: HashMap<String, String>,
 }
 
 fn default_platform() -> String {
@@ -30,6 +39,8 @@ impl Default for MobileDeviceMcpSettings {
         Self {
             debug: false,
             platform: default_platform(),
+            auto_approve_virtual_devices: false,
+            device_aliases: HashMap::new(),
         }
     }
 }
@@ -38,7 +49,7 @@ impl Default for MobileDeviceMcpSettings {
 // Device Information
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeviceInfo {
     pub id: String,
     pub name: String,
@@ -47,6 +58,14 @@ pub struct DeviceInfo {
     pub state: String,
 }
 
+impl DeviceInfo {
+    /// Whether this is an emulator, simulator or mock rather than physical hardware
+    pub fn is_virtual(&self) -> bool {
+        matches!(self.device_type.as_str(), "emulator" | "simulator" | "mock")
+            || (self.platform == "android" && self.id.starts_with("emulator-"))
+    }
+}
+
 // ============================================================================
 // MCP Protocol Structures
 // ============================================================================
@@ -127,6 +146,7 @@ mod tests {
         let settings = MobileDeviceMcpSettings::default();
         assert!(!settings.debug);
         assert_eq!(settings.platform, "auto");
+        assert!(!settings.auto_approve_virtual_devices);
     }
 
     #[test]
@@ -134,6 +154,8 @@ mod tests {
         let settings = MobileDeviceMcpSettings {
             debug: true,
             platform: "android".to_string(),
+            auto_approve_virtual_devices: true,
+            device_aliases: HashMap::new(),
         };
 
         assert!(settings.debug);
@@ -145,6 +167,8 @@ mod tests {
         let settings = MobileDeviceMcpSettings {
             debug: true,
             platform: "ios".to_string(),
+            auto_approve_virtual_devices: false,
+            device_aliases: HashMap::new(),
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -169,6 +193,15 @@ mod tests {
 
         assert!(!settings.debug);
         assert_eq!(settings.platform, "auto");
+        assert!(settings.device_aliases.is_empty());
+    }
+
+    #[test]
+    fn test_settings_device_aliases() {
+        let json = r#"{"device_aliases": {"pixel": "emulator-5554", "iphone": "ABC-123"}}"#;
+        let settings: MobileDeviceMcpSettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.device_aliases["pixel"], "emulator-5554");
+        assert_eq!(settings.device_aliases.len(), 2);
     }
 
     #[test]
@@ -262,11 +295,29 @@ mod tests {
             let settings = MobileDeviceMcpSettings {
                 debug: false,
                 platform: platform.to_string(),
+                auto_approve_virtual_devices: false,
+                device_aliases: HashMap::new(),
             };
             assert_eq!(settings.platform, platform);
         }
     }
 
+    #[test]
+    fn test_device_is_virtual() {
+        let device = |id: &str, platform: &str, device_type: &str| DeviceInfo {
+            id: id.to_string(),
+            name: "Device".to_string(),
+            platform: platform.to_string(),
+            device_type: device_type.to_string(),
+            state: "connected".to_string(),
+        };
+
+        assert!(device("emulator-5554", "android", "mobile").is_virtual());
+        assert!(device("ABCD-1234", "ios", "simulator").is_virtual());
+        assert!(!device("R58M123ABC", "android", "mobile").is_virtual());
+        assert!(!device("00008030-001", "ios", "physical").is_virtual());
+    }
+
     #[test]
     fn test_mcp_response_with_error() {
         let response = McpResponse {